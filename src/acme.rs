@@ -0,0 +1,317 @@
+//! Manage the TXT records of ACME DNS-01 challenges, e.g. to obtain
+//! certificates from Let's Encrypt for names served by PowerDNS.
+//!
+//! A challenge is a TXT record at `_acme-challenge.{name}` holding the key
+//! authorization digest of the order. Certificates for both a name and its
+//! wildcard need two challenges at the same name, so records are added to
+//! and removed from the RRSet individually.
+//!
+//! Certificate automation can use [`PowerDnsSolver`] through the
+//! [`Dns01Solver`] trait, which is shaped after the present and cleanup
+//! steps of ACME clients like instant-acme:
+//!
+//! ```no_run
+//! # async fn example(client: powerdns::Client, digest: &str) -> Result<(), powerdns::Error> {
+//! use powerdns::acme::{Dns01Solver, PowerDnsSolver};
+//!
+//! let solver = PowerDnsSolver::new(&client);
+//! solver.present("www.example.com", digest).await?;
+//! // ... ask the ACME server to validate the challenge
+//! solver.cleanup("www.example.com", digest).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use crate::names::IntoZoneId;
+use crate::rdata::quote_character_string;
+use crate::record_type::RecordType;
+use crate::zones::{absolute_name, ZoneClient};
+use crate::{Client, Error};
+
+/// The TTL of challenge records, short so that a retried validation does not
+/// see a stale token
+pub const CHALLENGE_TTL: u32 = 60;
+
+/// The name of the TXT RRSet validating `domain`, with a trailing dot. A
+/// leading wildcard label is removed, as `*.example.com` is validated at
+/// `_acme-challenge.example.com.`.
+pub fn challenge_name(domain: &str) -> String {
+    let domain = absolute_name(domain);
+    let domain = domain.strip_prefix("*.").unwrap_or(&domain);
+    if domain.starts_with("_acme-challenge.") {
+        domain.to_string()
+    } else {
+        format!("_acme-challenge.{domain}")
+    }
+}
+
+impl ZoneClient<'_> {
+    /// Publish `token`, the digest to present for a DNS-01 challenge for
+    /// `domain`, in the zone `zone_id`. Tokens of other pending challenges
+    /// for the same name are kept.
+    ///
+    /// Adding the record reads the RRSet first, so challenges for the same
+    /// name should not be set concurrently from several processes.
+    pub async fn set_acme_challenge(
+        &self,
+        zone_id: impl IntoZoneId,
+        domain: &str,
+        token: &str,
+    ) -> Result<(), Error> {
+        self.upsert_record(
+            zone_id,
+            &challenge_name(domain),
+            RecordType::TXT,
+            CHALLENGE_TTL,
+            &quote_character_string(token),
+        )
+        .await
+    }
+
+    /// Remove `token` once its challenge completed, deleting the RRSet when
+    /// no other tokens are left. Returns whether the token was published.
+    pub async fn clear_acme_challenge(
+        &self,
+        zone_id: impl IntoZoneId,
+        domain: &str,
+        token: &str,
+    ) -> Result<bool, Error> {
+        self.remove_record(
+            zone_id,
+            &challenge_name(domain),
+            RecordType::TXT,
+            &quote_character_string(token),
+        )
+        .await
+    }
+
+    /// Remove all tokens for `domain`, e.g. those left behind by a client
+    /// that crashed during an order
+    pub async fn clear_acme_challenges(
+        &self,
+        zone_id: impl IntoZoneId,
+        domain: &str,
+    ) -> Result<(), Error> {
+        self.delete_rrset(zone_id, &challenge_name(domain), RecordType::TXT)
+            .await
+    }
+}
+
+/// Publishes and removes the TXT records of DNS-01 challenges. Implemented
+/// by [`PowerDnsSolver`], and by wrappers adding e.g. propagation checks.
+pub trait Dns01Solver {
+    type Error;
+
+    /// Publish `value`, the digest of the key authorization, for `domain`
+    fn present(
+        &self,
+        domain: &str,
+        value: &str,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Remove `value` once the challenge was validated or failed
+    fn cleanup(
+        &self,
+        domain: &str,
+        value: &str,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+/// A [`Dns01Solver`] managing challenges in the zones of a PowerDNS server
+#[derive(Clone, Copy)]
+pub struct PowerDnsSolver<'a> {
+    client: &'a Client,
+    zone: Option<&'a str>,
+}
+
+impl<'a> PowerDnsSolver<'a> {
+    /// A solver adding challenges to the most specific zone containing
+    /// their name, looked up for every challenge
+    pub fn new(client: &'a Client) -> Self {
+        PowerDnsSolver { client, zone: None }
+    }
+
+    /// Add challenges to `zone_id` instead of looking up their zone, e.g.
+    /// when `_acme-challenge` names are delegated to a dedicated zone with
+    /// CNAMEs
+    pub fn with_zone(client: &'a Client, zone_id: &'a str) -> Self {
+        PowerDnsSolver {
+            client,
+            zone: Some(zone_id),
+        }
+    }
+
+    /// The zone the challenge for `domain` is stored in
+    async fn zone_of(&self, domain: &str) -> Result<String, Error> {
+        if let Some(zone) = self.zone {
+            return Ok(zone.to_string());
+        }
+        let name = challenge_name(domain);
+        self.client
+            .zone()
+            .find_zone(&name)
+            .await?
+            .ok_or_else(|| Error::Other(format!("no zone on the server contains {name}").into()))
+    }
+}
+
+impl Dns01Solver for PowerDnsSolver<'_> {
+    type Error = Error;
+
+    async fn present(&self, domain: &str, value: &str) -> Result<(), Error> {
+        let zone = self.zone_of(domain).await?;
+        self.client
+            .zone()
+            .set_acme_challenge(&zone, domain, value)
+            .await
+    }
+
+    async fn cleanup(&self, domain: &str, value: &str) -> Result<(), Error> {
+        let zone = self.zone_of(domain).await?;
+        self.client
+            .zone()
+            .clear_acme_challenge(&zone, domain, value)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::acme::{challenge_name, Dns01Solver, PowerDnsSolver, CHALLENGE_TTL};
+    use crate::record_type::RecordType;
+    use crate::zones::{Zone, ZoneKind};
+
+    #[test]
+    fn name_of_challenge() {
+        assert_eq!(
+            challenge_name("www.example.com"),
+            "_acme-challenge.www.example.com."
+        );
+        assert_eq!(
+            challenge_name("*.example.com."),
+            "_acme-challenge.example.com."
+        );
+        assert_eq!(
+            challenge_name("_acme-challenge.example.com"),
+            "_acme-challenge.example.com."
+        );
+    }
+
+    #[tokio::test]
+    async fn set_and_clear_challenges() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+
+        let zones = client.zone();
+        let tokens = [
+            "LoqXcYV8q5ONbJQxbmR7SCTNo3tiAXDfowyjxAjEuX0",
+            "evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA",
+        ];
+        zones
+            .set_acme_challenge("example.com", "example.com", tokens[0])
+            .await
+            .unwrap();
+        zones
+            .set_acme_challenge("example.com", "*.example.com", tokens[1])
+            .await
+            .unwrap();
+        zones
+            .set_acme_challenge("example.com", "example.com", tokens[0])
+            .await
+            .unwrap();
+
+        let rrset = zones
+            .get_rrset(
+                "example.com",
+                "_acme-challenge.example.com.",
+                RecordType::TXT,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rrset.ttl, CHALLENGE_TTL);
+        let contents: Vec<&str> = rrset
+            .records
+            .iter()
+            .map(|record| record.content.as_str())
+            .collect();
+        assert_eq!(
+            contents,
+            [
+                "\"LoqXcYV8q5ONbJQxbmR7SCTNo3tiAXDfowyjxAjEuX0\"",
+                "\"evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA\""
+            ]
+        );
+
+        assert!(zones
+            .clear_acme_challenge("example.com", "example.com", tokens[0])
+            .await
+            .unwrap());
+        assert!(!zones
+            .clear_acme_challenge("example.com", "example.com", tokens[0])
+            .await
+            .unwrap());
+        let rrsets = server.zone("example.com.").unwrap().rrsets.unwrap();
+        assert_eq!(rrsets[0].records.len(), 1);
+        assert_eq!(
+            rrsets[0].records[0].content,
+            "\"evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA\""
+        );
+
+        zones
+            .clear_acme_challenges("example.com", "example.com")
+            .await
+            .unwrap();
+        assert!(server
+            .zone("example.com.")
+            .unwrap()
+            .rrsets
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn solve_in_most_specific_zone() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        for name in ["example.com.", "dev.example.com."] {
+            let zone = Zone::builder(name).kind(ZoneKind::Native).build().unwrap();
+            client.zone().create(zone).await.unwrap();
+        }
+
+        let solver = PowerDnsSolver::new(&client);
+        solver
+            .present("api.dev.example.com", "digest")
+            .await
+            .unwrap();
+        let rrsets = server.zone("dev.example.com.").unwrap().rrsets.unwrap();
+        assert_eq!(rrsets[0].name, "_acme-challenge.api.dev.example.com.");
+        assert!(server
+            .zone("example.com.")
+            .unwrap()
+            .rrsets
+            .unwrap()
+            .is_empty());
+
+        solver
+            .cleanup("api.dev.example.com", "digest")
+            .await
+            .unwrap();
+        assert!(server
+            .zone("dev.example.com.")
+            .unwrap()
+            .rrsets
+            .unwrap()
+            .is_empty());
+        assert!(solver.present("example.org", "digest").await.is_err());
+    }
+}
@@ -0,0 +1,371 @@
+//! An audit trail of the changes made through a Client, e.g. for compliance
+//! reviews.
+//!
+//! An [`AuditLog`] registered with
+//! [`ClientBuilder::audit_log`](crate::client::ClientBuilder::audit_log)
+//! records every mutating request, whether or not the server accepted it,
+//! with a summary of its payload. Only the names of fields are recorded, so
+//! TSIG secrets and private keys never end up in the log.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A mutating request sent by a Client
+#[serde_with::serde_as]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct AuditRecord {
+    /// When the request was sent, serialized as milliseconds since the epoch
+    #[serde_as(as = "serde_with::TimestampMilliSeconds<i64>")]
+    pub timestamp: SystemTime,
+    /// The id of the server the request was sent to
+    pub server: String,
+    /// The HTTP method, e.g. `PATCH`
+    pub method: String,
+    /// The path of the request below the base URL
+    pub path: String,
+    /// The zone the request changed, if any
+    pub zone: Option<String>,
+    /// The changed fields and rrsets, see [`crate::audit`]
+    pub summary: String,
+    pub result: AuditResult,
+}
+
+/// The outcome of an audited request
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditResult {
+    /// The server answered with this status code
+    Status(u16),
+    /// No response was received, e.g. because the server was unreachable
+    Failed(String),
+}
+
+impl AuditResult {
+    /// Whether the server accepted the request
+    pub fn is_success(&self) -> bool {
+        matches!(self, AuditResult::Status(status) if (200..300).contains(status))
+    }
+}
+
+/// Collects the [`AuditRecord`]s of the Clients it is registered with, in
+/// memory or by writing them to a stream
+pub struct AuditLog {
+    records: Mutex<VecDeque<AuditRecord>>,
+    /// The number of records kept in memory, unlimited when None
+    capacity: Option<usize>,
+    writer: Option<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl AuditLog {
+    /// An audit log keeping all records in memory
+    pub fn new() -> Self {
+        AuditLog {
+            records: Mutex::default(),
+            capacity: None,
+            writer: None,
+        }
+    }
+
+    /// An audit log keeping the latest `capacity` records in memory,
+    /// dropping the oldest ones
+    pub fn with_capacity(capacity: usize) -> Self {
+        AuditLog {
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity: Some(capacity),
+            writer: None,
+        }
+    }
+
+    /// An audit log writing each record to `writer` as a line of JSON,
+    /// without keeping any in memory. Records that cannot be written are
+    /// dropped, as the request was already made.
+    pub fn streaming(writer: impl Write + Send + 'static) -> Self {
+        AuditLog {
+            records: Mutex::default(),
+            capacity: Some(0),
+            writer: Some(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    /// The records kept in memory, oldest first
+    pub fn records(&self) -> Vec<AuditRecord> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.iter().cloned().collect()
+    }
+
+    /// Remove and return the records kept in memory, oldest first
+    pub fn take(&self) -> Vec<AuditRecord> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.drain(..).collect()
+    }
+
+    /// Add a record, e.g. for changes made without a Client
+    pub fn record(&self, record: AuditRecord) {
+        if let Some(writer) = &self.writer {
+            let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+            if let Ok(mut line) = serde_json::to_vec(&record) {
+                line.push(b'\n');
+                let _ = writer.write_all(&line).and_then(|()| writer.flush());
+            }
+        }
+
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        if self.capacity == Some(0) {
+            return;
+        }
+        if self
+            .capacity
+            .is_some_and(|capacity| records.len() >= capacity)
+        {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        AuditLog::new()
+    }
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("capacity", &self.capacity)
+            .field("streaming", &self.writer.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AuditRecord {
+    /// The record of `request` before it is sent, its result is set once it
+    /// is known
+    pub(crate) fn pending(server: &str, request: &reqwest::Request) -> Self {
+        let path = request.url().path().to_string();
+        AuditRecord {
+            timestamp: SystemTime::now(),
+            server: server.to_string(),
+            method: request.method().to_string(),
+            zone: zone_id(&path).map(str::to_string),
+            path,
+            summary: summarize(request.body().and_then(|body| body.as_bytes())),
+            result: AuditResult::Status(0),
+        }
+    }
+}
+
+/// The zone id in paths like `/api/v1/servers/localhost/zones/{zone_id}/...`
+fn zone_id(path: &str) -> Option<&str> {
+    let mut segments = path.split('/');
+    segments.find(|segment| *segment == "zones")?;
+    segments.next().filter(|zone_id| !zone_id.is_empty())
+}
+
+/// Summarize a JSON payload by the names of its fields, and the name and
+/// type of the rrsets it changes, e.g.
+/// `name example.com.; fields kind, nameservers; REPLACE www.example.com. A`
+fn summarize(body: Option<&[u8]>) -> String {
+    let Some(body) = body.filter(|body| !body.is_empty()) else {
+        return String::new();
+    };
+    let Ok(Value::Object(object)) = serde_json::from_slice::<Value>(body) else {
+        return format!("{} bytes", body.len());
+    };
+
+    let mut parts = Vec::new();
+    if let Some(Value::String(name)) = object.get("name") {
+        parts.push(format!("name {name}"));
+    }
+    let fields: Vec<&str> = object
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !matches!(*key, "name" | "rrsets"))
+        .collect();
+    if !fields.is_empty() {
+        parts.push(format!("fields {}", fields.join(", ")));
+    }
+    if let Some(Value::Array(rrsets)) = object.get("rrsets") {
+        fn field<'a>(rrset: &'a Value, key: &str) -> &'a str {
+            rrset.get(key).and_then(Value::as_str).unwrap_or("?")
+        }
+        let changes: Vec<String> = rrsets
+            .iter()
+            .map(|rrset| {
+                let changetype = rrset
+                    .get("changetype")
+                    .and_then(Value::as_str)
+                    .unwrap_or("CREATE");
+                let change = format!(
+                    "{changetype} {} {}",
+                    field(rrset, "name"),
+                    field(rrset, "type")
+                );
+                match rrset.get("records").and_then(Value::as_array) {
+                    Some(records) if changetype != "DELETE" => {
+                        let plural = if records.len() == 1 { "" } else { "s" };
+                        format!("{change} ({} record{plural})", records.len())
+                    }
+                    _ => change,
+                }
+            })
+            .collect();
+        if !changes.is_empty() {
+            parts.push(changes.join(", "));
+        }
+    }
+    parts.join("; ")
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use crate::audit::{summarize, AuditLog, AuditRecord, AuditResult};
+    use crate::cryptokeys::{Cryptokey, KeyType};
+    use crate::record_type::RecordType;
+    use crate::zones::{Zone, ZoneKind};
+    use crate::Client;
+
+    #[test]
+    fn summarize_payload() {
+        let patch = br#"{"rrsets":[
+            {"name":"www.example.com.","type":"A","changetype":"REPLACE","ttl":300,"records":[{"content":"192.0.2.1"}]},
+            {"name":"old.example.com.","type":"AAAA","changetype":"DELETE"}
+        ]}"#;
+        assert_eq!(
+            summarize(Some(patch)),
+            "REPLACE www.example.com. A (1 record), DELETE old.example.com. AAAA"
+        );
+        let key = br#"{"name":"transfer","algorithm":"hmac-sha256","key":"c2VjcmV0"}"#;
+        assert_eq!(summarize(Some(key)), "name transfer; fields algorithm, key");
+        assert_eq!(summarize(Some(b"[1,2]")), "5 bytes");
+        assert_eq!(summarize(None), "");
+    }
+
+    #[tokio::test]
+    async fn record_mutations() {
+        let server = crate::mock::MockServer::start();
+        let audit_log = Arc::new(AuditLog::new());
+        let client = Client::builder()
+            .base_url(server.base_url())
+            .api_key(server.api_key())
+            .audit_log(audit_log.clone())
+            .build()
+            .unwrap();
+
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        client.zone().create(zone.clone()).await.unwrap();
+        assert!(client.zone().create(zone).await.is_err());
+        client
+            .zone()
+            .replace_rrset(
+                "example.com",
+                "www.example.com.",
+                RecordType::A,
+                300,
+                &["192.0.2.1"],
+            )
+            .await
+            .unwrap();
+        client.zone().list().await.unwrap();
+        let cryptokey = Cryptokey {
+            keytype: Some(KeyType::Csk),
+            privatekey: Some(String::from("Private-key-format: v1.2")),
+            ..Default::default()
+        };
+        client
+            .cryptokeys("example.com.")
+            .create(cryptokey)
+            .await
+            .unwrap();
+
+        let records = audit_log.take();
+        let summary: Vec<(&str, Option<&str>, bool)> = records
+            .iter()
+            .map(|record| {
+                (
+                    record.method.as_str(),
+                    record.zone.as_deref(),
+                    record.result.is_success(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("POST", None, true),
+                ("POST", None, false),
+                ("PATCH", Some("example.com."), true),
+                ("POST", Some("example.com."), true),
+            ]
+        );
+        assert_eq!(records[1].result, AuditResult::Status(409));
+        assert_eq!(records[2].summary, "REPLACE www.example.com. A (1 record)");
+        assert!(records[3].summary.contains("privatekey"));
+        assert!(!records[3].summary.contains("Private-key-format"));
+        assert!(audit_log.records().is_empty());
+    }
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn keep_latest_records() {
+        let record = |status| AuditRecord {
+            result: AuditResult::Status(status),
+            ..AuditRecord::pending("localhost", &server_request())
+        };
+        let audit_log = AuditLog::with_capacity(2);
+        for status in [200, 201, 204] {
+            audit_log.record(record(status));
+        }
+        let statuses: Vec<AuditResult> = audit_log
+            .records()
+            .into_iter()
+            .map(|record| record.result)
+            .collect();
+        assert_eq!(
+            statuses,
+            [AuditResult::Status(201), AuditResult::Status(204)]
+        );
+
+        let buffer = Buffer::default();
+        let audit_log = AuditLog::streaming(buffer.clone());
+        audit_log.record(record(204));
+        assert!(audit_log.records().is_empty());
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: AuditRecord = serde_json::from_str(written.trim_end()).unwrap();
+        assert_eq!(line.result, AuditResult::Status(204));
+        assert_eq!(line.zone.as_deref(), Some("example.com."));
+    }
+
+    fn server_request() -> reqwest::Request {
+        reqwest::Client::new()
+            .delete("http://localhost:8081/api/v1/servers/localhost/zones/example.com.")
+            .build()
+            .unwrap()
+    }
+}
@@ -0,0 +1,335 @@
+//! Back up all zones of a server to NDJSON and restore them, e.g. for
+//! disaster recovery.
+//!
+//! A backup has one line per zone, a [`ZoneBackup`] with the rrsets,
+//! metadata and DNSSEC keys of the zone. As every line is a complete zone,
+//! backups can be filtered or split with line-based tools before restoring.
+
+use std::io::{BufRead, Write};
+
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::cryptokeys::Cryptokey;
+use crate::metadata::{Metadata, MetadataKind};
+#[allow(deprecated)]
+use crate::zones::Zone;
+use crate::{Client, Error};
+
+/// Everything needed to recreate a zone, one line of a backup
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[allow(deprecated)]
+pub struct ZoneBackup {
+    /// The zone with its rrsets
+    pub zone: Zone,
+    #[serde(default)]
+    pub metadata: Vec<Metadata>,
+    /// The DNSSEC keys of the zone, with their private keys unless the backup
+    /// was made with [`BackupOptions::skip_private_keys`]
+    #[serde(default)]
+    pub cryptokeys: Vec<Cryptokey>,
+}
+
+/// Options for [`Client::backup`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BackupOptions {
+    /// Leave out the private keys of DNSSEC keys, so the backup contains no
+    /// secret key material. Zones restored from it get new keys.
+    pub skip_private_keys: bool,
+}
+
+/// Options for [`Client::restore`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RestoreOptions {
+    /// Leave zones that already exist untouched instead of failing
+    pub skip_existing: bool,
+}
+
+/// The zones recreated by [`Client::restore`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RestoreReport {
+    pub restored: Vec<String>,
+    /// Zones that already existed, with [`RestoreOptions::skip_existing`]
+    pub skipped: Vec<String>,
+}
+
+#[allow(deprecated)]
+impl Client {
+    /// The backup of a single zone
+    pub async fn backup_zone(
+        &self,
+        zone_id: &str,
+        options: &BackupOptions,
+    ) -> Result<ZoneBackup, Error> {
+        let zone = self.zone().get(zone_id).await?;
+        let zone_id = zone.id.clone().unwrap_or_else(|| zone_id.to_string());
+        let metadata = self.metadata(&zone_id).list().await?;
+
+        let keys = self.cryptokeys(&zone_id);
+        let mut cryptokeys = keys.list().await?;
+        if !options.skip_private_keys {
+            // the private keys are only returned for single keys
+            for cryptokey in &mut cryptokeys {
+                if let Some(id) = cryptokey.id {
+                    *cryptokey = keys.get(id).await?;
+                }
+            }
+        }
+
+        Ok(ZoneBackup {
+            zone,
+            metadata,
+            cryptokeys,
+        })
+    }
+
+    /// Write a backup of all zones to `writer` as NDJSON, one
+    /// [`ZoneBackup`] per line, returning the number of zones. Zones are
+    /// fetched one at a time, so the backup is never held in memory as a
+    /// whole.
+    pub async fn backup(
+        &self,
+        mut writer: impl Write,
+        options: &BackupOptions,
+    ) -> Result<usize, Error> {
+        let zone_client = self.zone();
+        let zones = zone_client.list_stream(None);
+        futures_util::pin_mut!(zones);
+
+        let mut count = 0;
+        while let Some(zone) = zones.next().await {
+            let zone = zone?;
+            let Some(zone_id) = zone.id.or(zone.name) else {
+                continue;
+            };
+            let backup = self.backup_zone(&zone_id, options).await?;
+            serde_json::to_writer(&mut writer, &backup)?;
+            writer.write_all(b"\n")?;
+            count += 1;
+        }
+        writer.flush()?;
+        Ok(count)
+    }
+
+    /// Recreate a zone from its backup, returning false when it already
+    /// exists and [`RestoreOptions::skip_existing`] is set.
+    ///
+    /// DNSSEC keys with a private key are imported. When the zone used
+    /// DNSSEC but the backup has no private keys, the server generates new
+    /// keys instead. Metadata that is part of the zone itself, like
+    /// SOA-EDIT-API, is restored with the zone.
+    pub async fn restore_zone(
+        &self,
+        backup: ZoneBackup,
+        options: &RestoreOptions,
+    ) -> Result<bool, Error> {
+        let ZoneBackup {
+            mut zone,
+            metadata,
+            cryptokeys,
+        } = backup;
+        let Some(name) = zone.name.clone() else {
+            return Err(Error::Other("the backup of a zone has no name".into()));
+        };
+
+        let cryptokeys: Vec<Cryptokey> = cryptokeys
+            .into_iter()
+            .filter(|cryptokey| cryptokey.privatekey.is_some())
+            .collect();
+        if !cryptokeys.is_empty() {
+            // the imported keys replace the ones the server would generate
+            zone.dnssec = None;
+        }
+        zone.id = None;
+        zone.url = None;
+        zone.serial = None;
+        zone.notified_serial = None;
+        zone.edited_serial = None;
+        zone.nameservers = None;
+
+        match self.zone().create(zone).await {
+            Ok(_) => {}
+            Err(e) if e.is_conflict() && options.skip_existing => return Ok(false),
+            Err(e) => return Err(e),
+        }
+
+        let metadata_client = self.metadata(&name);
+        for item in metadata {
+            if !is_zone_setting(&item.kind) {
+                metadata_client.create(item).await?;
+            }
+        }
+        let cryptokey_client = self.cryptokeys(&name);
+        for cryptokey in cryptokeys {
+            cryptokey_client
+                .create(Cryptokey {
+                    type_field: None,
+                    id: None,
+                    dnskey: None,
+                    ds: None,
+                    cds: None,
+                    ..cryptokey
+                })
+                .await?;
+        }
+        Ok(true)
+    }
+
+    /// Recreate the zones of a backup written by [`Client::backup`], one
+    /// zone at a time. Empty lines are ignored. Fails on the first zone that
+    /// cannot be restored, leaving the zones before it restored.
+    pub async fn restore(
+        &self,
+        reader: impl BufRead,
+        options: &RestoreOptions,
+    ) -> Result<RestoreReport, Error> {
+        let mut report = RestoreReport::default();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let backup: ZoneBackup = serde_json::from_str(&line)?;
+            let name = backup.zone.name.clone().unwrap_or_default();
+            if self.restore_zone(backup, options).await? {
+                report.restored.push(name);
+            } else {
+                report.skipped.push(name);
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Metadata kinds that are set through the fields of a [`Zone`], and cannot
+/// be created through the metadata endpoint
+fn is_zone_setting(kind: &MetadataKind) -> bool {
+    matches!(
+        kind,
+        MetadataKind::ApiRectify
+            | MetadataKind::AxfrMasterTsig
+            | MetadataKind::Nsec3Narrow
+            | MetadataKind::Nsec3Param
+            | MetadataKind::Presigned
+            | MetadataKind::SoaEdit
+            | MetadataKind::SoaEditApi
+            | MetadataKind::TsigAllowAxfr
+    ) || kind.as_str() == "LUA-AXFR-SCRIPT"
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::backup::{BackupOptions, RestoreOptions};
+    use crate::cryptokeys::{Cryptokey, KeyType};
+    use crate::metadata::{Metadata, MetadataKind};
+    use crate::record_type::RecordType;
+    use crate::zones::{RRSet, Record, Zone, ZoneKind};
+
+    #[tokio::test]
+    async fn backup_and_restore() {
+        let source = crate::mock::MockServer::start();
+        let client = source.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .rrset(RRSet::new(
+                "www.example.com.",
+                RecordType::A,
+                300,
+                vec![Record::new("192.0.2.1")],
+            ))
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+        let metadata = Metadata {
+            kind: MetadataKind::AlsoNotify,
+            metadata: vec![String::from("192.0.2.53")],
+        };
+        client
+            .metadata("example.com.")
+            .create(metadata.clone())
+            .await
+            .unwrap();
+        let cryptokey = Cryptokey {
+            keytype: Some(KeyType::Csk),
+            active: Some(true),
+            ..Default::default()
+        };
+        client
+            .cryptokeys("example.com.")
+            .create(cryptokey)
+            .await
+            .unwrap();
+
+        let mut backup = Vec::new();
+        let count = client
+            .backup(&mut backup, &BackupOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(backup.iter().filter(|byte| **byte == b'\n').count(), 1);
+
+        let target = crate::mock::MockServer::start();
+        let report = target
+            .client()
+            .restore(backup.as_slice(), &RestoreOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(report.restored, ["example.com."]);
+        let rrsets = target.zone("example.com.").unwrap().rrsets.unwrap();
+        assert_eq!(rrsets[0].records, [Record::new("192.0.2.1")]);
+        let keys = target.cryptokeys("example.com.");
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].privatekey.is_some());
+        let restored = target
+            .client()
+            .metadata("example.com.")
+            .list()
+            .await
+            .unwrap();
+        assert_eq!(restored, [metadata]);
+
+        let options = RestoreOptions {
+            skip_existing: true,
+        };
+        let report = target
+            .client()
+            .restore(backup.as_slice(), &options)
+            .await
+            .unwrap();
+        assert_eq!(report.skipped, ["example.com."]);
+        assert!(target
+            .client()
+            .restore(backup.as_slice(), &RestoreOptions::default())
+            .await
+            .unwrap_err()
+            .is_conflict());
+    }
+
+    #[tokio::test]
+    async fn skip_private_keys() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+        let cryptokey = Cryptokey {
+            keytype: Some(KeyType::Csk),
+            ..Default::default()
+        };
+        client
+            .cryptokeys("example.com.")
+            .create(cryptokey)
+            .await
+            .unwrap();
+
+        let options = BackupOptions {
+            skip_private_keys: true,
+        };
+        let backup = client.backup_zone("example.com", &options).await.unwrap();
+        assert_eq!(backup.cryptokeys.len(), 1);
+        assert!(backup.cryptokeys[0].privatekey.is_none());
+    }
+}
@@ -0,0 +1,1164 @@
+//! A blocking Client, for use in programs that do not otherwise use async.
+//!
+//! The blocking Client shares its implementation with the async
+//! [`crate::Client`], so it offers the same endpoints with the same error
+//! handling, but sends its requests with [`reqwest::blocking`] and waits
+//! for retries and rate limits by sleeping the thread. No tokio runtime is
+//! needed; like [`reqwest::blocking`], the Client must not be used from
+//! within an async runtime.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{BufRead, Write};
+use std::net::IpAddr;
+#[cfg(feature = "yaml")]
+use std::path::Path;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::backup::{BackupOptions, RestoreOptions, RestoreReport, ZoneBackup};
+use crate::caa::Caa;
+use crate::changeset::ChangeSet;
+use crate::chunked::{ChunkOptions, ChunkProgress, ChunkedPatchReport};
+use crate::cryptokeys::Cryptokey;
+#[cfg(feature = "dane")]
+use crate::dane::{Sshfp, Tlsa};
+use crate::diff::{ApplyOptions, ZoneDiff};
+use crate::dnssec::DnssecPolicy;
+use crate::drift::ZoneDrift;
+use crate::dry_run::ZoneDryRun;
+use crate::mail::MailSetup;
+use crate::metadata::{Metadata, MetadataKind};
+#[cfg(feature = "metrics")]
+use crate::metrics::Metric;
+use crate::names::IntoZoneId;
+use crate::ptr::PtrOptions;
+#[cfg(feature = "yaml")]
+use crate::reconcile::{Plan, ReconcileOptions};
+use crate::record_type::RecordType;
+use crate::recursor::{CacheFlushResult, RecursorZone};
+use crate::search::{ObjectType, SearchResult};
+use crate::server::{ApiVersion, Health, Server, Statistic};
+use crate::srv::Srv;
+use crate::tsigkeys::TsigKey;
+#[cfg(feature = "yaml")]
+use crate::yaml::ZoneDefinition;
+#[allow(deprecated)]
+use crate::zones::{
+    CreateZone, GetZoneOptions, PatchZone, RRSet, Zone, ZoneDetail, ZoneKind, ZoneListFilter,
+    ZoneSettings, ZoneSummary,
+};
+use crate::Error;
+
+/// A Client for the PowerDNS HTTP API whose methods block the current thread
+/// until the response arrives
+pub struct Client {
+    inner: crate::Client,
+}
+
+impl Client {
+    /// Create a Client for the webserver at `base_url`, a string or a
+    /// [`reqwest::Url`]. Fails when `base_url` is not an http or https URL or
+    /// `api_token` is not a valid header value.
+    pub fn new(
+        base_url: impl AsRef<str>,
+        server_name: &str,
+        api_token: &str,
+    ) -> Result<Self, Error> {
+        Client::from_builder(
+            crate::Client::builder()
+                .base_url(base_url)
+                .server_name(server_name)
+                .api_key(api_token),
+        )
+    }
+
+    /// Create a Client with the connection options, retry policy, rate
+    /// limits, middleware and observers configured on `builder`
+    pub fn from_builder(builder: crate::client::ClientBuilder) -> Result<Self, Error> {
+        Ok(Client {
+            inner: builder.build_blocking()?,
+        })
+    }
+
+    /// Create a Client reusing an already configured reqwest blocking
+    /// Client, see [`crate::Client::with_http_client`]
+    pub fn with_http_client(
+        http_client: reqwest::blocking::Client,
+        base_url: impl AsRef<str>,
+        server_name: &str,
+        api_key: &str,
+    ) -> Result<Self, Error> {
+        let mut inner = crate::Client::with_http_client(
+            crate::client::request_client(),
+            base_url,
+            server_name,
+            api_key,
+        )?;
+        inner.blocking_http_client = Some(http_client);
+        Ok(Client { inner })
+    }
+
+    /// See [`crate::Client::for_server`]
+    pub fn for_server(&self, server_name: &str) -> Self {
+        Client {
+            inner: self.inner.for_server(server_name),
+        }
+    }
+
+    /// See [`crate::Client::set_api_key`]
+    pub fn set_api_key(&self, api_key: &str) -> Result<(), Error> {
+        self.inner.set_api_key(api_key)
+    }
+
+    /// See [`crate::Client::api_versions`]
+    pub fn api_versions(&self) -> Result<Vec<ApiVersion>, Error> {
+        self.block_on(self.inner.api_versions())
+    }
+
+    /// See [`crate::Client::ping`]
+    pub fn ping(&self) -> Result<(), Error> {
+        self.block_on(self.inner.ping())
+    }
+
+    /// See [`crate::Client::serial_drift`]
+    pub fn serial_drift<'a>(
+        &self,
+        secondaries: impl IntoIterator<Item = &'a Client>,
+    ) -> Result<Vec<ZoneDrift>, Error> {
+        let secondaries = secondaries.into_iter().map(|secondary| &secondary.inner);
+        self.block_on(self.inner.serial_drift(secondaries))
+    }
+
+    /// See [`crate::Client::backup_zone`]
+    pub fn backup_zone(&self, zone_id: &str, options: &BackupOptions) -> Result<ZoneBackup, Error> {
+        self.block_on(self.inner.backup_zone(zone_id, options))
+    }
+
+    /// See [`crate::Client::backup`]
+    pub fn backup(&self, writer: impl Write, options: &BackupOptions) -> Result<usize, Error> {
+        self.block_on(self.inner.backup(writer, options))
+    }
+
+    /// See [`crate::Client::restore_zone`]
+    pub fn restore_zone(
+        &self,
+        backup: ZoneBackup,
+        options: &RestoreOptions,
+    ) -> Result<bool, Error> {
+        self.block_on(self.inner.restore_zone(backup, options))
+    }
+
+    /// See [`crate::Client::restore`]
+    pub fn restore(
+        &self,
+        reader: impl BufRead,
+        options: &RestoreOptions,
+    ) -> Result<RestoreReport, Error> {
+        self.block_on(self.inner.restore(reader, options))
+    }
+
+    /// See [`crate::Client::raw_get`]
+    pub fn raw_get<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        self.block_on(self.inner.raw_get(path))
+    }
+
+    /// See [`crate::Client::raw_post`]
+    pub fn raw_post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, Error> {
+        self.block_on(self.inner.raw_post(path, body))
+    }
+
+    /// See [`crate::Client::raw_put`]
+    pub fn raw_put<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, Error> {
+        self.block_on(self.inner.raw_put(path, body))
+    }
+
+    /// See [`crate::Client::raw_patch`]
+    pub fn raw_patch<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, Error> {
+        self.block_on(self.inner.raw_patch(path, body))
+    }
+
+    /// See [`crate::Client::raw_delete`]
+    pub fn raw_delete<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        self.block_on(self.inner.raw_delete(path))
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        block_on(future)
+    }
+
+    pub fn server(&self) -> ServerClient<'_> {
+        ServerClient { client: self }
+    }
+
+    pub fn zone(&self) -> ZoneClient<'_> {
+        ZoneClient { client: self }
+    }
+
+    pub fn cryptokeys(&self, zone_id: &str) -> CryptokeyClient<'_> {
+        CryptokeyClient {
+            client: self,
+            zone_id: zone_id.to_string(),
+        }
+    }
+
+    pub fn metadata(&self, zone_id: &str) -> MetadataClient<'_> {
+        MetadataClient {
+            client: self,
+            zone_id: zone_id.to_string(),
+        }
+    }
+
+    pub fn tsigkeys(&self) -> TsigKeyClient<'_> {
+        TsigKeyClient { client: self }
+    }
+
+    pub fn recursor(&self) -> RecursorClient<'_> {
+        RecursorClient { client: self }
+    }
+
+    /// See [`crate::Client::search`]
+    pub fn search(
+        &self,
+        query: &str,
+        max: u32,
+        object_type: ObjectType,
+    ) -> Result<Vec<SearchResult>, Error> {
+        self.block_on(self.inner.search(query, max, object_type))
+    }
+}
+
+pub struct ServerClient<'a> {
+    client: &'a Client,
+}
+
+impl ServerClient<'_> {
+    /// See [`crate::server::ServerClient::list`]
+    pub fn list(&self) -> Result<Vec<Server>, Error> {
+        self.client.block_on(self.client.inner.server().list())
+    }
+
+    /// See [`crate::server::ServerClient::get`]
+    pub fn get(&self, server_id: &str) -> Result<Server, Error> {
+        self.client
+            .block_on(self.client.inner.server().get(server_id))
+    }
+
+    /// See [`crate::server::ServerClient::health`]
+    pub fn health(&self) -> Health {
+        self.client.block_on(self.client.inner.server().health())
+    }
+
+    /// See [`crate::server::ServerClient::statistics`]
+    pub fn statistics(
+        &self,
+        statistic: Option<&str>,
+        include_rings: bool,
+    ) -> Result<Vec<Statistic>, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .server()
+                .statistics(statistic, include_rings),
+        )
+    }
+
+    /// See [`crate::server::ServerClient::metrics`]
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Result<Vec<Metric>, Error> {
+        self.client.block_on(self.client.inner.server().metrics())
+    }
+}
+
+pub struct ZoneClient<'a> {
+    client: &'a Client,
+}
+
+#[allow(deprecated)]
+impl ZoneClient<'_> {
+    /// See [`crate::zones::ZoneClient::list`]
+    pub fn list(&self) -> Result<Vec<Zone>, Error> {
+        self.client.block_on(self.client.inner.zone().list())
+    }
+
+    /// See [`crate::zones::ZoneClient::list_summaries`]
+    pub fn list_summaries(&self) -> Result<Vec<ZoneSummary>, Error> {
+        self.client
+            .block_on(self.client.inner.zone().list_summaries())
+    }
+
+    /// See [`crate::zones::ZoneClient::list_filtered`]
+    pub fn list_filtered(&self, filter: &ZoneListFilter) -> Result<Vec<Zone>, Error> {
+        self.client
+            .block_on(self.client.inner.zone().list_filtered(filter))
+    }
+
+    /// See [`crate::zones::ZoneClient::create`]
+    pub fn create(&self, zone: Zone) -> Result<Zone, Error> {
+        self.client.block_on(self.client.inner.zone().create(zone))
+    }
+
+    /// See [`crate::zones::ZoneClient::wait_for_serial`]
+    pub fn wait_for_serial(
+        &self,
+        zone_id: impl IntoZoneId,
+        expected_serial: u32,
+        timeout: Duration,
+    ) -> Result<u32, Error> {
+        self.client
+            .block_on(
+                self.client
+                    .inner
+                    .zone()
+                    .wait_for_serial(zone_id, expected_serial, timeout),
+            )
+    }
+
+    /// See [`crate::zones::ZoneClient::create_zone`]
+    pub fn create_zone(&self, zone: &CreateZone) -> Result<ZoneDetail, Error> {
+        self.client
+            .block_on(self.client.inner.zone().create_zone(zone))
+    }
+
+    /// See [`crate::zones::ZoneClient::create_from_zonefile`]
+    pub fn create_from_zonefile(
+        &self,
+        name: &str,
+        kind: ZoneKind,
+        zonefile: &str,
+    ) -> Result<Zone, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .create_from_zonefile(name, kind, zonefile),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::get`]
+    pub fn get(&self, zone_id: impl IntoZoneId) -> Result<Zone, Error> {
+        self.client.block_on(self.client.inner.zone().get(zone_id))
+    }
+
+    /// See [`crate::zones::ZoneClient::get_with`]
+    pub fn get_with(
+        &self,
+        zone_id: impl IntoZoneId,
+        options: &GetZoneOptions,
+    ) -> Result<Zone, Error> {
+        self.client
+            .block_on(self.client.inner.zone().get_with(zone_id, options))
+    }
+
+    /// See [`crate::zones::ZoneClient::get_detail`]
+    pub fn get_detail(&self, zone_id: impl IntoZoneId) -> Result<ZoneDetail, Error> {
+        self.client
+            .block_on(self.client.inner.zone().get_detail(zone_id))
+    }
+
+    /// See [`crate::zones::ZoneClient::get_many`]
+    pub fn get_many<I>(
+        &self,
+        zone_ids: I,
+        concurrency: usize,
+    ) -> HashMap<String, Result<Zone, Error>>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.client
+            .block_on(self.client.inner.zone().get_many(zone_ids, concurrency))
+    }
+
+    /// See [`crate::zones::ZoneClient::get_if_changed`]
+    pub fn get_if_changed(
+        &self,
+        zone_id: impl IntoZoneId,
+        known_serial: u32,
+    ) -> Result<Option<Zone>, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .get_if_changed(zone_id, known_serial),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::get_cached`]
+    pub fn get_cached(&self, zone_id: impl IntoZoneId) -> Result<Zone, Error> {
+        self.client
+            .block_on(self.client.inner.zone().get_cached(zone_id))
+    }
+
+    /// See [`crate::zones::ZoneClient::delete`]
+    pub fn delete(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().delete(zone_id))
+    }
+
+    /// See [`crate::zones::ZoneClient::update`]
+    pub fn update(&self, zone_id: impl IntoZoneId, settings: ZoneSettings) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().update(zone_id, settings))
+    }
+
+    /// See [`crate::zones::ZoneClient::set_tsig_keys`]
+    pub fn set_tsig_keys(
+        &self,
+        zone_id: impl IntoZoneId,
+        master_keys: &[TsigKey],
+        slave_keys: &[TsigKey],
+    ) -> Result<(), Error> {
+        self.client.block_on(self.client.inner.zone().set_tsig_keys(
+            zone_id,
+            master_keys,
+            slave_keys,
+        ))
+    }
+
+    /// See [`crate::zones::ZoneClient::patch`]
+    pub fn patch(&self, zone_id: impl IntoZoneId, zone: PatchZone) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().patch(zone_id, zone))
+    }
+
+    /// See [`crate::zones::ZoneClient::dry_run`]
+    pub fn dry_run(&self) -> ZoneDryRun<'_> {
+        self.client.inner.zone().dry_run()
+    }
+
+    /// See [`crate::zones::ZoneClient::patch_unchecked`]
+    pub fn patch_unchecked(&self, zone_id: impl IntoZoneId, zone: PatchZone) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().patch_unchecked(zone_id, zone))
+    }
+
+    /// See [`crate::zones::ZoneClient::patch_if_serial`]
+    pub fn patch_if_serial(
+        &self,
+        zone_id: impl IntoZoneId,
+        expected_serial: u32,
+        zone: PatchZone,
+    ) -> Result<(), Error> {
+        self.client
+            .block_on(
+                self.client
+                    .inner
+                    .zone()
+                    .patch_if_serial(zone_id, expected_serial, zone),
+            )
+    }
+
+    /// See [`crate::zones::ZoneClient::notify`]
+    pub fn notify(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().notify(zone_id))
+    }
+
+    /// See [`crate::zones::ZoneClient::axfr_retrieve`]
+    pub fn axfr_retrieve(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().axfr_retrieve(zone_id))
+    }
+
+    /// See [`crate::zones::ZoneClient::export`]
+    pub fn export(&self, zone_id: impl IntoZoneId) -> Result<String, Error> {
+        self.client
+            .block_on(self.client.inner.zone().export(zone_id))
+    }
+
+    /// See [`crate::zones::ZoneClient::rectify`]
+    pub fn rectify(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().rectify(zone_id))
+    }
+
+    /// See [`crate::zones::ZoneClient::get_rrset`]
+    pub fn get_rrset(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+    ) -> Result<Option<RRSet>, Error> {
+        self.client
+            .block_on(self.client.inner.zone().get_rrset(zone_id, name, rtype))
+    }
+
+    /// See [`crate::zones::ZoneClient::replace_rrset`]
+    pub fn replace_rrset(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+        ttl: u32,
+        contents: &[&str],
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .replace_rrset(zone_id, name, rtype, ttl, contents),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::delete_rrset`]
+    pub fn delete_rrset(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+    ) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().delete_rrset(zone_id, name, rtype))
+    }
+
+    /// See [`crate::zones::ZoneClient::commit`]
+    pub fn commit(&self, zone_id: impl IntoZoneId, changes: &ChangeSet) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().commit(zone_id, changes))
+    }
+
+    /// See [`crate::zones::ZoneClient::plan`]
+    #[cfg(feature = "yaml")]
+    pub fn plan(
+        &self,
+        definitions: &[ZoneDefinition],
+        options: &ReconcileOptions,
+    ) -> Result<Plan, Error> {
+        self.client
+            .block_on(self.client.inner.zone().plan(definitions, options))
+    }
+
+    /// See [`crate::zones::ZoneClient::apply_plan`]
+    #[cfg(feature = "yaml")]
+    pub fn apply_plan(&self, plan: &Plan) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().apply_plan(plan))
+    }
+
+    /// See [`crate::zones::ZoneClient::plan_directory`]
+    #[cfg(feature = "yaml")]
+    pub fn plan_directory(
+        &self,
+        path: impl AsRef<Path>,
+        options: &ReconcileOptions,
+    ) -> Result<Plan, Error> {
+        self.client
+            .block_on(self.client.inner.zone().plan_directory(path, options))
+    }
+
+    /// See [`crate::zones::ZoneClient::apply_directory`]
+    #[cfg(feature = "yaml")]
+    pub fn apply_directory(
+        &self,
+        path: impl AsRef<Path>,
+        options: &ReconcileOptions,
+    ) -> Result<Plan, Error> {
+        self.client
+            .block_on(self.client.inner.zone().apply_directory(path, options))
+    }
+
+    /// See [`crate::zones::ZoneClient::patch_chunked`]
+    pub fn patch_chunked(
+        &self,
+        zone_id: impl IntoZoneId,
+        zone: PatchZone,
+        options: &ChunkOptions,
+        progress: impl FnMut(&ChunkProgress),
+    ) -> Result<ChunkedPatchReport, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .patch_chunked(zone_id, zone, options, progress),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::commit_chunked`]
+    pub fn commit_chunked(
+        &self,
+        zone_id: impl IntoZoneId,
+        changes: &ChangeSet,
+        options: &ChunkOptions,
+        progress: impl FnMut(&ChunkProgress),
+    ) -> Result<ChunkedPatchReport, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .commit_chunked(zone_id, changes, options, progress),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::upsert_record`]
+    pub fn upsert_record(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+        ttl: u32,
+        content: &str,
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .upsert_record(zone_id, name, rtype, ttl, content),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::remove_record`]
+    pub fn remove_record(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+        content: &str,
+    ) -> Result<bool, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .remove_record(zone_id, name, rtype, content),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::set_acme_challenge`]
+    pub fn set_acme_challenge(
+        &self,
+        zone_id: impl IntoZoneId,
+        domain: &str,
+        token: &str,
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .set_acme_challenge(zone_id, domain, token),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::clear_acme_challenge`]
+    pub fn clear_acme_challenge(
+        &self,
+        zone_id: impl IntoZoneId,
+        domain: &str,
+        token: &str,
+    ) -> Result<bool, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .clear_acme_challenge(zone_id, domain, token),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::clear_acme_challenges`]
+    pub fn clear_acme_challenges(
+        &self,
+        zone_id: impl IntoZoneId,
+        domain: &str,
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .clear_acme_challenges(zone_id, domain),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::set_up_mail`]
+    pub fn set_up_mail(
+        &self,
+        zone_id: impl IntoZoneId,
+        domain: &str,
+        setup: &MailSetup,
+    ) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().set_up_mail(zone_id, domain, setup))
+    }
+
+    /// See [`crate::zones::ZoneClient::set_caa`]
+    pub fn set_caa(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        records: &[Caa],
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .set_caa(zone_id, name, ttl, records),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::set_caa_issuers`]
+    pub fn set_caa_issuers(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        issuers: &[&str],
+        iodef: Option<&str>,
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .set_caa_issuers(zone_id, name, ttl, issuers, iodef),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::upsert_tlsa`]
+    #[cfg(feature = "dane")]
+    pub fn upsert_tlsa(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        tlsa: &Tlsa,
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .upsert_tlsa(zone_id, name, ttl, tlsa),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::upsert_sshfp`]
+    #[cfg(feature = "dane")]
+    pub fn upsert_sshfp(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        sshfp: &Sshfp,
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .upsert_sshfp(zone_id, name, ttl, sshfp),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::publish_service`]
+    pub fn publish_service(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        instance: &Srv,
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .publish_service(zone_id, name, ttl, instance),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::withdraw_service`]
+    pub fn withdraw_service(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        target: &str,
+        port: u16,
+    ) -> Result<bool, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .withdraw_service(zone_id, name, target, port),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::set_record_disabled`]
+    pub fn set_record_disabled(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+        content: &str,
+        disabled: bool,
+    ) -> Result<bool, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .set_record_disabled(zone_id, name, rtype, content, disabled),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::ensure_rrset`]
+    pub fn ensure_rrset(&self, zone_id: impl IntoZoneId, desired: RRSet) -> Result<bool, Error> {
+        self.client
+            .block_on(self.client.inner.zone().ensure_rrset(zone_id, desired))
+    }
+
+    /// See [`crate::zones::ZoneClient::apply`]
+    pub fn apply(
+        &self,
+        zone_id: impl IntoZoneId,
+        desired: Vec<RRSet>,
+        options: ApplyOptions,
+    ) -> Result<ZoneDiff, Error> {
+        self.client
+            .block_on(self.client.inner.zone().apply(zone_id, desired, options))
+    }
+
+    /// See [`crate::zones::ZoneClient::upsert_address`]
+    pub fn upsert_address(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        address: IpAddr,
+        ttl: u32,
+        options: &PtrOptions,
+    ) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .upsert_address(zone_id, name, address, ttl, options),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::remove_address`]
+    pub fn remove_address(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        address: IpAddr,
+    ) -> Result<bool, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .zone()
+                .remove_address(zone_id, name, address),
+        )
+    }
+
+    /// See [`crate::zones::ZoneClient::secure_zone`]
+    pub fn secure_zone(
+        &self,
+        zone_id: impl IntoZoneId,
+        policy: &DnssecPolicy,
+    ) -> Result<Vec<String>, Error> {
+        self.client
+            .block_on(self.client.inner.zone().secure_zone(zone_id, policy))
+    }
+
+    /// See [`crate::zones::ZoneClient::unsecure_zone`]
+    pub fn unsecure_zone(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.zone().unsecure_zone(zone_id))
+    }
+}
+
+pub struct CryptokeyClient<'a> {
+    client: &'a Client,
+    zone_id: String,
+}
+
+impl CryptokeyClient<'_> {
+    /// See [`crate::cryptokeys::CryptokeyClient::list`]
+    pub fn list(&self) -> Result<Vec<Cryptokey>, Error> {
+        self.client
+            .block_on(self.client.inner.cryptokeys(&self.zone_id).list())
+    }
+
+    /// See [`crate::cryptokeys::CryptokeyClient::get`]
+    pub fn get(&self, cryptokey_id: u64) -> Result<Cryptokey, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .cryptokeys(&self.zone_id)
+                .get(cryptokey_id),
+        )
+    }
+
+    /// See [`crate::cryptokeys::CryptokeyClient::create`]
+    pub fn create(&self, cryptokey: Cryptokey) -> Result<Cryptokey, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .cryptokeys(&self.zone_id)
+                .create(cryptokey),
+        )
+    }
+
+    /// See [`crate::cryptokeys::CryptokeyClient::update`]
+    pub fn update(&self, cryptokey_id: u64, cryptokey: Cryptokey) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .cryptokeys(&self.zone_id)
+                .update(cryptokey_id, cryptokey),
+        )
+    }
+
+    /// See [`crate::cryptokeys::CryptokeyClient::activate`]
+    pub fn activate(&self, cryptokey_id: u64) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .cryptokeys(&self.zone_id)
+                .activate(cryptokey_id),
+        )
+    }
+
+    /// See [`crate::cryptokeys::CryptokeyClient::deactivate`]
+    pub fn deactivate(&self, cryptokey_id: u64) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .cryptokeys(&self.zone_id)
+                .deactivate(cryptokey_id),
+        )
+    }
+
+    /// See [`crate::cryptokeys::CryptokeyClient::publish`]
+    pub fn publish(&self, cryptokey_id: u64) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .cryptokeys(&self.zone_id)
+                .publish(cryptokey_id),
+        )
+    }
+
+    /// See [`crate::cryptokeys::CryptokeyClient::unpublish`]
+    pub fn unpublish(&self, cryptokey_id: u64) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .cryptokeys(&self.zone_id)
+                .unpublish(cryptokey_id),
+        )
+    }
+
+    /// See [`crate::cryptokeys::CryptokeyClient::delete`]
+    pub fn delete(&self, cryptokey_id: u64) -> Result<(), Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .cryptokeys(&self.zone_id)
+                .delete(cryptokey_id),
+        )
+    }
+}
+
+pub struct MetadataClient<'a> {
+    client: &'a Client,
+    zone_id: String,
+}
+
+impl MetadataClient<'_> {
+    /// See [`crate::metadata::MetadataClient::list`]
+    pub fn list(&self) -> Result<Vec<Metadata>, Error> {
+        self.client
+            .block_on(self.client.inner.metadata(&self.zone_id).list())
+    }
+
+    /// See [`crate::metadata::MetadataClient::create`]
+    pub fn create(&self, metadata: Metadata) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.metadata(&self.zone_id).create(metadata))
+    }
+
+    /// See [`crate::metadata::MetadataClient::get`]
+    pub fn get(&self, kind: &MetadataKind) -> Result<Metadata, Error> {
+        self.client
+            .block_on(self.client.inner.metadata(&self.zone_id).get(kind))
+    }
+
+    /// See [`crate::metadata::MetadataClient::update`]
+    pub fn update(&self, metadata: Metadata) -> Result<Metadata, Error> {
+        self.client
+            .block_on(self.client.inner.metadata(&self.zone_id).update(metadata))
+    }
+
+    /// See [`crate::metadata::MetadataClient::delete`]
+    pub fn delete(&self, kind: &MetadataKind) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.metadata(&self.zone_id).delete(kind))
+    }
+}
+
+pub struct TsigKeyClient<'a> {
+    client: &'a Client,
+}
+
+impl TsigKeyClient<'_> {
+    /// See [`crate::tsigkeys::TsigKeyClient::list`]
+    pub fn list(&self) -> Result<Vec<TsigKey>, Error> {
+        self.client.block_on(self.client.inner.tsigkeys().list())
+    }
+
+    /// See [`crate::tsigkeys::TsigKeyClient::get`]
+    pub fn get(&self, tsigkey_id: &str) -> Result<TsigKey, Error> {
+        self.client
+            .block_on(self.client.inner.tsigkeys().get(tsigkey_id))
+    }
+
+    /// See [`crate::tsigkeys::TsigKeyClient::create`]
+    pub fn create(&self, tsigkey: TsigKey) -> Result<TsigKey, Error> {
+        self.client
+            .block_on(self.client.inner.tsigkeys().create(tsigkey))
+    }
+
+    /// See [`crate::tsigkeys::TsigKeyClient::update`]
+    pub fn update(&self, tsigkey_id: &str, tsigkey: TsigKey) -> Result<TsigKey, Error> {
+        self.client
+            .block_on(self.client.inner.tsigkeys().update(tsigkey_id, tsigkey))
+    }
+
+    /// See [`crate::tsigkeys::TsigKeyClient::delete`]
+    pub fn delete(&self, tsigkey_id: &str) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.tsigkeys().delete(tsigkey_id))
+    }
+}
+
+pub struct RecursorClient<'a> {
+    client: &'a Client,
+}
+
+impl RecursorClient<'_> {
+    /// See [`crate::recursor::RecursorClient::list_zones`]
+    pub fn list_zones(&self) -> Result<Vec<RecursorZone>, Error> {
+        self.client
+            .block_on(self.client.inner.recursor().list_zones())
+    }
+
+    /// See [`crate::recursor::RecursorClient::get_zone`]
+    pub fn get_zone(&self, zone_id: &str) -> Result<RecursorZone, Error> {
+        self.client
+            .block_on(self.client.inner.recursor().get_zone(zone_id))
+    }
+
+    /// See [`crate::recursor::RecursorClient::create_zone`]
+    pub fn create_zone(&self, zone: RecursorZone) -> Result<RecursorZone, Error> {
+        self.client
+            .block_on(self.client.inner.recursor().create_zone(zone))
+    }
+
+    /// See [`crate::recursor::RecursorClient::update_zone`]
+    pub fn update_zone(&self, zone_id: &str, zone: RecursorZone) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.recursor().update_zone(zone_id, zone))
+    }
+
+    /// See [`crate::recursor::RecursorClient::delete_zone`]
+    pub fn delete_zone(&self, zone_id: &str) -> Result<(), Error> {
+        self.client
+            .block_on(self.client.inner.recursor().delete_zone(zone_id))
+    }
+
+    /// See [`crate::recursor::RecursorClient::flush_cache`]
+    pub fn flush_cache(&self, domain: &str, subtree: bool) -> Result<CacheFlushResult, Error> {
+        self.client
+            .block_on(self.client.inner.recursor().flush_cache(domain, subtree))
+    }
+
+    /// See [`crate::recursor::RecursorClient::statistics`]
+    pub fn statistics(
+        &self,
+        statistic: Option<&str>,
+        include_rings: bool,
+    ) -> Result<Vec<Statistic>, Error> {
+        self.client.block_on(
+            self.client
+                .inner
+                .recursor()
+                .statistics(statistic, include_rings),
+        )
+    }
+
+    /// See [`crate::recursor::RecursorClient::metrics`]
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Result<Vec<Metric>, Error> {
+        self.client.block_on(self.client.inner.recursor().metrics())
+    }
+}
+
+/// Wakes the thread polling a future in [`block_on`]
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Poll `future` on the current thread until it completes. The futures of a
+/// blocking Client only wait on the blocking requests, sleeps and
+/// semaphores, so this needs no reactor or timer.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::blocking::Client;
+    use crate::zones::{Zone, ZoneKind};
+
+    #[test]
+    fn unreachable_server() {
+        let client = Client::new("http://127.0.0.1:9", "localhost", "secret").unwrap();
+
+        let zone = client.zone().create(Zone::default());
+
+        assert!(matches!(zone, Err(crate::Error::RequestError(_))));
+    }
+
+    #[test]
+    fn create_and_get_zone() {
+        let server = crate::mock::MockServer::start();
+        let client = Client::new(server.base_url(), "localhost", server.api_key()).unwrap();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+
+        client.zone().create(zone).unwrap();
+
+        assert_eq!(
+            client.zone().get("example.com").unwrap().name.as_deref(),
+            Some("example.com.")
+        );
+        assert!(Client::new("ftp://example.com", "localhost", "secret").is_err());
+    }
+}
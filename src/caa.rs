@@ -0,0 +1,353 @@
+//! Build and validate CAA records, which restrict the certificate
+//! authorities allowed to issue certificates for a name (RFC 8659).
+//!
+//! ```
+//! use powerdns::caa::Caa;
+//!
+//! let caa = Caa::issue("letsencrypt.org").parameter("validationmethods", "dns-01");
+//! assert_eq!(caa.to_string(), "0 issue \"letsencrypt.org; validationmethods=dns-01\"");
+//! assert!(caa.validate().is_ok());
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::names::IntoZoneId;
+use crate::rdata::{RData, RDataError};
+use crate::record_type::RecordType;
+use crate::zones::ZoneClient;
+use crate::Error;
+
+/// The flag making CAs that do not understand the tag of a record refuse
+/// to issue
+pub const CRITICAL: u8 = 128;
+
+/// A single CAA record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caa {
+    pub flags: u8,
+    /// The property, e.g. `issue`
+    pub tag: String,
+    pub value: String,
+}
+
+/// Returned when a CAA record does not follow the syntax of RFC 8659
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CaaError {
+    #[error("flags {0} set bits other than the critical flag")]
+    ReservedFlags(u8),
+
+    #[error("tag {0:?} is not 1 to 15 ASCII letters and digits")]
+    InvalidTag(String),
+
+    #[error("issuer {0:?} is not a domain name")]
+    InvalidIssuer(String),
+
+    #[error("parameter {0:?} is not of the form tag=value")]
+    InvalidParameter(String),
+
+    #[error("iodef {0:?} is not a mailto:, http: or https: URL")]
+    InvalidIodef(String),
+}
+
+impl Caa {
+    /// Allow `issuer`, the domain of a CA like `letsencrypt.org`, to issue
+    /// certificates for the name and its wildcard
+    pub fn issue(issuer: &str) -> Self {
+        Caa::new("issue", issuer)
+    }
+
+    /// Allow `issuer` to issue wildcard certificates, overriding the
+    /// `issue` records for them
+    pub fn issuewild(issuer: &str) -> Self {
+        Caa::new("issuewild", issuer)
+    }
+
+    /// Forbid all CAs from issuing certificates
+    pub fn forbid_issue() -> Self {
+        Caa::new("issue", ";")
+    }
+
+    /// Forbid all CAs from issuing wildcard certificates
+    pub fn forbid_issuewild() -> Self {
+        Caa::new("issuewild", ";")
+    }
+
+    /// Report requests violating the policy to `url`, e.g.
+    /// `mailto:security@example.com`
+    pub fn iodef(url: &str) -> Self {
+        Caa::new("iodef", url)
+    }
+
+    fn new(tag: &str, value: &str) -> Self {
+        Caa {
+            flags: 0,
+            tag: tag.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Set the critical flag
+    pub fn critical(mut self) -> Self {
+        self.flags |= CRITICAL;
+        self
+    }
+
+    /// Add a parameter to an `issue` or `issuewild` record, e.g.
+    /// `validationmethods=dns-01` or the `accounturi` of RFC 8657
+    pub fn parameter(mut self, tag: &str, value: &str) -> Self {
+        self.value.push_str(&format!("; {tag}={value}"));
+        self
+    }
+
+    pub fn is_critical(&self) -> bool {
+        self.flags & CRITICAL != 0
+    }
+
+    /// The issuer of an `issue` or `issuewild` record, None when it forbids
+    /// issuance
+    pub fn issuer(&self) -> Option<&str> {
+        let issuer = self.value.split(';').next()?.trim();
+        (!issuer.is_empty()).then_some(issuer)
+    }
+
+    /// Check the flags and the syntax of the tag and, for the tags defined
+    /// by RFC 8659, of the value. Values of other tags are not checked.
+    pub fn validate(&self) -> Result<(), CaaError> {
+        if self.flags & !CRITICAL != 0 {
+            return Err(CaaError::ReservedFlags(self.flags));
+        }
+        let tag_is_valid = (1..=15).contains(&self.tag.len())
+            && self.tag.bytes().all(|b| b.is_ascii_alphanumeric());
+        if !tag_is_valid {
+            return Err(CaaError::InvalidTag(self.tag.clone()));
+        }
+
+        match self.tag.to_ascii_lowercase().as_str() {
+            "issue" | "issuewild" => {
+                let mut parts = self.value.split(';');
+                let issuer = parts.next().unwrap_or_default().trim();
+                if !issuer.is_empty() && !is_issuer(issuer) {
+                    return Err(CaaError::InvalidIssuer(issuer.to_string()));
+                }
+                for parameter in parts.map(str::trim).filter(|p| !p.is_empty()) {
+                    if !is_parameter(parameter) {
+                        return Err(CaaError::InvalidParameter(parameter.to_string()));
+                    }
+                }
+            }
+            "iodef" => {
+                let valid = ["mailto:", "http://", "https://"].iter().any(|scheme| {
+                    self.value.len() > scheme.len()
+                        && self
+                            .value
+                            .get(..scheme.len())
+                            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+                });
+                if !valid || self.value.contains(char::is_whitespace) {
+                    return Err(CaaError::InvalidIodef(self.value.clone()));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Format the record as content in the format PowerDNS expects
+    pub fn to_content(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Whether `issuer` is a domain name of letters, digits and hyphens
+fn is_issuer(issuer: &str) -> bool {
+    issuer.split('.').all(|label| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    })
+}
+
+fn is_parameter(parameter: &str) -> bool {
+    let Some((tag, value)) = parameter.split_once('=') else {
+        return false;
+    };
+    let tag = tag.trim();
+    let value = value.trim();
+    !tag.is_empty()
+        && tag.bytes().all(|b| b.is_ascii_alphanumeric())
+        && value.bytes().all(|b| b.is_ascii_graphic() && b != b';')
+}
+
+impl fmt::Display for Caa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&RData::from(self.clone()).to_content())
+    }
+}
+
+impl FromStr for Caa {
+    type Err = RDataError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        match RData::parse(&RecordType::CAA, content)? {
+            RData::CAA { flags, tag, value } => Ok(Caa { flags, tag, value }),
+            _ => unreachable!("CAA content parses as RData::CAA"),
+        }
+    }
+}
+
+impl From<Caa> for RData {
+    fn from(caa: Caa) -> Self {
+        RData::CAA {
+            flags: caa.flags,
+            tag: caa.tag,
+            value: caa.value,
+        }
+    }
+}
+
+impl ZoneClient<'_> {
+    /// Replace the CAA RRSet of `name` with `records`, after validating
+    /// them. An empty list deletes the RRSet, allowing all CAs again.
+    pub async fn set_caa(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        records: &[Caa],
+    ) -> Result<(), Error> {
+        for record in records {
+            record.validate()?;
+        }
+        if records.is_empty() {
+            return self.delete_rrset(zone_id, name, RecordType::CAA).await;
+        }
+        let contents: Vec<String> = records.iter().map(Caa::to_content).collect();
+        let contents: Vec<&str> = contents.iter().map(String::as_str).collect();
+        self.replace_rrset(zone_id, name, RecordType::CAA, ttl, &contents)
+            .await
+    }
+
+    /// Allow only `issuers` to issue certificates for `name`, and report
+    /// violations to `iodef` if given. Without issuers, issuance is
+    /// forbidden.
+    pub async fn set_caa_issuers(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        issuers: &[&str],
+        iodef: Option<&str>,
+    ) -> Result<(), Error> {
+        let mut records: Vec<Caa> = issuers.iter().map(|issuer| Caa::issue(issuer)).collect();
+        if records.is_empty() {
+            records.push(Caa::forbid_issue());
+        }
+        records.extend(iodef.map(Caa::iodef));
+        self.set_caa(zone_id, name, ttl, &records).await
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::caa::{Caa, CaaError};
+    use crate::record_type::RecordType;
+    use crate::zones::{Zone, ZoneKind};
+    use crate::Error;
+
+    #[test]
+    fn build_and_validate() {
+        let caa = Caa::issuewild("letsencrypt.org").critical();
+        assert_eq!(caa.to_content(), "128 issuewild \"letsencrypt.org\"");
+        assert_eq!(caa.to_content().parse::<Caa>().unwrap(), caa);
+        assert_eq!(caa.issuer(), Some("letsencrypt.org"));
+        assert_eq!(Caa::forbid_issue().to_content(), "0 issue \";\"");
+        assert_eq!(Caa::forbid_issue().issuer(), None);
+
+        assert!(Caa::forbid_issue().validate().is_ok());
+        assert!(Caa::iodef("mailto:security@example.com").validate().is_ok());
+        assert_eq!(
+            Caa::issue("lets encrypt").validate(),
+            Err(CaaError::InvalidIssuer(String::from("lets encrypt")))
+        );
+        assert_eq!(
+            Caa::issue("ca.example")
+                .parameter("bad tag", "x")
+                .validate(),
+            Err(CaaError::InvalidParameter(String::from("bad tag=x")))
+        );
+        assert!(matches!(
+            Caa::iodef("security@example.com").validate(),
+            Err(CaaError::InvalidIodef(_))
+        ));
+        let mut caa = Caa::issue("ca.example");
+        caa.flags = 1;
+        assert_eq!(caa.validate(), Err(CaaError::ReservedFlags(1)));
+        caa.flags = 0;
+        caa.tag = String::from("is-sue");
+        assert!(matches!(caa.validate(), Err(CaaError::InvalidTag(_))));
+    }
+
+    #[tokio::test]
+    async fn set_issuers() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+
+        let zones = client.zone();
+        zones
+            .set_caa_issuers(
+                "example.com",
+                "example.com",
+                3600,
+                &["letsencrypt.org", "sectigo.com"],
+                Some("mailto:security@example.com"),
+            )
+            .await
+            .unwrap();
+        let rrset = zones
+            .get_rrset("example.com", "example.com", RecordType::CAA)
+            .await
+            .unwrap()
+            .unwrap();
+        let contents: Vec<&str> = rrset
+            .records
+            .iter()
+            .map(|record| record.content.as_str())
+            .collect();
+        assert_eq!(
+            contents,
+            [
+                "0 issue \"letsencrypt.org\"",
+                "0 issue \"sectigo.com\"",
+                "0 iodef \"mailto:security@example.com\""
+            ]
+        );
+
+        let invalid = zones
+            .set_caa_issuers("example.com", "example.com", 3600, &["-ca"], None)
+            .await;
+        assert!(matches!(invalid, Err(Error::InvalidCaa(_))));
+
+        zones
+            .set_caa("example.com", "example.com", 3600, &[])
+            .await
+            .unwrap();
+        assert!(server
+            .zone("example.com.")
+            .unwrap()
+            .rrsets
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,289 @@
+//! Queue changes to many rrsets of a zone and commit them at once, so
+//! related changes are applied atomically in a single PATCH.
+
+use crate::names::IntoZoneId;
+use crate::record_type::RecordType;
+use crate::zones::{
+    absolute_name, PatchZone, RRSet, RRSetUpdate, Record, ReplaceRRSet, ZoneClient,
+};
+use crate::Error;
+
+/// Changes to the rrsets of a zone, sent with [`ZoneClient::commit`].
+///
+/// ```
+/// use powerdns::changeset::ChangeSet;
+/// use powerdns::record_type::RecordType;
+///
+/// let changes = ChangeSet::new()
+///     .add("www.example.com", RecordType::A, 300, "192.0.2.2")
+///     .replace("mail.example.com", RecordType::MX, 3600, &["10 mx.example.com."])
+///     .delete("old.example.com", RecordType::CNAME);
+/// assert_eq!(changes.len(), 3);
+/// ```
+///
+/// Changes to the same RRSet are combined in the order they were queued, so
+/// an RRSet is changed at most once per commit: adding a record after
+/// replacing an RRSet adds it to the replacement, and a later replace or
+/// delete discards the earlier changes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ChangeSet {
+    changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Change {
+    name: String,
+    rtype: RecordType,
+    action: Action,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    /// Records added to the ones currently in the RRSet
+    Add {
+        ttl: u32,
+        records: Vec<Record>,
+    },
+    Replace {
+        ttl: u32,
+        records: Vec<Record>,
+    },
+    Delete,
+}
+
+impl ChangeSet {
+    pub fn new() -> Self {
+        ChangeSet::default()
+    }
+
+    /// Add a record to the RRSet with the given name and type, keeping the
+    /// records already in it, and set the TTL of the RRSet to `ttl`. The
+    /// RRSet is created if it does not exist yet.
+    pub fn add(mut self, name: &str, rtype: RecordType, ttl: u32, content: &str) -> Self {
+        let record = Record::new(content);
+        let action = match self.take(name, &rtype) {
+            Some(Action::Add { mut records, .. }) => {
+                add_record(&mut records, record);
+                Action::Add { ttl, records }
+            }
+            Some(Action::Replace { mut records, .. }) => {
+                add_record(&mut records, record);
+                Action::Replace { ttl, records }
+            }
+            Some(Action::Delete) => Action::Replace {
+                ttl,
+                records: vec![record],
+            },
+            None => Action::Add {
+                ttl,
+                records: vec![record],
+            },
+        };
+        self.push(name, rtype, action)
+    }
+
+    /// Replace the RRSet with the given name and type with one record per
+    /// entry of `contents`, creating it if it does not exist
+    pub fn replace(mut self, name: &str, rtype: RecordType, ttl: u32, contents: &[&str]) -> Self {
+        self.take(name, &rtype);
+        let records = contents
+            .iter()
+            .map(|content| Record::new(content))
+            .collect();
+        self.push(name, rtype, Action::Replace { ttl, records })
+    }
+
+    /// Delete the RRSet with the given name and type
+    pub fn delete(mut self, name: &str, rtype: RecordType) -> Self {
+        self.take(name, &rtype);
+        self.push(name, rtype, Action::Delete)
+    }
+
+    /// The number of rrsets that are changed
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether committing needs the current rrsets of the zone, which is
+    /// the case when records are added
+    pub fn adds_records(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|change| matches!(change.action, Action::Add { .. }))
+    }
+
+    /// The patch applying the changes to a zone with the `current` rrsets,
+    /// which are only used for added records
+    pub fn to_patch(&self, current: &[RRSet]) -> PatchZone {
+        self.changes
+            .iter()
+            .map(|change| match &change.action {
+                Action::Add { ttl, records } => {
+                    let mut merged = current
+                        .iter()
+                        .find(|rrset| {
+                            absolute_name(&rrset.name) == change.name
+                                && rrset.type_field == change.rtype
+                        })
+                        .map(|rrset| rrset.records.clone())
+                        .unwrap_or_default();
+                    for record in records {
+                        add_record(&mut merged, record.clone());
+                    }
+                    replace(change, *ttl, merged)
+                }
+                Action::Replace { ttl, records } => replace(change, *ttl, records.clone()),
+                Action::Delete => RRSetUpdate::delete(&change.name, change.rtype.clone()),
+            })
+            .collect()
+    }
+
+    /// Remove the queued change of an RRSet, returning its action
+    fn take(&mut self, name: &str, rtype: &RecordType) -> Option<Action> {
+        let name = absolute_name(name);
+        let index = self
+            .changes
+            .iter()
+            .position(|change| change.name == name && change.rtype == *rtype)?;
+        Some(self.changes.remove(index).action)
+    }
+
+    fn push(mut self, name: &str, rtype: RecordType, action: Action) -> Self {
+        self.changes.push(Change {
+            name: absolute_name(name),
+            rtype,
+            action,
+        });
+        self
+    }
+}
+
+#[allow(deprecated)]
+impl ZoneClient<'_> {
+    /// Apply all changes of `changes` to a zone in a single PATCH, so either
+    /// all or none of them are applied. When records are added, the zone is
+    /// fetched first to keep the records already in their rrsets. Committing
+    /// an empty ChangeSet does not send any request. See
+    /// [`ZoneClient::commit_chunked`] for change sets too large for a single
+    /// request.
+    pub async fn commit(&self, zone_id: impl IntoZoneId, changes: &ChangeSet) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let current = if changes.adds_records() {
+            self.get(&zone_id).await?.rrsets.unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        self.patch(&zone_id, changes.to_patch(&current)).await
+    }
+}
+
+fn replace(change: &Change, ttl: u32, records: Vec<Record>) -> RRSetUpdate {
+    RRSetUpdate::Replace(ReplaceRRSet {
+        name: change.name.clone(),
+        rtype: change.rtype.clone(),
+        ttl,
+        records,
+        comments: None,
+    })
+}
+
+/// Add `record` to `records`, unless a record with the same content exists
+fn add_record(records: &mut Vec<Record>, record: Record) {
+    if !records
+        .iter()
+        .any(|existing| existing.content == record.content)
+    {
+        records.push(record);
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::changeset::ChangeSet;
+    use crate::record_type::RecordType;
+    use crate::zones::{RRSet, RRSetUpdate, Record, Zone, ZoneKind};
+
+    #[test]
+    fn combine_changes() {
+        let changes = ChangeSet::new()
+            .replace("www.example.com", RecordType::A, 300, &["192.0.2.1"])
+            .add("WWW.example.com.", RecordType::A, 600, "192.0.2.2")
+            .add("www.example.com", RecordType::A, 600, "192.0.2.2")
+            .add("old.example.com", RecordType::A, 300, "192.0.2.3")
+            .delete("old.example.com", RecordType::A)
+            .delete("new.example.com", RecordType::A)
+            .add("new.example.com", RecordType::A, 300, "192.0.2.4");
+        assert_eq!(changes.len(), 3);
+        assert!(!changes.adds_records());
+
+        let patch = changes.to_patch(&[]);
+        let RRSetUpdate::Replace(www) = &patch.rrsets[0] else {
+            panic!("www.example.com. is not replaced");
+        };
+        assert_eq!(www.name, "www.example.com.");
+        assert_eq!(www.ttl, 600);
+        assert_eq!(
+            www.records,
+            [Record::new("192.0.2.1"), Record::new("192.0.2.2")]
+        );
+        assert_eq!(
+            patch.rrsets[1],
+            RRSetUpdate::delete("old.example.com.", RecordType::A)
+        );
+        assert!(
+            matches!(&patch.rrsets[2], RRSetUpdate::Replace(rrset) if rrset.records.len() == 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn commit_changes() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .rrset(RRSet::new(
+                "www.example.com.",
+                RecordType::A,
+                300,
+                vec![Record::new("192.0.2.1")],
+            ))
+            .rrset(RRSet::new(
+                "old.example.com.",
+                RecordType::CNAME,
+                300,
+                vec![Record::new("www.example.com.")],
+            ))
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+
+        let changes = ChangeSet::new()
+            .add("www.example.com", RecordType::A, 300, "192.0.2.2")
+            .replace("mail.example.com", RecordType::A, 300, &["192.0.2.25"])
+            .delete("old.example.com", RecordType::CNAME);
+        client.zone().commit("example.com", &changes).await.unwrap();
+        client
+            .zone()
+            .commit("example.com", &ChangeSet::new())
+            .await
+            .unwrap();
+
+        let rrsets = server.zone("example.com.").unwrap().rrsets.unwrap();
+        let find = |name: &str| rrsets.iter().find(|rrset| rrset.name == name);
+        assert_eq!(find("www.example.com.").unwrap().records.len(), 2);
+        assert_eq!(
+            find("mail.example.com.").unwrap().records,
+            [Record::new("192.0.2.25")]
+        );
+        assert!(find("old.example.com.").is_none());
+    }
+}
@@ -0,0 +1,303 @@
+//! Split very large patches into several smaller PATCH requests, e.g. to
+//! stay below the body limit of a proxy or to avoid locking the backend for
+//! a long time.
+
+use std::collections::HashMap;
+
+use crate::changeset::ChangeSet;
+use crate::names::IntoZoneId;
+use crate::zones::{PatchZone, RRSetUpdate, ZoneClient};
+use crate::Error;
+
+/// Options for [`ZoneClient::patch_chunked`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkOptions {
+    /// The number of rrsets sent per request, defaults to 1000
+    pub batch_size: usize,
+    /// What to do when a chunk is rejected
+    pub on_error: OnChunkError,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        ChunkOptions {
+            batch_size: 1000,
+            on_error: OnChunkError::FailFast,
+        }
+    }
+}
+
+/// What [`ZoneClient::patch_chunked`] does when a chunk is rejected
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OnChunkError {
+    /// Return the error without sending the remaining chunks
+    #[default]
+    FailFast,
+    /// Send the remaining chunks, and report the failed ones
+    Continue,
+}
+
+/// Passed to the progress callback of [`ZoneClient::patch_chunked`] after
+/// each chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkProgress {
+    /// The number of chunks sent so far, including the current one
+    pub sent: usize,
+    pub chunks: usize,
+    /// The number of rrsets in the chunks sent so far
+    pub rrsets_sent: usize,
+    pub rrsets: usize,
+    /// Whether the current chunk was applied
+    pub applied: bool,
+}
+
+/// The outcome of [`ZoneClient::patch_chunked`]
+#[derive(Debug, Default)]
+pub struct ChunkedPatchReport {
+    pub chunks: usize,
+    /// The number of rrsets in the applied chunks
+    pub applied: usize,
+    /// The chunks the server rejected, only with [`OnChunkError::Continue`]
+    pub failed: Vec<FailedChunk>,
+}
+
+/// A chunk rejected by the server, which can be sent again with
+/// [`ZoneClient::patch`]
+#[derive(Debug)]
+pub struct FailedChunk {
+    /// The position of the chunk, starting at 0
+    pub index: usize,
+    pub patch: PatchZone,
+    pub error: Error,
+}
+
+impl ChunkedPatchReport {
+    /// Whether all chunks were applied
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl PatchZone {
+    /// Split the patch into patches of at most `batch_size` rrsets. All
+    /// changes to the same name, compared case-insensitively, are kept in
+    /// one patch even when they are not adjacent, so e.g. replacing an A
+    /// RRSet by a CNAME is never split, which can make a patch slightly
+    /// larger than `batch_size`. Names keep the order they first appear in,
+    /// and the changes to each name their relative order.
+    pub fn chunks(self, batch_size: usize) -> Vec<PatchZone> {
+        let batch_size = batch_size.max(1);
+        let mut groups: Vec<Vec<RRSetUpdate>> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for update in self.rrsets {
+            let position = *positions
+                .entry(update.name().to_ascii_lowercase())
+                .or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+            groups[position].push(update);
+        }
+
+        let mut chunks: Vec<PatchZone> = Vec::new();
+        let mut current: Vec<RRSetUpdate> = Vec::new();
+        for group in groups {
+            if current.len() >= batch_size {
+                chunks.push(PatchZone {
+                    rrsets: std::mem::take(&mut current),
+                });
+            }
+            current.extend(group);
+        }
+        if !current.is_empty() {
+            chunks.push(PatchZone { rrsets: current });
+        }
+        chunks
+    }
+}
+
+#[allow(deprecated)]
+impl ZoneClient<'_> {
+    /// Apply a large patch with one PATCH request per chunk of
+    /// [`ChunkOptions::batch_size`] rrsets, see [`PatchZone::chunks`], calling
+    /// `progress` after each of them.
+    ///
+    /// The whole patch is validated before the first request. Each chunk is
+    /// applied atomically, but the patch as a whole is not: when a chunk is
+    /// rejected, the earlier chunks stay applied. With
+    /// [`OnChunkError::FailFast`] its error is returned, otherwise the failed
+    /// chunks are listed in the report.
+    pub async fn patch_chunked(
+        &self,
+        zone_id: impl IntoZoneId,
+        zone: PatchZone,
+        options: &ChunkOptions,
+        mut progress: impl FnMut(&ChunkProgress),
+    ) -> Result<ChunkedPatchReport, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        zone.validate()?;
+
+        let rrsets = zone.rrsets.len();
+        let chunks = zone.chunks(options.batch_size);
+        let mut report = ChunkedPatchReport {
+            chunks: chunks.len(),
+            ..Default::default()
+        };
+        let mut rrsets_sent = 0;
+
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            let size = chunk.rrsets.len();
+            rrsets_sent += size;
+            // only cloned when it is needed to report a failure
+            let copy = (options.on_error == OnChunkError::Continue).then(|| chunk.clone());
+            let result = self.patch_unchecked(&zone_id, chunk).await;
+
+            progress(&ChunkProgress {
+                sent: index + 1,
+                chunks: report.chunks,
+                rrsets_sent,
+                rrsets,
+                applied: result.is_ok(),
+            });
+            match (result, copy) {
+                (Ok(()), _) => report.applied += size,
+                (Err(error), Some(patch)) => report.failed.push(FailedChunk {
+                    index,
+                    patch,
+                    error,
+                }),
+                (Err(error), None) => return Err(error),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Like [`ZoneClient::commit`], but sending the changes in chunks with
+    /// [`ZoneClient::patch_chunked`], so they are no longer applied
+    /// atomically
+    pub async fn commit_chunked(
+        &self,
+        zone_id: impl IntoZoneId,
+        changes: &ChangeSet,
+        options: &ChunkOptions,
+        progress: impl FnMut(&ChunkProgress),
+    ) -> Result<ChunkedPatchReport, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let current = if changes.adds_records() {
+            self.get(&zone_id).await?.rrsets.unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        self.patch_chunked(&zone_id, changes.to_patch(&current), options, progress)
+            .await
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::chunked::{ChunkOptions, OnChunkError};
+    use crate::record_type::RecordType;
+    use crate::zones::{PatchZone, RRSet, RRSetUpdate, Record, Zone, ZoneKind};
+
+    fn deletions(names: &[&str]) -> PatchZone {
+        names
+            .iter()
+            .map(|name| RRSetUpdate::delete(name, RecordType::A))
+            .collect()
+    }
+
+    #[test]
+    fn split_patch() {
+        let mut patch = deletions(&["a.example.com.", "b.example.com.", "c.example.com."]);
+        patch
+            .rrsets
+            .push(RRSetUpdate::delete("c.example.com.", RecordType::AAAA));
+        patch
+            .rrsets
+            .push(RRSetUpdate::delete("d.example.com.", RecordType::A));
+
+        let sizes: Vec<_> = patch
+            .chunks(2)
+            .iter()
+            .map(|chunk| chunk.rrsets.len())
+            .collect();
+        assert_eq!(sizes, [2, 2, 1]);
+        assert!(PatchZone { rrsets: vec![] }.chunks(10).is_empty());
+    }
+
+    #[test]
+    fn keep_non_adjacent_changes_to_a_name_together() {
+        let cname = RRSetUpdate::replace(RRSet::new(
+            "www.example.com.",
+            RecordType::CNAME,
+            300,
+            vec![Record::new("web.example.net.")],
+        ));
+        let mut patch = deletions(&["a.example.com.", "b.example.com."]);
+        patch.rrsets.insert(0, cname.clone());
+        patch
+            .rrsets
+            .push(RRSetUpdate::delete("WWW.example.com.", RecordType::A));
+
+        let chunks = patch.chunks(1);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks[0].rrsets,
+            [
+                cname,
+                RRSetUpdate::delete("WWW.example.com.", RecordType::A)
+            ]
+        );
+        assert_eq!(chunks[1].rrsets[0].name(), "a.example.com.");
+        assert_eq!(chunks[2].rrsets[0].name(), "b.example.com.");
+    }
+
+    #[tokio::test]
+    async fn patch_in_chunks() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+
+        let patch = deletions(&[
+            "a.example.com.",
+            "b.example.com.",
+            "c.example.org.",
+            "d.example.com.",
+            "e.example.com.",
+        ]);
+        let fail_fast = ChunkOptions {
+            batch_size: 2,
+            ..Default::default()
+        };
+        let mut sent = Vec::new();
+        let result = client
+            .zone()
+            .patch_chunked("example.com", patch.clone(), &fail_fast, |progress| {
+                sent.push((progress.sent, progress.rrsets_sent, progress.applied))
+            })
+            .await;
+        assert!(matches!(result, Err(crate::Error::Unprocessable { .. })));
+        assert_eq!(sent, [(1, 2, true), (2, 4, false)]);
+
+        let options = ChunkOptions {
+            batch_size: 2,
+            on_error: OnChunkError::Continue,
+        };
+        let report = client
+            .zone()
+            .patch_chunked("example.com", patch, &options, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(report.chunks, 3);
+        assert_eq!(report.applied, 3);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].index, 1);
+        assert_eq!(report.failed[0].patch.rrsets[0].name(), "c.example.org.");
+        assert!(!report.is_complete());
+    }
+}
@@ -1,57 +1,1148 @@
-use reqwest::header;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
-use crate::{server::ServerClient, zones::ZoneClient};
+use reqwest::{header, Certificate, Identity, Method, Proxy, RequestBuilder, Response, Url};
+use serde::Serialize;
 
+#[cfg(feature = "tracing")]
+use crate::instrument;
+#[allow(deprecated)]
+use crate::{
+    audit::{AuditLog, AuditRecord, AuditResult},
+    cryptokeys::CryptokeyClient,
+    error::RequestMethod,
+    metadata::MetadataClient,
+    middleware::{Middleware, MiddlewareStack},
+    observer::{ChangeEvent, ChangeKind, Observer, ObserverList},
+    operations::{self, Operation, OperationRecorder, RecorderList},
+    rate_limit::RateLimiter,
+    recursor::RecursorClient,
+    retry::{retry_after, RetryPolicy},
+    server::ServerClient,
+    tsigkeys::TsigKeyClient,
+    zones::{Zone, ZoneClient},
+    Error,
+};
+
+/// The path of the API below the base URL, unless configured otherwise
+const DEFAULT_API_PATH: &str = "/api/v1";
+
+#[allow(deprecated)]
 pub struct Client {
     pub(crate) base_url: String,
+    /// The base URL followed by the API path, e.g.
+    /// `http://localhost:8081/api/v1`
+    pub(crate) api_url: String,
     pub(crate) server_name: String,
     pub(crate) http_client: reqwest::Client,
+    /// Sends the requests instead of `http_client` for a
+    /// [`crate::blocking::Client`], without a tokio runtime
+    #[cfg(feature = "blocking")]
+    pub(crate) blocking_http_client: Option<reqwest::blocking::Client>,
+    /// Shared with the Clients created by [`Client::for_server`], so that
+    /// [`Client::set_api_key`] updates all of them
+    api_key: Arc<RwLock<Option<header::HeaderValue>>>,
+    /// Username and password sent with HTTP Basic auth
+    basic_auth: Option<(String, Option<String>)>,
+    retry_policy: RetryPolicy,
+    /// Shared with the Clients created by [`Client::for_server`]
+    rate_limiter: Option<Arc<RateLimiter>>,
+    middleware: MiddlewareStack,
+    observers: ObserverList,
+    /// Shared with the Clients created by [`Client::for_server`]
+    audit_log: Option<Arc<AuditLog>>,
+    recorders: RecorderList,
+    /// The base URL followed by the fallback URLs, empty without failover
+    endpoints: Arc<[Url]>,
+    /// Index of the endpoint that answered the last request
+    active_endpoint: Arc<AtomicUsize>,
+    /// Zones fetched by [`ZoneClient::get_cached`], keyed by zone id
+    pub(crate) zone_cache: Mutex<HashMap<String, Zone>>,
 }
 
 impl Client {
-    pub fn new(base_url: &str, server_name: &str, api_token: &str) -> Self {
-        let mut headers = header::HeaderMap::new();
-        let mut auth_header = header::HeaderValue::from_str(api_token).unwrap();
-        auth_header.set_sensitive(true);
-        headers.insert("X-API-Key", auth_header);
-        let accept_header = header::HeaderValue::from_static("application/json");
-        headers.insert(header::ACCEPT, accept_header);
-
-        let http_client = reqwest::Client::builder()
-            .user_agent("powerdns.rs/0.1")
-            .default_headers(headers)
+    /// Create a Client for the webserver at `base_url`, a string or a
+    /// [`Url`].
+    ///
+    /// # Panics
+    ///
+    /// When `base_url` is not an http or https URL or `api_token` is not a
+    /// valid header value, use [`Client::builder`] to handle these errors.
+    #[deprecated(note = "use `Client::builder()`, whose `build` returns these errors instead of panicking")]
+    pub fn new(base_url: impl AsRef<str>, server_name: &str, api_token: &str) -> Self {
+        Client::builder()
+            .base_url(base_url)
+            .server_name(server_name)
+            .api_key(api_token)
             .build()
-            .unwrap();
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        Client {
+    /// Create a Client reusing an already configured reqwest Client, e.g. one
+    /// with proxies or middleware. The API key is sent with every request
+    /// instead of being part of the default headers of `http_client`.
+    pub fn with_http_client(
+        http_client: reqwest::Client,
+        base_url: impl AsRef<str>,
+        server_name: &str,
+        api_key: &str,
+    ) -> Result<Self, Error> {
+        let base_url = parse_base_url(base_url.as_ref())?;
+        let base_url = base_url.as_str().trim_end_matches('/');
+        Ok(Client {
             base_url: base_url.to_string(),
+            api_url: api_url(base_url, DEFAULT_API_PATH),
             server_name: server_name.to_string(),
             http_client,
+            #[cfg(feature = "blocking")]
+            blocking_http_client: None,
+            api_key: Arc::new(RwLock::new(Some(api_key_header(api_key)?))),
+            basic_auth: None,
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+            middleware: MiddlewareStack::default(),
+            observers: ObserverList::default(),
+            audit_log: None,
+            recorders: RecorderList::default(),
+            endpoints: Arc::new([]),
+            active_endpoint: Arc::default(),
+            zone_cache: Mutex::default(),
+        })
+    }
+
+    /// A Client for the server `server_name` behind the same webserver, e.g.
+    /// a proxy fronting several PowerDNS instances. The connection pool,
+    /// credentials, retry policy, rate limits, middleware, observers, audit
+    /// log and operation recorders are shared with this Client; the zone
+    /// cache is not.
+    pub fn for_server(&self, server_name: &str) -> Client {
+        Client {
+            base_url: self.base_url.clone(),
+            api_url: self.api_url.clone(),
+            server_name: server_name.to_string(),
+            http_client: self.http_client.clone(),
+            #[cfg(feature = "blocking")]
+            blocking_http_client: self.blocking_http_client.clone(),
+            api_key: self.api_key.clone(),
+            basic_auth: self.basic_auth.clone(),
+            retry_policy: self.retry_policy.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            middleware: self.middleware.clone(),
+            observers: self.observers.clone(),
+            audit_log: self.audit_log.clone(),
+            recorders: self.recorders.clone(),
+            endpoints: self.endpoints.clone(),
+            active_endpoint: self.active_endpoint.clone(),
+            zone_cache: Mutex::default(),
+        }
+    }
+
+    /// Send `api_key` instead of the previous key with all following
+    /// requests, e.g. after it was rotated. Applies to the Clients created by
+    /// [`Client::for_server`] as well.
+    pub fn set_api_key(&self, api_key: &str) -> Result<(), Error> {
+        let api_key = api_key_header(api_key)?;
+        *self.api_key.write().unwrap_or_else(|e| e.into_inner()) = Some(api_key);
+        Ok(())
+    }
+
+    /// Start building a Client with custom connection options
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Whether requests are sent with reqwest::blocking, in which case the
+    /// futures of the Client run without a tokio runtime and must not use
+    /// its timers
+    pub(crate) fn is_blocking(&self) -> bool {
+        #[cfg(feature = "blocking")]
+        return self.blocking_http_client.is_some();
+        #[cfg(not(feature = "blocking"))]
+        false
+    }
+
+    /// Wait for `delay` before the next request, blocking the thread for a
+    /// [`crate::blocking::Client`]
+    pub(crate) async fn sleep(&self, delay: Duration) {
+        if self.is_blocking() {
+            std::thread::sleep(delay);
+        } else {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Whether any observers are registered, to skip building events nobody
+    /// receives
+    pub(crate) fn has_observers(&self) -> bool {
+        !self.observers.is_empty()
+    }
+
+    /// Pass a change of `zone` to the observers, after it was made
+    pub(crate) fn emit(&self, zone: &str, kind: ChangeKind) {
+        if self.observers.is_empty() {
+            return;
+        }
+        self.observers.notify(&ChangeEvent {
+            server: self.server_name.clone(),
+            zone: zone.to_string(),
+            kind,
+        });
+    }
+
+    pub(crate) fn request(&self, method: Method, url: String) -> Request<'_> {
+        let builder = self.http_client.request(method, url);
+        let api_key = self.api_key.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let builder = match api_key {
+            Some(api_key) => builder.header("X-API-Key", api_key),
+            None => builder,
+        };
+        let builder = match &self.basic_auth {
+            Some((username, password)) => builder.basic_auth(username, password.as_ref()),
+            None => builder,
+        };
+        Request {
+            client: self,
+            builder,
+        }
+    }
+
+    pub(crate) fn get(&self, url: String) -> Request<'_> {
+        self.request(Method::GET, url)
+    }
+
+    pub(crate) fn post(&self, url: String) -> Request<'_> {
+        self.request(Method::POST, url)
+    }
+
+    pub(crate) fn put(&self, url: String) -> Request<'_> {
+        self.request(Method::PUT, url)
+    }
+
+    pub(crate) fn patch(&self, url: String) -> Request<'_> {
+        self.request(Method::PATCH, url)
+    }
+
+    pub(crate) fn delete(&self, url: String) -> Request<'_> {
+        self.request(Method::DELETE, url)
+    }
+
+    /// Execute a request, failing over to the next endpoint when the current
+    /// one cannot be reached. Requests the retry policy retries also fail
+    /// over on server errors and timeouts, others may have reached the server.
+    async fn execute(&self, mut request: reqwest::Request) -> Result<Response, Error> {
+        let Some(path) = self.endpoint_path(request.url()) else {
+            return self.execute_with_retries(request).await;
+        };
+        let idempotent = self.retry_policy.retries_method(request.method());
+
+        let first = self.active_endpoint.load(Ordering::Relaxed);
+        for offset in 0..self.endpoints.len() - 1 {
+            let index = (first + offset) % self.endpoints.len();
+            let Some(mut attempt) = request.try_clone() else {
+                break;
+            };
+            *attempt.url_mut() = endpoint_url(&self.endpoints[index], &path)?;
+            match self.execute_with_retries(attempt).await {
+                Ok(response) if idempotent && response.status().is_server_error() => {}
+                Err(Error::RequestError(e))
+                    if e.is_connect() || (idempotent && e.is_timeout()) => {}
+                result => {
+                    self.active_endpoint.store(index, Ordering::Relaxed);
+                    return result;
+                }
+            }
+        }
+
+        let index = (first + self.endpoints.len() - 1) % self.endpoints.len();
+        *request.url_mut() = endpoint_url(&self.endpoints[index], &path)?;
+        let result = self.execute_with_retries(request).await;
+        if result.is_ok() {
+            self.active_endpoint.store(index, Ordering::Relaxed);
         }
+        result
+    }
+
+    /// The part of `url` following the base URL, None without failover
+    fn endpoint_path(&self, url: &Url) -> Option<String> {
+        let base = self.endpoints.first()?;
+        let path = url.as_str().strip_prefix(base.as_str().trim_end_matches('/'))?;
+        Some(path.to_string())
     }
 
-    pub fn server(&self) -> ServerClient {
+    /// Execute a request, retrying according to the RetryPolicy
+    async fn execute_with_retries(&self, request: reqwest::Request) -> Result<Response, Error> {
+        let policy = &self.retry_policy;
+        let mut attempt = 1;
+
+        while attempt < policy.max_attempts && policy.retries_method(request.method()) {
+            let Some(retry) = request.try_clone() else {
+                break;
+            };
+            let delay = match self.send_once(retry).await {
+                Ok(response) if policy.retries_status(response.status()) => {
+                    match retry_after(response.headers()) {
+                        Some(delay) if delay > policy.max_retry_after => return Ok(response),
+                        Some(delay) => delay,
+                        None => policy.backoff(attempt),
+                    }
+                }
+                Err(Error::RequestError(e)) if policy.retries_error(&e) => policy.backoff(attempt),
+                result => return result,
+            };
+            self.sleep(delay).await;
+            attempt += 1;
+        }
+
+        self.send_once(request).await
+    }
+
+    async fn send_once(&self, mut request: reqwest::Request) -> Result<Response, Error> {
+        self.middleware.on_request(&mut request)?;
+        let _permit = match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter.acquire(self.is_blocking()).await,
+            None => None,
+        };
+
+        #[cfg(feature = "tracing")]
+        instrument::log_request(&request);
+        #[cfg(feature = "blocking")]
+        let response = match &self.blocking_http_client {
+            Some(http_client) => execute_blocking(http_client, request),
+            None => self.http_client.execute(request).await,
+        };
+        #[cfg(not(feature = "blocking"))]
+        let response = self.http_client.execute(request).await;
+        #[cfg(feature = "tracing")]
+        let response = instrument::log_response(response).await;
+        self.middleware.on_result(response)
+    }
+
+    pub fn server(&self) -> ServerClient<'_> {
         ServerClient::new(self)
     }
 
-    pub fn zone(&self) -> ZoneClient {
+    pub fn zone(&self) -> ZoneClient<'_> {
         ZoneClient::new(self)
     }
+
+    pub fn cryptokeys(&self, zone_id: &str) -> CryptokeyClient<'_> {
+        CryptokeyClient::new(self, zone_id)
+    }
+
+    pub fn metadata(&self, zone_id: &str) -> MetadataClient<'_> {
+        MetadataClient::new(self, zone_id)
+    }
+
+    pub fn tsigkeys(&self) -> TsigKeyClient<'_> {
+        TsigKeyClient::new(self)
+    }
+
+    pub fn recursor(&self) -> RecursorClient<'_> {
+        RecursorClient::new(self)
+    }
+}
+
+/// A request to the PowerDNS API, sent according to the options of the
+/// Client it was created by
+pub(crate) struct Request<'a> {
+    client: &'a Client,
+    builder: RequestBuilder,
+}
+
+impl Request<'_> {
+    pub(crate) fn header(mut self, key: header::HeaderName, value: header::HeaderValue) -> Self {
+        self.builder = self.builder.header(key, value);
+        self
+    }
+
+    pub(crate) fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        self.builder = self.builder.query(query);
+        self
+    }
+
+    pub(crate) fn json<T: Serialize + ?Sized>(mut self, json: &T) -> Self {
+        self.builder = self.builder.json(json);
+        self
+    }
+
+    /// Send the request, retrying according to the RetryPolicy of the Client
+    pub(crate) async fn send(self) -> Result<Response, Error> {
+        #[allow(unused_mut)]
+        let mut request = self.builder.build()?;
+        let method = request.method().clone();
+        let audit = match &self.client.audit_log {
+            Some(audit_log) if method != Method::GET => {
+                Some((audit_log, AuditRecord::pending(&self.client.server_name, &request)))
+            }
+            _ => None,
+        };
+        let endpoint = (!self.client.recorders.is_empty())
+            .then(|| operations::endpoint(request.url().path()));
+
+        #[cfg(feature = "tracing")]
+        let span = instrument::request_span(&request);
+        #[cfg(feature = "otel")]
+        crate::otel::propagate(&mut request, &span);
+        let start = std::time::Instant::now();
+        let response = self.client.execute(request);
+        #[cfg(feature = "tracing")]
+        let response = tracing::Instrument::instrument(response, span.clone());
+        let response = response.await;
+
+        let duration = start.elapsed();
+        #[cfg(feature = "tracing")]
+        instrument::record_duration(&span, duration);
+        if let Some(endpoint) = endpoint {
+            let operation = Operation {
+                method: method.clone(),
+                endpoint,
+                status: response.as_ref().ok().map(Response::status),
+                duration,
+            };
+            self.client.recorders.record(&operation);
+        }
+        if let Some((audit_log, mut record)) = audit {
+            record.result = match &response {
+                Ok(response) => AuditResult::Status(response.status().as_u16()),
+                Err(e) => AuditResult::Failed(e.to_string()),
+            };
+            audit_log.record(record);
+        }
+        let mut response = response?;
+        response.extensions_mut().insert(RequestMethod(method));
+        Ok(response)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn build(self) -> reqwest::Result<reqwest::Request> {
+        self.builder.build()
+    }
+}
+
+/// Apply the connection options of a ClientBuilder to a reqwest
+/// ClientBuilder, which has the same methods for the async and the blocking
+/// client
+macro_rules! connection_options {
+    ($http_client:expr, $builder:expr) => {{
+        let mut headers = header::HeaderMap::new();
+        let accept_header = header::HeaderValue::from_static("application/json");
+        headers.insert(header::ACCEPT, accept_header);
+
+        let mut http_client = $http_client
+            .user_agent($builder.user_agent)
+            .default_headers(headers)
+            .tcp_keepalive($builder.tcp_keepalive)
+            .danger_accept_invalid_certs($builder.accept_invalid_certs);
+        for certificate in $builder.root_certificates {
+            http_client = http_client.add_root_certificate(certificate);
+        }
+        if let Some(identity) = $builder.identity {
+            http_client = http_client.identity(identity);
+        }
+        if $builder.no_proxy {
+            http_client = http_client.no_proxy();
+        }
+        for proxy in $builder.proxies {
+            http_client = http_client.proxy(proxy);
+        }
+        if let Some(timeout) = $builder.connect_timeout {
+            http_client = http_client.connect_timeout(timeout);
+        }
+        if let Some(timeout) = $builder.timeout {
+            http_client = http_client.timeout(timeout);
+        }
+        if let Some(max) = $builder.pool_max_idle_per_host {
+            http_client = http_client.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = $builder.pool_idle_timeout {
+            http_client = http_client.pool_idle_timeout(timeout);
+        }
+        http_client
+    }};
+}
+
+/// Builds a Client with custom connection options
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    base_url: String,
+    api_path: String,
+    server_name: String,
+    api_key: Option<String>,
+    basic_auth: Option<(String, Option<String>)>,
+    user_agent: String,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    root_certificates: Vec<Certificate>,
+    identity: Option<Identity>,
+    accept_invalid_certs: bool,
+    proxies: Vec<Proxy>,
+    no_proxy: bool,
+    retry_policy: RetryPolicy,
+    requests_per_second: Option<f64>,
+    max_concurrent_requests: Option<usize>,
+    middleware: MiddlewareStack,
+    observers: ObserverList,
+    audit_log: Option<Arc<AuditLog>>,
+    recorders: RecorderList,
+    fallback_base_urls: Vec<String>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        ClientBuilder {
+            base_url: String::from("http://localhost:8081"),
+            api_path: String::from(DEFAULT_API_PATH),
+            server_name: String::from("localhost"),
+            api_key: None,
+            basic_auth: None,
+            user_agent: String::from("powerdns.rs/0.1"),
+            connect_timeout: None,
+            timeout: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            tcp_keepalive: None,
+            root_certificates: Vec::new(),
+            identity: None,
+            accept_invalid_certs: false,
+            proxies: Vec::new(),
+            no_proxy: false,
+            retry_policy: RetryPolicy::default(),
+            requests_per_second: None,
+            max_concurrent_requests: None,
+            middleware: MiddlewareStack::default(),
+            observers: ObserverList::default(),
+            audit_log: None,
+            recorders: RecorderList::default(),
+            fallback_base_urls: Vec::new(),
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// The URL of the PowerDNS webserver, a string or a [`Url`], defaults to
+    /// `http://localhost:8081`. IPv6 addresses must be in brackets, e.g.
+    /// `http://[fd00::1]:8081`. Validated by [`ClientBuilder::build`].
+    pub fn base_url(mut self, base_url: impl AsRef<str>) -> Self {
+        self.base_url = base_url.as_ref().to_string();
+        self
+    }
+
+    /// The path of the API below the base URL, defaults to `/api/v1`. Set
+    /// it when the API is served under another path by a reverse proxy, e.g.
+    /// `/dns/api/v1` for `https://proxy.internal/dns/api/v1/servers`.
+    pub fn api_path(mut self, api_path: &str) -> Self {
+        self.api_path = api_path.to_string();
+        self
+    }
+
+    /// Another URL of a webserver serving the same data, used when the
+    /// current one cannot be reached or returns a server error. Fallbacks are
+    /// tried in the order they were added, and the Client keeps using the
+    /// last one that answered. Requests the [`RetryPolicy`] does not retry,
+    /// e.g. POST by default, only fail over when the connection could not be
+    /// established, as a server error or timeout may follow a change.
+    pub fn fallback_base_url(mut self, base_url: impl AsRef<str>) -> Self {
+        self.fallback_base_urls.push(base_url.as_ref().to_string());
+        self
+    }
+
+    /// The id of the server to manage, defaults to `localhost`
+    pub fn server_name(mut self, server_name: &str) -> Self {
+        self.server_name = server_name.to_string();
+        self
+    }
+
+    /// The key sent in the X-API-Key header of every request
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Authenticate to the webserver with HTTP Basic auth, e.g. when it is
+    /// protected by a reverse proxy. Sent in addition to the API key if one
+    /// is set; leave out [`ClientBuilder::api_key`] to only send this.
+    pub fn basic_auth(mut self, username: &str, password: Option<&str>) -> Self {
+        self.basic_auth = Some((username.to_string(), password.map(str::to_string)));
+        self
+    }
+
+    /// Override the User-Agent header, defaults to `powerdns.rs/0.1`
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Timeout for establishing a connection to the webserver
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for a whole request, from connecting until the response body
+    /// has been read
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum number of idle connections kept open to the webserver
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// How long idle connections are kept open before being closed
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Interval of TCP keepalive probes on open connections
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Trust an additional root certificate, e.g. the CA of an internal PKI
+    /// the PowerDNS webserver certificate is issued by
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Present a client certificate to the webserver
+    pub fn identity(mut self, identity: Identity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Accept any server certificate, including expired and self-signed
+    /// ones and those issued for other hostnames.
+    ///
+    /// # Warning
+    ///
+    /// This makes the connection vulnerable to man-in-the-middle attacks and
+    /// exposes the API key. Prefer [`ClientBuilder::add_root_certificate`].
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    /// Send requests through a proxy. By default the proxy configured in the
+    /// environment (`HTTP_PROXY`, `HTTPS_PROXY`, `NO_PROXY`) is used.
+    ///
+    /// Hosts to exclude from proxying can be set on the proxy with
+    /// [`Proxy::no_proxy`], e.g. `NoProxy::from_string("localhost,.internal")`.
+    /// SOCKS proxies require the `socks` feature of reqwest to be enabled.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Do not use any proxy, including the one configured in the environment
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    /// Retry failed requests according to `retry_policy`, by default requests
+    /// are not retried
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Send at most `requests_per_second` requests per second, spacing them
+    /// out evenly. Retries count towards the limit.
+    pub fn requests_per_second(mut self, requests_per_second: f64) -> Self {
+        self.requests_per_second = Some(requests_per_second);
+        self
+    }
+
+    /// Keep at most `max` requests in flight at the same time. A request is
+    /// in flight until the response headers have been received.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    /// Call `middleware` around every request, after the middleware that
+    /// was added before
+    pub fn middleware(self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware_arc(Arc::new(middleware))
+    }
+
+    /// Like [`ClientBuilder::middleware`], for middleware that is shared with
+    /// other Clients or inspected later
+    pub fn middleware_arc(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Call `observer` after every successful change made through the
+    /// Client, after the observers that were added before. See
+    /// [`crate::observer`].
+    pub fn on_change(self, observer: impl Observer + 'static) -> Self {
+        self.on_change_arc(Arc::new(observer))
+    }
+
+    /// Like [`ClientBuilder::on_change`], for observers that are shared with
+    /// other Clients or inspected later
+    pub fn on_change_arc(mut self, observer: Arc<dyn Observer>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Record every mutating request in `audit_log`, including the ones the
+    /// server rejected. See [`crate::audit`].
+    pub fn audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Pass every API call made through the Client to `recorder`, after the
+    /// recorders that were added before. See [`crate::operations`].
+    pub fn record_operations(self, recorder: impl OperationRecorder + 'static) -> Self {
+        self.record_operations_arc(Arc::new(recorder))
+    }
+
+    /// Like [`ClientBuilder::record_operations`], for recorders that are
+    /// shared with other Clients or inspected later, like
+    /// [`crate::operations::OperationStats`]
+    pub fn record_operations_arc(mut self, recorder: Arc<dyn OperationRecorder>) -> Self {
+        self.recorders.push(recorder);
+        self
+    }
+
+    /// Fails with [`Error::InvalidBaseUrl`] when the base URL or a fallback
+    /// is not an http or https URL
+    pub fn build(self) -> Result<Client, Error> {
+        let http_client = connection_options!(reqwest::Client::builder(), self.clone()).build()?;
+        self.build_with(http_client)
+    }
+
+    /// Build a Client creating its requests with `http_client`
+    fn build_with(self, http_client: reqwest::Client) -> Result<Client, Error> {
+        let api_key = self.api_key.as_deref().map(api_key_header).transpose()?;
+        let base_url = parse_base_url(&self.base_url)?;
+        let fallbacks = self
+            .fallback_base_urls
+            .iter()
+            .map(|url| parse_base_url(url))
+            .collect::<Result<Vec<_>, _>>()?;
+        let endpoints = if fallbacks.is_empty() {
+            Vec::new()
+        } else {
+            std::iter::once(base_url.clone()).chain(fallbacks).collect()
+        };
+
+        let base_url = base_url.as_str().trim_end_matches('/');
+        Ok(Client {
+            base_url: base_url.to_string(),
+            api_url: api_url(base_url, &self.api_path),
+            server_name: self.server_name,
+            http_client,
+            #[cfg(feature = "blocking")]
+            blocking_http_client: None,
+            api_key: Arc::new(RwLock::new(api_key)),
+            basic_auth: self.basic_auth,
+            retry_policy: self.retry_policy,
+            rate_limiter: (self.requests_per_second.is_some()
+                || self.max_concurrent_requests.is_some())
+            .then(|| Arc::new(RateLimiter::new(self.requests_per_second, self.max_concurrent_requests))),
+            middleware: self.middleware,
+            observers: self.observers,
+            audit_log: self.audit_log,
+            recorders: self.recorders,
+            endpoints: endpoints.into(),
+            active_endpoint: Arc::default(),
+            zone_cache: Mutex::default(),
+        })
+    }
+
+    /// Build a Client sending its requests with reqwest::blocking, for a
+    /// [`crate::blocking::Client`]
+    #[cfg(feature = "blocking")]
+    pub(crate) fn build_blocking(self) -> Result<Client, Error> {
+        // unlike the async client, reqwest::blocking defaults to a timeout
+        let http_client = reqwest::blocking::Client::builder().timeout(None);
+        let http_client = connection_options!(http_client, self.clone()).build()?;
+        let mut client = self.build_with(request_client())?;
+        client.blocking_http_client = Some(http_client);
+        Ok(client)
+    }
+}
+
+/// The reqwest Client creating the requests of all blocking Clients, which
+/// send them with their reqwest::blocking Client instead. Shared so that no
+/// blocking Client sets up a connection pool it never uses.
+#[cfg(feature = "blocking")]
+pub(crate) fn request_client() -> reqwest::Client {
+    static REQUEST_CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+    REQUEST_CLIENT.get_or_init(reqwest::Client::new).clone()
+}
+
+/// Parse the URL of a webserver, which must be http or https and cannot have
+/// a query or fragment, as paths are appended to it
+fn parse_base_url(url: &str) -> Result<Url, Error> {
+    let invalid = |reason: &str| Error::InvalidBaseUrl {
+        url: url.to_string(),
+        reason: reason.to_string(),
+    };
+    let parsed = Url::parse(url).map_err(|e| invalid(&e.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(invalid("the scheme must be http or https"));
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(invalid("it cannot have a query or fragment"));
+    }
+    Ok(parsed)
+}
+
+/// `api_path` below `base_url`, with exactly one slash between them and
+/// none at the end
+fn api_url(base_url: &str, api_path: &str) -> String {
+    let api_path = api_path.trim_matches('/');
+    if api_path.is_empty() {
+        base_url.to_string()
+    } else {
+        format!("{base_url}/{api_path}")
+    }
+}
+
+/// `path` appended to the base URL `endpoint`
+fn endpoint_url(endpoint: &Url, path: &str) -> Result<Url, Error> {
+    let url = format!("{}{path}", endpoint.as_str().trim_end_matches('/'));
+    Url::parse(&url).map_err(|e| Error::Other(Box::new(e)))
+}
+
+/// Send a request with reqwest::blocking, reading the whole body so the
+/// response can be handled like one received by the async client
+#[cfg(feature = "blocking")]
+fn execute_blocking(
+    http_client: &reqwest::blocking::Client,
+    request: reqwest::Request,
+) -> reqwest::Result<Response> {
+    use reqwest::ResponseBuilderExt;
+
+    let mut builder = http_client
+        .request(request.method().clone(), request.url().clone())
+        .headers(request.headers().clone());
+    if let Some(body) = request.body().and_then(reqwest::Body::as_bytes) {
+        builder = builder.body(body.to_vec());
+    }
+    if let Some(timeout) = request.timeout() {
+        builder = builder.timeout(*timeout);
+    }
+
+    let response = builder.send()?;
+    let mut http_response = http::Response::builder()
+        .status(response.status())
+        .version(response.version())
+        .url(response.url().clone());
+    if let Some(headers) = http_response.headers_mut() {
+        *headers = response.headers().clone();
+    }
+    let body = response.bytes()?;
+    // the parts were copied from a valid response
+    Ok(Response::from(http_response.body(body).expect("valid response parts")))
+}
+
+fn api_key_header(api_key: &str) -> Result<header::HeaderValue, Error> {
+    let mut auth_header =
+        header::HeaderValue::from_str(api_key).map_err(|e| Error::Other(Box::new(e)))?;
+    auth_header.set_sensitive(true);
+    Ok(auth_header)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::client::Client;
-    use std::env;
+    use crate::mock::MockServer;
+    use crate::retry::RetryPolicy;
+    use crate::Error;
+    use std::time::Duration;
 
     #[test]
+    #[allow(deprecated)]
     fn build_client() {
-        dotenvy::dotenv().ok();
+        let server = MockServer::start();
+        let _client = Client::new(server.base_url(), "localhost", server.api_key());
+    }
+
+    #[test]
+    fn build_client_with_options() {
+        let client = Client::builder()
+            .base_url("http://127.0.0.1:8081")
+            .api_key("secret")
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(30))
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(Duration::from_secs(60))
+            .user_agent("test-agent")
+            .build()
+            .unwrap();
+
+        assert_eq!(client.base_url, "http://127.0.0.1:8081");
+        assert_eq!(client.server_name, "localhost");
+    }
+
+    #[test]
+    fn custom_api_path() {
+        let client = Client::builder()
+            .base_url("https://proxy.internal/")
+            .api_path("/dns/api/v1/")
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url, "https://proxy.internal");
+        assert_eq!(client.api_url, "https://proxy.internal/dns/api/v1");
 
-        let _client = Client::new(
-            &env::var("PDNS_HOST").unwrap_or_else(|_| String::from("http://localhost:8081")),
-            &env::var("PDNS_SERVER").unwrap_or_else(|_| String::from("localhost")),
-            &env::var("PDNS_API_KEY").unwrap(),
+        let request = client.zone().dry_run().delete("example.com").unwrap();
+        assert_eq!(
+            request.url,
+            "https://proxy.internal/dns/api/v1/servers/localhost/zones/example.com."
         );
     }
+
+    #[test]
+    fn normalize_base_url() {
+        let client = Client::builder()
+            .base_url("HTTP://[FD00::1]:8081/")
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url, "http://[fd00::1]:8081");
+        assert_eq!(client.api_url, "http://[fd00::1]:8081/api/v1");
+
+        let url = reqwest::Url::parse("https://dns.example.com/pdns/").unwrap();
+        let client = Client::builder().base_url(&url).build().unwrap();
+        assert_eq!(client.api_url, "https://dns.example.com/pdns/api/v1");
+    }
+
+    #[test]
+    fn reject_invalid_base_url() {
+        for url in ["localhost:8081", "ftp://localhost", "http://", "http://localhost/?a=b"] {
+            let result = Client::builder().base_url(url).build();
+            assert!(
+                matches!(&result, Err(Error::InvalidBaseUrl { url: invalid, .. }) if invalid == url),
+                "{url}"
+            );
+        }
+        let result = Client::builder().fallback_base_url("not a url").build();
+        assert!(matches!(result, Err(Error::InvalidBaseUrl { .. })));
+    }
+
+    #[tokio::test]
+    async fn trailing_slash_in_base_url() {
+        let server = MockServer::start();
+        let base_url = format!("{}/", server.base_url());
+        let client = Client::builder()
+            .base_url(&base_url)
+            .server_name("localhost")
+            .api_key(server.api_key())
+            .build()
+            .unwrap();
+        assert!(client.zone().list().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn client_for_server() {
+        let server = MockServer::start();
+        let client = server.client();
+        let other = client.for_server("other");
+        assert_eq!(other.server_name, "other");
+        assert_eq!(other.base_url, client.base_url);
+
+        assert!(client.zone().list().await.is_ok());
+        assert!(other.zone().list().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn fail_over_to_fallback_url() {
+        let server = MockServer::start();
+        // nothing listens on the port after the listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let unreachable = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let client = Client::builder()
+            .base_url(&unreachable)
+            .fallback_base_url(server.base_url())
+            .api_key(server.api_key())
+            .build()
+            .unwrap();
+        assert!(client.zone().list().await.is_ok());
+        assert!(client.zone().list().await.is_ok());
+        assert_eq!(client.active_endpoint.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn fail_over_on_server_error_only_when_idempotent() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        use crate::zones::{Zone, ZoneKind};
+
+        let server = MockServer::start();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let failing = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let _ = stream.read(&mut [0; 4096]);
+                let response = "HTTP/1.1 503 Service Unavailable\r\n\
+                                content-length: 0\r\nconnection: close\r\n\r\n";
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let client = Client::builder()
+            .base_url(&failing)
+            .fallback_base_url(server.base_url())
+            .api_key(server.api_key())
+            .build()
+            .unwrap();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        assert!(client.zone().create(zone).await.is_err());
+        assert!(server.zone("example.com.").is_none());
+        assert_eq!(client.active_endpoint.load(std::sync::atomic::Ordering::Relaxed), 0);
+
+        assert!(client.zone().list().await.is_ok());
+        assert_eq!(client.active_endpoint.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn reject_invalid_api_key() {
+        assert!(Client::builder().api_key("invalid\nkey").build().is_err());
+    }
+
+    #[test]
+    fn send_configured_credentials() {
+        let client = Client::builder()
+            .basic_auth("user", Some("pass"))
+            .build()
+            .unwrap();
+        let request = client.get(String::from("http://127.0.0.1:8081/api")).build().unwrap();
+        assert_eq!(request.headers()["Authorization"], "Basic dXNlcjpwYXNz");
+        assert!(!request.headers().contains_key("X-API-Key"));
+
+        let client = Client::builder().api_key("secret").build().unwrap();
+        let request = client.get(String::from("http://127.0.0.1:8081/api")).build().unwrap();
+        assert_eq!(request.headers()["X-API-Key"], "secret");
+        assert!(!request.headers().contains_key("Authorization"));
+    }
+
+    #[tokio::test]
+    async fn rotate_api_key() {
+        let server = MockServer::start();
+        let client = Client::builder()
+            .base_url(server.base_url())
+            .server_name("localhost")
+            .api_key("expired")
+            .build()
+            .unwrap();
+        let other = client.for_server("localhost");
+        assert!(client.zone().list().await.is_err());
+
+        client.set_api_key(server.api_key()).unwrap();
+        assert!(client.zone().list().await.is_ok());
+        assert!(other.zone().list().await.is_ok());
+        assert!(client.set_api_key("invalid\nkey").is_err());
+    }
+
+    #[test]
+    fn reuse_http_client() {
+        let http_client = reqwest::Client::builder().build().unwrap();
+        let client =
+            Client::with_http_client(http_client, "http://127.0.0.1:8081", "localhost", "secret")
+                .unwrap();
+
+        let request = client.get(String::from("http://127.0.0.1:8081/api")).build().unwrap();
+        assert_eq!(request.headers()["X-API-Key"], "secret");
+    }
+
+    #[test]
+    fn build_client_with_proxy() {
+        let proxy = reqwest::Proxy::all("http://proxy.internal:3128")
+            .unwrap()
+            .no_proxy(reqwest::NoProxy::from_string("localhost,.internal"));
+
+        let client = Client::builder().proxy(proxy).build();
+        assert!(client.is_ok());
+    }
+
+    #[tokio::test]
+    async fn retry_unavailable_server() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let responses = [
+                "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n[]",
+            ];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let _ = stream.read(&mut [0; 4096]);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..RetryPolicy::new(2)
+        };
+        let client = Client::builder()
+            .base_url(&base_url)
+            .api_key("secret")
+            .retry_policy(policy)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.tsigkeys().list().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn honor_retry_after() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let responses = [
+                "HTTP/1.1 429 Too Many Requests\r\nretry-after: 0\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n[]",
+                "HTTP/1.1 503 Service Unavailable\r\nretry-after: 3600\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+            ];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let _ = stream.read(&mut [0; 4096]);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let policy = RetryPolicy {
+            // a retry would wait for the backoff if Retry-After was ignored
+            initial_backoff: Duration::from_secs(3600),
+            ..RetryPolicy::new(2)
+        };
+        let client = Client::builder()
+            .base_url(&base_url)
+            .api_key("secret")
+            .retry_policy(policy)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.tsigkeys().list().await.unwrap(), vec![]);
+        let error = client.tsigkeys().list().await.unwrap_err();
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(3600)));
+    }
 }
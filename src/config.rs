@@ -0,0 +1,255 @@
+//! Construct a Client from a configuration file, e.g. one mounted into a
+//! container together with the API key.
+//!
+//! [`ClientConfig`] can be deserialized from any format supported by serde;
+//! JSON, TOML and YAML files can be read directly with
+//! [`ClientConfig::from_file`]:
+//!
+//! ```json
+//! {
+//!     "base_url": "https://dns.internal:8081",
+//!     "server": "localhost",
+//!     "api_key_file": "/run/secrets/pdns-api-key",
+//!     "timeout": 30,
+//!     "ca_certificate": "/etc/ssl/internal-ca.pem"
+//! }
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::{Certificate, Identity};
+use serde::{Deserialize, Serialize};
+
+use crate::client::ClientBuilder;
+use crate::{Client, Error};
+
+/// The options of a [`Client`] that can be set in a configuration file
+#[derive(Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    /// See [`ClientBuilder::base_url`]
+    pub base_url: Option<String>,
+    /// See [`ClientBuilder::api_path`]
+    pub api_path: Option<String>,
+    /// The id of the server to manage, see [`ClientBuilder::server_name`]
+    pub server: Option<String>,
+    pub api_key: Option<String>,
+    /// A file containing the API key, takes precedence over `api_key`.
+    /// Leading and trailing whitespace is ignored.
+    pub api_key_file: Option<PathBuf>,
+    /// Timeout for establishing a connection, in seconds
+    pub connect_timeout: Option<f64>,
+    /// Timeout for a whole request, in seconds
+    pub timeout: Option<f64>,
+    /// A PEM file with an additional root certificate to trust
+    pub ca_certificate: Option<PathBuf>,
+    /// A PEM file with the certificate presented to the webserver, requires
+    /// `client_key`
+    pub client_certificate: Option<PathBuf>,
+    /// A PEM file with the PKCS #8 private key of `client_certificate`
+    pub client_key: Option<PathBuf>,
+    /// See [`ClientBuilder::danger_accept_invalid_certs`]
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl fmt::Debug for ClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientConfig")
+            .field("base_url", &self.base_url)
+            .field("api_path", &self.api_path)
+            .field("server", &self.server)
+            .field("api_key", &self.api_key.as_ref().map(|_| "[redacted]"))
+            .field("api_key_file", &self.api_key_file)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("timeout", &self.timeout)
+            .field("ca_certificate", &self.ca_certificate)
+            .field("client_certificate", &self.client_certificate)
+            .field("client_key", &self.client_key)
+            .field(
+                "danger_accept_invalid_certs",
+                &self.danger_accept_invalid_certs,
+            )
+            .finish()
+    }
+}
+
+impl ClientConfig {
+    /// Read a configuration from a file, as TOML when its extension is
+    /// `.toml`, as YAML when it is `.yaml` or `.yml` and as JSON otherwise
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let config = read_file(path)?;
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension {
+            Some("toml") => {
+                let config = String::from_utf8_lossy(&config);
+                toml::from_str(&config).map_err(|e| Error::Other(Box::new(e)))
+            }
+            Some("yaml" | "yml") => {
+                serde_yaml::from_slice(&config).map_err(|e| Error::Other(Box::new(e)))
+            }
+            _ => Ok(serde_json::from_slice(&config)?),
+        }
+    }
+
+    /// A ClientBuilder with the options of this configuration, reading the
+    /// files it refers to
+    pub fn builder(&self) -> Result<ClientBuilder, Error> {
+        let mut builder = Client::builder();
+        if let Some(base_url) = &self.base_url {
+            builder = builder.base_url(base_url);
+        }
+        if let Some(api_path) = &self.api_path {
+            builder = builder.api_path(api_path);
+        }
+        if let Some(server) = &self.server {
+            builder = builder.server_name(server);
+        }
+        if let Some(path) = &self.api_key_file {
+            let api_key = read_file(path)?;
+            builder = builder.api_key(String::from_utf8_lossy(&api_key).trim());
+        } else if let Some(api_key) = &self.api_key {
+            builder = builder.api_key(api_key);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(seconds(timeout)?);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(seconds(timeout)?);
+        }
+        if let Some(path) = &self.ca_certificate {
+            builder = builder.add_root_certificate(Certificate::from_pem(&read_file(path)?)?);
+        }
+        match (&self.client_certificate, &self.client_key) {
+            (Some(certificate), Some(key)) => {
+                let identity =
+                    Identity::from_pkcs8_pem(&read_file(certificate)?, &read_file(key)?)?;
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(Error::Other(
+                    "client_certificate and client_key must be set together".into(),
+                ))
+            }
+        }
+        Ok(builder.danger_accept_invalid_certs(self.danger_accept_invalid_certs))
+    }
+
+    /// Build a Client with the options of this configuration
+    pub fn build(&self) -> Result<Client, Error> {
+        self.builder()?.build()
+    }
+}
+
+impl Client {
+    /// Create a Client from a configuration file, see
+    /// [`ClientConfig::from_file`]
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        ClientConfig::from_file(path)?.build()
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|source| Error::ReadFile {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn seconds(seconds: f64) -> Result<Duration, Error> {
+    Duration::try_from_secs_f64(seconds).map_err(|e| Error::Other(Box::new(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::config::ClientConfig;
+    use crate::mock::MockServer;
+    use crate::{Client, Error};
+
+    #[tokio::test]
+    async fn client_from_config_file() {
+        let server = MockServer::start();
+        let dir = std::env::temp_dir().join(format!("powerdns-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let key_file = dir.join("api-key");
+        fs::write(&key_file, format!("{}\n", server.api_key())).unwrap();
+        let config = serde_json::json!({
+            "base_url": server.base_url(),
+            "server": "localhost",
+            "api_key": "ignored",
+            "api_key_file": key_file,
+            "timeout": 5,
+        });
+        let config_file = dir.join("config.json");
+        fs::write(&config_file, config.to_string()).unwrap();
+
+        let client = Client::from_config_file(&config_file).unwrap();
+        assert!(client.zone().list().await.is_ok());
+
+        fs::remove_file(&key_file).unwrap();
+        assert!(matches!(
+            Client::from_config_file(&config_file),
+            Err(Error::ReadFile { path, .. }) if path == key_file
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn read_toml_and_yaml() {
+        let dir = std::env::temp_dir().join(format!("powerdns-formats-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let expected = ClientConfig {
+            base_url: Some(String::from("https://dns.internal:8081")),
+            server: Some(String::from("localhost")),
+            timeout: Some(30.0),
+            ..Default::default()
+        };
+
+        let toml = dir.join("config.toml");
+        fs::write(
+            &toml,
+            "base_url = \"https://dns.internal:8081\"\nserver = \"localhost\"\ntimeout = 30.0\n",
+        )
+        .unwrap();
+        assert_eq!(ClientConfig::from_file(&toml).unwrap(), expected);
+
+        let yaml = dir.join("config.yml");
+        fs::write(
+            &yaml,
+            "base_url: https://dns.internal:8081\nserver: localhost\ntimeout: 30\n",
+        )
+        .unwrap();
+        assert_eq!(ClientConfig::from_file(&yaml).unwrap(), expected);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn redact_api_key() {
+        let config = ClientConfig {
+            api_key: Some(String::from("secret")),
+            ..Default::default()
+        };
+        let debug = format!("{config:?}");
+        assert!(debug.contains("api_key: Some(\"[redacted]\")"));
+        assert!(!debug.contains("secret"));
+    }
+
+    #[test]
+    fn reject_invalid_config() {
+        let unknown =
+            serde_json::from_value::<ClientConfig>(serde_json::json!({"api-key": "secret"}));
+        assert!(unknown.is_err());
+
+        let config = ClientConfig {
+            client_certificate: Some("client.pem".into()),
+            ..Default::default()
+        };
+        assert!(config.build().is_err());
+    }
+}
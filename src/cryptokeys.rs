@@ -0,0 +1,231 @@
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::Client;
+use crate::Error;
+use crate::error::decode;
+
+/// Describes a DNSSEC cryptographic key
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Cryptokey {
+    /// Set to “Cryptokey”
+    #[serde(rename = "type")]
+    pub type_field: Option<String>,
+    /// The internal identifier, read only
+    pub id: Option<u64>,
+    /// The type of the key, one of “ksk”, “zsk” or “csk”
+    pub keytype: Option<KeyType>,
+    /// Whether or not the key is in active use
+    pub active: Option<bool>,
+    /// Whether or not the DNSKEY record is published in the zone
+    pub published: Option<bool>,
+    /// The DNSKEY record for this key
+    pub dnskey: Option<String>,
+    /// An array of DS records for this key
+    pub ds: Option<Vec<String>>,
+    /// An array of DS records for this key, filtered by CDS publication
+    /// settings
+    pub cds: Option<Vec<String>>,
+    /// The private key in ISC format
+    pub privatekey: Option<String>,
+    /// The name of the algorithm of the key, should be a mnemonic
+    pub algorithm: Option<String>,
+    /// The size of the key
+    pub bits: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+    Ksk,
+    Zsk,
+    Csk,
+}
+
+pub struct CryptokeyClient<'a> {
+    api_client: &'a Client,
+    zone_id: String,
+}
+
+impl<'a> CryptokeyClient<'a> {
+    pub fn new(api_client: &'a Client, zone_id: &str) -> Self {
+        CryptokeyClient {
+            api_client,
+            zone_id: zone_id.to_string(),
+        }
+    }
+
+    fn url(&self) -> String {
+        format!(
+            "{}/servers/{}/zones/{}/cryptokeys",
+            self.api_client.api_url, self.api_client.server_name, self.zone_id
+        )
+    }
+
+    /// Get all CryptoKeys for a zone, except the privatekey
+    pub async fn list(&self) -> Result<Vec<Cryptokey>, Error> {
+        let response = self.api_client.get(self.url()).send().await?;
+
+        match response.status() {
+            // 200 OK – List of Cryptokey objects Returns: array of Cryptokey objects
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<Vec<Cryptokey>>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Returns all data about the CryptoKey, including the privatekey
+    pub async fn get(&self, cryptokey_id: u64) -> Result<Cryptokey, Error> {
+        let response = self
+            .api_client
+            .get(format!("{}/{cryptokey_id}", self.url()))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – Cryptokey Returns: Cryptokey object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<Cryptokey>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Creates a Cryptokey
+    ///
+    /// This method adds a new key to a zone. The key can either be generated
+    /// or imported by supplying the `privatekey`. Only `keytype` is required;
+    /// `algorithm` and `bits` MAY be given when generating a key.
+    pub async fn create(&self, cryptokey: Cryptokey) -> Result<Cryptokey, Error> {
+        let response = self
+            .api_client
+            .post(self.url())
+            .json(&cryptokey)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 201 Created – Created Returns: Cryptokey object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::CREATED => Ok(decode::<Cryptokey>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// This method (de)activates a key specified by `cryptokey_id`. Only the
+    /// `active` and `published` fields of `cryptokey` are taken into account.
+    pub async fn update(&self, cryptokey_id: u64, cryptokey: Cryptokey) -> Result<(), Error> {
+        let response = self
+            .api_client
+            .put(format!("{}/{cryptokey_id}", self.url()))
+            .json(&cryptokey)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 204 No Content – OK
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Activates the key specified by `cryptokey_id`
+    pub async fn activate(&self, cryptokey_id: u64) -> Result<(), Error> {
+        self.set_active(cryptokey_id, true).await
+    }
+
+    /// Deactivates the key specified by `cryptokey_id`
+    pub async fn deactivate(&self, cryptokey_id: u64) -> Result<(), Error> {
+        self.set_active(cryptokey_id, false).await
+    }
+
+    async fn set_active(&self, cryptokey_id: u64, active: bool) -> Result<(), Error> {
+        let cryptokey = Cryptokey {
+            active: Some(active),
+            ..Default::default()
+        };
+        self.update(cryptokey_id, cryptokey).await
+    }
+
+    /// Publishes the DNSKEY record of the key specified by `cryptokey_id`
+    pub async fn publish(&self, cryptokey_id: u64) -> Result<(), Error> {
+        self.set_published(cryptokey_id, true).await
+    }
+
+    /// Removes the DNSKEY record of the key specified by `cryptokey_id` from
+    /// the zone without changing whether the key is active
+    pub async fn unpublish(&self, cryptokey_id: u64) -> Result<(), Error> {
+        self.set_published(cryptokey_id, false).await
+    }
+
+    async fn set_published(&self, cryptokey_id: u64, published: bool) -> Result<(), Error> {
+        let cryptokey = Cryptokey {
+            published: Some(published),
+            ..Default::default()
+        };
+        self.update(cryptokey_id, cryptokey).await
+    }
+
+    /// This method deletes a key specified by `cryptokey_id`
+    pub async fn delete(&self, cryptokey_id: u64) -> Result<(), Error> {
+        let response = self
+            .api_client
+            .delete(format!("{}/{cryptokey_id}", self.url()))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 204 No Content – OK
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cryptokeys::{Cryptokey, KeyType};
+
+    #[test]
+    fn deserialize_cryptokey() {
+        let key: Cryptokey = serde_json::from_value(serde_json::json!({
+            "type": "Cryptokey",
+            "id": 1,
+            "keytype": "csk",
+            "active": true,
+            "published": true,
+            "dnskey": "257 3 13 dGVzdA==",
+            "ds": ["12345 13 2 abcdef"],
+            "algorithm": "ECDSAP256SHA256",
+            "bits": 256
+        }))
+        .unwrap();
+
+        assert_eq!(key.id, Some(1));
+        assert_eq!(key.keytype, Some(KeyType::Csk));
+        assert_eq!(key.privatekey, None);
+    }
+}
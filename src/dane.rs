@@ -0,0 +1,474 @@
+//! Generate TLSA records from certificates, for DANE (RFC 6698), and SSHFP
+//! records from SSH host keys (RFC 4255).
+//!
+//! ```no_run
+//! # async fn example(client: powerdns::Client, pem: &[u8], host_key: &str) -> Result<(), Box<dyn std::error::Error>> {
+//! use powerdns::dane::{Sshfp, SshfpType, Tlsa, TlsaMatching, TlsaSelector, TlsaUsage};
+//!
+//! let tlsa = Tlsa::from_certificate(
+//!     pem,
+//!     TlsaUsage::DaneEe,
+//!     TlsaSelector::SubjectPublicKeyInfo,
+//!     TlsaMatching::Sha256,
+//! )?;
+//! let name = Tlsa::name(443, "tcp", "www.example.com");
+//! client.zone().upsert_tlsa("example.com", &name, 3600, &tlsa).await?;
+//!
+//! let sshfp = Sshfp::from_public_key(host_key, SshfpType::Sha256)?;
+//! client.zone().upsert_sshfp("example.com", "host.example.com", 3600, &sshfp).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use openssl::x509::X509;
+use thiserror::Error;
+
+use crate::names::IntoZoneId;
+use crate::rdata::RDataError;
+use crate::record_type::RecordType;
+use crate::zones::{absolute_name, ZoneClient};
+use crate::Error;
+
+/// Returned when a certificate or public key cannot be read
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DaneError {
+    #[error("invalid certificate: {0}")]
+    InvalidCertificate(String),
+
+    #[error("invalid SSH public key: {0}")]
+    InvalidPublicKey(String),
+
+    #[error("SSH key type {0:?} has no SSHFP algorithm number")]
+    UnsupportedKeyType(String),
+}
+
+/// Which certificates of a chain a TLSA record constrains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsaUsage {
+    /// A CA certificate, which must also be trusted by the client
+    PkixTa = 0,
+    /// The end entity certificate, which must also be trusted by the client
+    PkixEe = 1,
+    /// A trust anchor, without requiring a CA trusted by the client
+    DaneTa = 2,
+    /// The end entity certificate, without requiring a CA trusted by the
+    /// client
+    DaneEe = 3,
+}
+
+/// Which part of the certificate a TLSA record matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsaSelector {
+    FullCertificate = 0,
+    /// The public key, so the record survives renewals that keep the key
+    SubjectPublicKeyInfo = 1,
+}
+
+/// How the selected data is stored in a TLSA record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsaMatching {
+    /// The data itself, in DER
+    Full = 0,
+    Sha256 = 1,
+    Sha512 = 2,
+}
+
+/// A TLSA record, formatted as e.g. `3 1 1 {hex digest}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tlsa {
+    pub usage: u8,
+    pub selector: u8,
+    pub matching: u8,
+    pub data: Vec<u8>,
+}
+
+impl Tlsa {
+    /// The record matching `certificate`, in PEM or DER. Of a PEM chain,
+    /// the first certificate is used.
+    pub fn from_certificate(
+        certificate: &[u8],
+        usage: TlsaUsage,
+        selector: TlsaSelector,
+        matching: TlsaMatching,
+    ) -> Result<Tlsa, DaneError> {
+        let error = |e: openssl::error::ErrorStack| DaneError::InvalidCertificate(e.to_string());
+        let certificate = if certificate.starts_with(b"-----") {
+            X509::from_pem(certificate).map_err(error)?
+        } else {
+            X509::from_der(certificate).map_err(error)?
+        };
+        let selected = match selector {
+            TlsaSelector::FullCertificate => certificate.to_der().map_err(error)?,
+            TlsaSelector::SubjectPublicKeyInfo => certificate
+                .public_key()
+                .and_then(|key| key.public_key_to_der())
+                .map_err(error)?,
+        };
+        let data = match matching {
+            TlsaMatching::Full => selected,
+            TlsaMatching::Sha256 => openssl::sha::sha256(&selected).to_vec(),
+            TlsaMatching::Sha512 => openssl::sha::sha512(&selected).to_vec(),
+        };
+        Ok(Tlsa {
+            usage: usage as u8,
+            selector: selector as u8,
+            matching: matching as u8,
+            data,
+        })
+    }
+
+    /// The name of the TLSA RRSet of a service, e.g.
+    /// `_443._tcp.www.example.com.`
+    pub fn name(port: u16, protocol: &str, domain: &str) -> String {
+        format!("_{port}._{protocol}.{}", absolute_name(domain))
+    }
+
+    /// Format the record as content in the format PowerDNS expects
+    pub fn to_content(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Tlsa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.usage,
+            self.selector,
+            self.matching,
+            hex(&self.data)
+        )
+    }
+}
+
+impl FromStr for Tlsa {
+    type Err = RDataError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        let error = || RDataError {
+            rtype: RecordType::TLSA,
+            content: content.to_string(),
+        };
+        let fields: Vec<&str> = content.split_whitespace().collect();
+        let [usage, selector, matching, data @ ..] = fields.as_slice() else {
+            return Err(error());
+        };
+        Ok(Tlsa {
+            usage: usage.parse().map_err(|_| error())?,
+            selector: selector.parse().map_err(|_| error())?,
+            matching: matching.parse().map_err(|_| error())?,
+            data: from_hex(&data.concat()).ok_or_else(error)?,
+        })
+    }
+}
+
+/// The algorithm of the key an SSHFP record is the fingerprint of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SshfpAlgorithm {
+    Rsa = 1,
+    Dsa = 2,
+    Ecdsa = 3,
+    Ed25519 = 4,
+    Ed448 = 6,
+}
+
+impl SshfpAlgorithm {
+    /// The algorithm of an SSH key type, e.g. `ssh-ed25519`
+    pub fn from_key_type(key_type: &str) -> Option<Self> {
+        match key_type {
+            "ssh-rsa" => Some(SshfpAlgorithm::Rsa),
+            "ssh-dss" => Some(SshfpAlgorithm::Dsa),
+            "ssh-ed25519" => Some(SshfpAlgorithm::Ed25519),
+            "ssh-ed448" => Some(SshfpAlgorithm::Ed448),
+            t if t.starts_with("ecdsa-sha2-") => Some(SshfpAlgorithm::Ecdsa),
+            _ => None,
+        }
+    }
+}
+
+/// The hash of an SSHFP fingerprint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SshfpType {
+    Sha1 = 1,
+    Sha256 = 2,
+}
+
+/// An SSHFP record, formatted as e.g. `4 2 {hex fingerprint}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sshfp {
+    pub algorithm: u8,
+    pub fingerprint_type: u8,
+    pub fingerprint: Vec<u8>,
+}
+
+impl Sshfp {
+    /// The record for a public key in the format of OpenSSH, e.g. a line of
+    /// `/etc/ssh/ssh_host_ed25519_key.pub`
+    pub fn from_public_key(
+        public_key: &str,
+        fingerprint_type: SshfpType,
+    ) -> Result<Sshfp, DaneError> {
+        let invalid = |reason: &str| DaneError::InvalidPublicKey(reason.to_string());
+        let mut fields = public_key.split_whitespace();
+        let (Some(key_type), Some(blob)) = (fields.next(), fields.next()) else {
+            return Err(invalid("expected a key type and a base64 key"));
+        };
+        let algorithm = SshfpAlgorithm::from_key_type(key_type)
+            .ok_or_else(|| DaneError::UnsupportedKeyType(key_type.to_string()))?;
+        let blob = openssl::base64::decode_block(blob).map_err(|_| invalid("key is not base64"))?;
+
+        // the key starts with its type, as a string prefixed by its length
+        let embedded = blob
+            .get(..4)
+            .map(|len| u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize)
+            .and_then(|len| blob.get(4..4 + len));
+        if embedded != Some(key_type.as_bytes()) {
+            return Err(invalid("key does not match its type"));
+        }
+
+        let fingerprint = match fingerprint_type {
+            SshfpType::Sha1 => openssl::sha::sha1(&blob).to_vec(),
+            SshfpType::Sha256 => openssl::sha::sha256(&blob).to_vec(),
+        };
+        Ok(Sshfp {
+            algorithm: algorithm as u8,
+            fingerprint_type: fingerprint_type as u8,
+            fingerprint,
+        })
+    }
+
+    /// Format the record as content in the format PowerDNS expects
+    pub fn to_content(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Sshfp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.algorithm,
+            self.fingerprint_type,
+            hex(&self.fingerprint)
+        )
+    }
+}
+
+impl FromStr for Sshfp {
+    type Err = RDataError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        let error = || RDataError {
+            rtype: RecordType::SSHFP,
+            content: content.to_string(),
+        };
+        let fields: Vec<&str> = content.split_whitespace().collect();
+        let [algorithm, fingerprint_type, fingerprint @ ..] = fields.as_slice() else {
+            return Err(error());
+        };
+        Ok(Sshfp {
+            algorithm: algorithm.parse().map_err(|_| error())?,
+            fingerprint_type: fingerprint_type.parse().map_err(|_| error())?,
+            fingerprint: from_hex(&fingerprint.concat()).ok_or_else(error)?,
+        })
+    }
+}
+
+fn hex(data: &[u8]) -> String {
+    let mut hex = String::with_capacity(data.len() * 2);
+    for byte in data {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.is_empty() || !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+impl ZoneClient<'_> {
+    /// Add `tlsa` to the TLSA RRSet `name`, see [`Tlsa::name`], keeping the
+    /// records of other certificates, e.g. during a key rollover
+    pub async fn upsert_tlsa(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        tlsa: &Tlsa,
+    ) -> Result<(), Error> {
+        self.upsert_record(zone_id, name, RecordType::TLSA, ttl, &tlsa.to_content())
+            .await
+    }
+
+    /// Add `sshfp` to the SSHFP RRSet of the host `name`, keeping the
+    /// fingerprints of its other host keys
+    pub async fn upsert_sshfp(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        sshfp: &Sshfp,
+    ) -> Result<(), Error> {
+        self.upsert_record(zone_id, name, RecordType::SSHFP, ttl, &sshfp.to_content())
+            .await
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use openssl::asn1::Asn1Time;
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::hash::MessageDigest;
+    use openssl::nid::Nid;
+    use openssl::pkey::PKey;
+    use openssl::x509::{X509NameBuilder, X509};
+
+    use crate::dane::{DaneError, Sshfp, SshfpType, Tlsa, TlsaMatching, TlsaSelector, TlsaUsage};
+    use crate::record_type::RecordType;
+    use crate::zones::{Zone, ZoneKind};
+
+    const HOST_KEY: &str = "ssh-ed25519 \
+        AAAAC3NzaC1lZDI1NTE5AAAAIGx4AMnkWmcE6fSXsTN03nw6rXs5P1a7DFuhNJhwkBXi root@host";
+
+    fn certificate() -> (X509, PKey<openssl::pkey::Private>) {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "www.example.com").unwrap();
+        let name = name.build();
+        let mut builder = X509::builder().unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        (builder.build(), key)
+    }
+
+    #[test]
+    fn tlsa_from_certificate() {
+        let (certificate, key) = certificate();
+        let pem = certificate.to_pem().unwrap();
+        let tlsa = Tlsa::from_certificate(
+            &pem,
+            TlsaUsage::DaneEe,
+            TlsaSelector::SubjectPublicKeyInfo,
+            TlsaMatching::Sha256,
+        )
+        .unwrap();
+        let spki = key.public_key_to_der().unwrap();
+        assert_eq!(tlsa.data, openssl::sha::sha256(&spki));
+        assert!(tlsa.to_content().starts_with("3 1 1 "));
+        assert_eq!(tlsa.to_content().len(), 6 + 64);
+        assert_eq!(tlsa.to_content().parse::<Tlsa>().unwrap(), tlsa);
+
+        let der = certificate.to_der().unwrap();
+        let full = Tlsa::from_certificate(
+            &der,
+            TlsaUsage::PkixEe,
+            TlsaSelector::FullCertificate,
+            TlsaMatching::Full,
+        )
+        .unwrap();
+        assert_eq!(full.data, der);
+        assert!(matches!(
+            Tlsa::from_certificate(
+                b"not a certificate",
+                TlsaUsage::DaneEe,
+                TlsaSelector::FullCertificate,
+                TlsaMatching::Sha512,
+            ),
+            Err(DaneError::InvalidCertificate(_))
+        ));
+        assert_eq!(
+            Tlsa::name(25, "tcp", "mail.example.com"),
+            "_25._tcp.mail.example.com."
+        );
+    }
+
+    #[test]
+    fn sshfp_from_public_key() {
+        let sshfp = Sshfp::from_public_key(HOST_KEY, SshfpType::Sha256).unwrap();
+        assert_eq!(sshfp.algorithm, 4);
+        assert_eq!(sshfp.fingerprint.len(), 32);
+        assert!(sshfp.to_content().starts_with("4 2 "));
+        assert_eq!(sshfp.to_content().parse::<Sshfp>().unwrap(), sshfp);
+
+        let sha1 = Sshfp::from_public_key(HOST_KEY, SshfpType::Sha1).unwrap();
+        assert_eq!(sha1.fingerprint.len(), 20);
+
+        let mismatched = HOST_KEY.replace("ssh-ed25519", "ssh-rsa");
+        assert!(matches!(
+            Sshfp::from_public_key(&mismatched, SshfpType::Sha256),
+            Err(DaneError::InvalidPublicKey(_))
+        ));
+        assert_eq!(
+            Sshfp::from_public_key("ssh-foo AAAA", SshfpType::Sha256),
+            Err(DaneError::UnsupportedKeyType(String::from("ssh-foo")))
+        );
+    }
+
+    #[tokio::test]
+    async fn upsert_records() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+
+        let zones = client.zone();
+        for fingerprint_type in [SshfpType::Sha1, SshfpType::Sha256] {
+            let sshfp = Sshfp::from_public_key(HOST_KEY, fingerprint_type).unwrap();
+            zones
+                .upsert_sshfp("example.com", "host.example.com", 3600, &sshfp)
+                .await
+                .unwrap();
+        }
+        let rrset = zones
+            .get_rrset("example.com", "host.example.com", RecordType::SSHFP)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rrset.records.len(), 2);
+        assert!(rrset.records[1].content.starts_with("4 2 "));
+
+        let (certificate, _) = certificate();
+        let tlsa = Tlsa::from_certificate(
+            &certificate.to_der().unwrap(),
+            TlsaUsage::DaneEe,
+            TlsaSelector::SubjectPublicKeyInfo,
+            TlsaMatching::Sha256,
+        )
+        .unwrap();
+        let name = Tlsa::name(443, "tcp", "www.example.com");
+        zones
+            .upsert_tlsa("example.com", &name, 3600, &tlsa)
+            .await
+            .unwrap();
+        let rrset = zones
+            .get_rrset("example.com", &name, RecordType::TLSA)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rrset.records[0].content, tlsa.to_content());
+    }
+}
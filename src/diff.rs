@@ -0,0 +1,409 @@
+//! Compare the rrsets of a zone with a desired state, and turn the
+//! differences into a [`PatchZone`].
+//!
+//! A [`ZoneDiff`] displays as a terraform-style list of changes, e.g. to show
+//! operators what will change before applying it:
+//!
+//! ```text
+//! ~ mail.example.com. A
+//!     ~ ttl 300 -> 60
+//! + new.example.com. 300 TXT "hello"
+//! - old.example.com. 300 CNAME www.example.com.
+//! ~ www.example.com. A
+//!     + 192.0.2.3
+//!     - 192.0.2.1
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::record_type::RecordType;
+#[allow(deprecated)]
+use crate::zones::{absolute_name, Comment, PatchZone, RRSet, RRSetUpdate, Record, Zone};
+
+/// The differences between the current and the desired rrsets of a zone.
+/// RRSets are identified by their name and type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ZoneDiff {
+    /// RRSets that only exist in the desired state
+    pub added: Vec<RRSet>,
+    /// RRSets that only exist in the current state
+    pub removed: Vec<RRSet>,
+    /// RRSets whose TTL, records or comments differ
+    pub changed: Vec<ChangedRRSet>,
+}
+
+/// An RRSet that exists in both states, but with different data
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedRRSet {
+    pub current: RRSet,
+    pub desired: RRSet,
+}
+
+#[allow(deprecated)]
+impl ZoneDiff {
+    /// The differences between the rrsets of two zones. Zones without
+    /// rrsets, e.g. from a zone list, are treated as empty.
+    pub fn between(current: &Zone, desired: &Zone) -> Self {
+        ZoneDiff::from_rrsets(
+            current.rrsets.as_deref().unwrap_or_default(),
+            desired.rrsets.as_deref().unwrap_or_default(),
+        )
+    }
+
+    /// The differences between the current rrsets of a zone and the desired
+    /// ones. Comments of a desired RRSet are only compared when they are set,
+    /// and the order of records and comments is ignored.
+    pub fn from_rrsets(current: &[RRSet], desired: &[RRSet]) -> Self {
+        let mut current = by_key(current);
+        let mut diff = ZoneDiff::default();
+
+        for (key, desired) in by_key(desired) {
+            match current.remove(&key) {
+                None => diff.added.push(desired),
+                Some(current) if current.matches(&desired) => {}
+                Some(current) => diff.changed.push(ChangedRRSet { current, desired }),
+            }
+        }
+        diff.removed = current.into_values().collect();
+
+        diff
+    }
+
+    /// Whether both states are the same
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Drop the removed rrsets that must be kept according to `options`
+    pub(crate) fn restrict(&mut self, options: &ApplyOptions) {
+        if options.prune {
+            self.removed
+                .retain(|rrset| !options.protected_types.contains(&rrset.type_field));
+        } else {
+            self.removed.clear();
+        }
+    }
+
+    /// The patch that turns the current state into the desired one
+    pub fn into_patch(self) -> PatchZone {
+        let replaced = self
+            .added
+            .into_iter()
+            .chain(self.changed.into_iter().map(|changed| changed.desired))
+            .map(|rrset| {
+                // the server rejects replacing an RRSet with nothing
+                if rrset.records.is_empty() && rrset.comments.as_ref().is_none_or(Vec::is_empty) {
+                    RRSetUpdate::delete(&rrset.name, rrset.type_field)
+                } else {
+                    RRSetUpdate::replace(rrset)
+                }
+            });
+        let deleted = self.removed.into_iter().map(|rrset| RRSetUpdate::Delete {
+            name: rrset.name,
+            rtype: rrset.type_field,
+        });
+
+        replaced.chain(deleted).collect()
+    }
+}
+
+impl fmt::Display for ZoneDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_indented(f, "")
+    }
+}
+
+impl ZoneDiff {
+    /// Write the changes ordered by name and type, each line prefixed by
+    /// `indent`
+    pub(crate) fn write_indented(&self, f: &mut fmt::Formatter<'_>, indent: &str) -> fmt::Result {
+        enum Entry<'a> {
+            Added(&'a RRSet),
+            Removed(&'a RRSet),
+            Changed(&'a ChangedRRSet),
+        }
+        let mut entries: Vec<((String, &str), Entry)> = self
+            .added
+            .iter()
+            .map(|rrset| (key(rrset), Entry::Added(rrset)))
+            .chain(
+                self.removed
+                    .iter()
+                    .map(|rrset| (key(rrset), Entry::Removed(rrset))),
+            )
+            .chain(
+                self.changed
+                    .iter()
+                    .map(|changed| (key(&changed.desired), Entry::Changed(changed))),
+            )
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (_, entry) in entries {
+            match entry {
+                Entry::Added(rrset) => write_rrset(f, indent, '+', rrset)?,
+                Entry::Removed(rrset) => write_rrset(f, indent, '-', rrset)?,
+                Entry::Changed(ChangedRRSet { current, desired }) => {
+                    writeln!(f, "{indent}~ {} {}", desired.name, desired.type_field)?;
+                    if current.ttl != desired.ttl {
+                        writeln!(f, "{indent}    ~ ttl {} -> {}", current.ttl, desired.ttl)?;
+                    }
+                    let records = |rrset: &'_ RRSet| -> Vec<String> {
+                        let mut records: Vec<String> = rrset.records.iter().map(record).collect();
+                        records.sort();
+                        records
+                    };
+                    let (current_records, desired_records) = (records(current), records(desired));
+                    for added in desired_records
+                        .iter()
+                        .filter(|r| !current_records.contains(r))
+                    {
+                        writeln!(f, "{indent}    + {added}")?;
+                    }
+                    for removed in current_records
+                        .iter()
+                        .filter(|r| !desired_records.contains(r))
+                    {
+                        writeln!(f, "{indent}    - {removed}")?;
+                    }
+                    if let Some(comments) = &desired.comments {
+                        let current = current.comments.as_deref().unwrap_or_default();
+                        if !same_comments(current, comments) {
+                            writeln!(f, "{indent}    ~ comments")?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The name and type an RRSet is ordered by
+fn key(rrset: &RRSet) -> (String, &str) {
+    (absolute_name(&rrset.name), rrset.type_field.as_str())
+}
+
+/// Write every record of an RRSet that is added or removed as a whole
+fn write_rrset(f: &mut fmt::Formatter<'_>, indent: &str, sign: char, rrset: &RRSet) -> fmt::Result {
+    if rrset.records.is_empty() {
+        return writeln!(
+            f,
+            "{indent}{sign} {} {} {}",
+            rrset.name, rrset.ttl, rrset.type_field
+        );
+    }
+    for content in rrset.records.iter().map(record) {
+        writeln!(
+            f,
+            "{indent}{sign} {} {} {} {content}",
+            rrset.name, rrset.ttl, rrset.type_field
+        )?;
+    }
+    Ok(())
+}
+
+fn record(record: &Record) -> String {
+    if record.disabled.unwrap_or(false) {
+        format!("{} (disabled)", record.content)
+    } else {
+        record.content.clone()
+    }
+}
+
+fn same_comments(a: &[Comment], b: &[Comment]) -> bool {
+    let sorted = |comments: &[Comment]| {
+        let mut comments: Vec<(String, String)> = comments
+            .iter()
+            .map(|c| (c.content.clone(), c.account.clone()))
+            .collect();
+        comments.sort();
+        comments
+    };
+    sorted(a) == sorted(b)
+}
+
+/// Options for [`ZoneClient::apply`](crate::zones::ZoneClient::apply)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplyOptions {
+    /// Whether to delete rrsets that are not part of the desired state. By
+    /// default they are left untouched, so the desired state only manages
+    /// the rrsets it contains.
+    pub prune: bool,
+    /// Types of rrsets that are never pruned, defaults to SOA
+    pub protected_types: Vec<RecordType>,
+}
+
+impl Default for ApplyOptions {
+    fn default() -> Self {
+        ApplyOptions {
+            prune: false,
+            protected_types: vec![RecordType::SOA],
+        }
+    }
+}
+
+impl From<ZoneDiff> for PatchZone {
+    fn from(diff: ZoneDiff) -> Self {
+        diff.into_patch()
+    }
+}
+
+/// RRSets ordered by name and type, with normalized names
+fn by_key(rrsets: &[RRSet]) -> BTreeMap<(String, String), RRSet> {
+    rrsets
+        .iter()
+        .map(|rrset| {
+            let name = absolute_name(&rrset.name);
+            let key = (name.clone(), rrset.type_field.to_string());
+            (
+                key,
+                RRSet {
+                    name,
+                    ..rrset.clone()
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::ZoneDiff;
+    use crate::record_type::RecordType;
+    use crate::zones::{RRSet, RRSetUpdate, Record};
+
+    fn rrset(name: &str, type_field: RecordType, ttl: u32, content: &[&str]) -> RRSet {
+        RRSet {
+            name: name.to_string(),
+            type_field,
+            ttl,
+            changetype: None,
+            records: content
+                .iter()
+                .map(|content| Record {
+                    content: content.to_string(),
+                    disabled: Some(false),
+                })
+                .collect(),
+            comments: None,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn diff_rrsets() {
+        let current = [
+            rrset(
+                "www.example.com.",
+                RecordType::A,
+                300,
+                &["192.0.2.1", "192.0.2.2"],
+            ),
+            rrset("mail.example.com.", RecordType::A, 300, &["192.0.2.3"]),
+            rrset(
+                "old.example.com.",
+                RecordType::CNAME,
+                300,
+                &["www.example.com."],
+            ),
+        ];
+        let desired = [
+            rrset(
+                "WWW.example.com",
+                RecordType::A,
+                300,
+                &["192.0.2.2", "192.0.2.1"],
+            ),
+            rrset("mail.example.com.", RecordType::A, 60, &["192.0.2.3"]),
+            rrset("new.example.com.", RecordType::TXT, 300, &["\"hello\""]),
+        ];
+
+        let diff = ZoneDiff::from_rrsets(&current, &desired);
+        assert_eq!(diff.added, [desired[2].clone()]);
+        assert_eq!(diff.removed, [current[2].clone()]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].desired.ttl, 60);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn diff_into_patch() {
+        let current = [rrset(
+            "old.example.com.",
+            RecordType::A,
+            300,
+            &["192.0.2.1"],
+        )];
+        let desired = [rrset(
+            "new.example.com.",
+            RecordType::A,
+            300,
+            &["192.0.2.1"],
+        )];
+
+        let patch = ZoneDiff::from_rrsets(&current, &desired).into_patch();
+        assert_eq!(patch.rrsets.len(), 2);
+        assert!(
+            matches!(&patch.rrsets[0], RRSetUpdate::Replace(rrset) if rrset.name == "new.example.com.")
+        );
+        assert_eq!(
+            patch.rrsets[1],
+            RRSetUpdate::delete("old.example.com.", RecordType::A)
+        );
+    }
+
+    #[test]
+    fn display_diff() {
+        let current = [
+            rrset(
+                "www.example.com.",
+                RecordType::A,
+                300,
+                &["192.0.2.1", "192.0.2.2"],
+            ),
+            rrset("mail.example.com.", RecordType::A, 300, &["192.0.2.25"]),
+            rrset(
+                "old.example.com.",
+                RecordType::CNAME,
+                300,
+                &["www.example.com."],
+            ),
+        ];
+        let desired = [
+            rrset(
+                "www.example.com.",
+                RecordType::A,
+                300,
+                &["192.0.2.2", "192.0.2.3"],
+            ),
+            rrset("mail.example.com.", RecordType::A, 60, &["192.0.2.25"]),
+            rrset("new.example.com.", RecordType::TXT, 300, &["\"hello\""]),
+        ];
+
+        let diff = ZoneDiff::from_rrsets(&current, &desired);
+        assert_eq!(
+            diff.to_string(),
+            "~ mail.example.com. A\n\
+             \x20   ~ ttl 300 -> 60\n\
+             + new.example.com. 300 TXT \"hello\"\n\
+             - old.example.com. 300 CNAME www.example.com.\n\
+             ~ www.example.com. A\n\
+             \x20   + 192.0.2.3\n\
+             \x20   - 192.0.2.1\n"
+        );
+        assert_eq!(ZoneDiff::default().to_string(), "");
+    }
+
+    #[test]
+    fn identical_rrsets() {
+        let rrsets = [rrset(
+            "www.example.com.",
+            RecordType::A,
+            300,
+            &["192.0.2.1"],
+        )];
+        assert!(ZoneDiff::from_rrsets(&rrsets, &rrsets).is_empty());
+    }
+}
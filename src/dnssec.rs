@@ -0,0 +1,128 @@
+//! Enable and disable DNSSEC for a zone, coordinating the cryptokeys and
+//! zone endpoints.
+
+use crate::cryptokeys::{Cryptokey, KeyType};
+use crate::names::IntoZoneId;
+use crate::zones::ZoneClient;
+use crate::Error;
+
+/// The keys created by [`ZoneClient::secure_zone`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnssecPolicy {
+    /// Create a separate KSK and ZSK instead of a single CSK
+    pub split_keys: bool,
+    /// The algorithm of the keys, defaults to `ecdsap256sha256`
+    pub algorithm: String,
+    /// The size of the KSK or CSK, for algorithms with variable key sizes
+    pub ksk_bits: Option<u32>,
+    /// The size of the ZSK, for algorithms with variable key sizes
+    pub zsk_bits: Option<u32>,
+}
+
+impl Default for DnssecPolicy {
+    fn default() -> Self {
+        DnssecPolicy {
+            split_keys: false,
+            algorithm: String::from("ecdsap256sha256"),
+            ksk_bits: None,
+            zsk_bits: None,
+        }
+    }
+}
+
+impl ZoneClient<'_> {
+    /// Sign a zone: create active keys according to `policy` and rectify the
+    /// zone. Returns the DS records to publish in the parent zone.
+    pub async fn secure_zone(
+        &self,
+        zone_id: impl IntoZoneId,
+        policy: &DnssecPolicy,
+    ) -> Result<Vec<String>, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let cryptokeys = self.api_client().cryptokeys(&zone_id);
+
+        let key = |keytype, bits| Cryptokey {
+            keytype: Some(keytype),
+            active: Some(true),
+            published: Some(true),
+            algorithm: Some(policy.algorithm.clone()),
+            bits,
+            ..Default::default()
+        };
+        let ksk = if policy.split_keys {
+            let ksk = cryptokeys
+                .create(key(KeyType::Ksk, policy.ksk_bits))
+                .await?;
+            cryptokeys
+                .create(key(KeyType::Zsk, policy.zsk_bits))
+                .await?;
+            ksk
+        } else {
+            cryptokeys
+                .create(key(KeyType::Csk, policy.ksk_bits))
+                .await?
+        };
+
+        self.rectify(&zone_id).await?;
+
+        match (ksk.ds, ksk.id) {
+            (Some(ds), _) => Ok(ds),
+            (None, Some(id)) => Ok(cryptokeys.get(id).await?.ds.unwrap_or_default()),
+            (None, None) => Ok(Vec::new()),
+        }
+    }
+
+    /// Stop signing a zone: deactivate and delete all of its keys. The DS
+    /// records in the parent zone must be removed before, or the zone will
+    /// fail to validate.
+    pub async fn unsecure_zone(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let cryptokeys = self.api_client().cryptokeys(&zone_id);
+
+        for key in cryptokeys.list().await? {
+            let Some(id) = key.id else {
+                continue;
+            };
+            if key.active.unwrap_or(false) {
+                cryptokeys.deactivate(id).await?;
+            }
+            cryptokeys.delete(id).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::cryptokeys::KeyType;
+    use crate::dnssec::DnssecPolicy;
+    use crate::zones::{Zone, ZoneKind};
+
+    #[tokio::test]
+    async fn secure_and_unsecure_zone() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        zones.create(zone).await.unwrap();
+
+        let policy = DnssecPolicy {
+            split_keys: true,
+            ..Default::default()
+        };
+        let ds = zones.secure_zone("example.com", &policy).await.unwrap();
+        assert_eq!(ds.len(), 1);
+
+        let keys = server.cryptokeys("example.com.");
+        let keytypes: Vec<_> = keys.iter().filter_map(|key| key.keytype.clone()).collect();
+        assert_eq!(keytypes, [KeyType::Ksk, KeyType::Zsk]);
+        assert!(keys.iter().all(|key| key.active == Some(true)));
+
+        zones.unsecure_zone("example.com").await.unwrap();
+        assert!(server.cryptokeys("example.com.").is_empty());
+    }
+}
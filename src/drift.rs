@@ -0,0 +1,177 @@
+//! Compare the serials of the zones of a primary with those of its
+//! secondaries, e.g. for a replication monitoring dashboard.
+//!
+//! The masters of a Slave zone are plain addresses without API access, so
+//! the secondaries are given as Clients of their own webservers.
+
+use crate::zones::{ZoneKind, ZoneSummary};
+use crate::{Client, Error};
+
+/// The serials of a zone on the primary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialStatus {
+    pub serial: u32,
+    /// The serial notifications have been sent out for
+    pub notified_serial: Option<u32>,
+    /// The serial secondaries see, after SOA-EDIT has been applied
+    pub edited_serial: Option<u32>,
+}
+
+impl SerialStatus {
+    /// The serial the secondaries are expected to have
+    pub fn expected_serial(&self) -> u32 {
+        self.edited_serial.unwrap_or(self.serial)
+    }
+}
+
+/// A secondary that is behind the primary for a zone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecondaryLag {
+    /// The base URL of the webserver of the secondary
+    pub base_url: String,
+    /// The id of the secondary behind that webserver
+    pub server: String,
+    /// The serial of the zone on the secondary, None when it does not have
+    /// the zone at all
+    pub serial: Option<u32>,
+}
+
+/// A zone that is not in sync on all servers, see [`Client::serial_drift`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZoneDrift {
+    /// Name of the zone, with a trailing dot
+    pub zone: String,
+    pub primary: SerialStatus,
+    /// Whether the primary has not sent notifications for its current serial
+    pub notify_pending: bool,
+    /// The secondaries with an older serial, in the order they were given
+    pub lagging: Vec<SecondaryLag>,
+}
+
+/// Whether serial `a` is before serial `b` in the sequence space of
+/// RFC 1982, so that serials that wrapped around compare correctly
+pub fn serial_lt(a: u32, b: u32) -> bool {
+    a != b && b.wrapping_sub(a) < 1 << 31
+}
+
+impl Client {
+    /// The zones of kind Master and Producer on this server, the primary,
+    /// whose serial is ahead of the serial on any of `secondaries` or whose
+    /// notifications are still pending. Zones that are in sync everywhere
+    /// are left out, so an empty result means replication is healthy.
+    pub async fn serial_drift<'a>(
+        &self,
+        secondaries: impl IntoIterator<Item = &'a Client>,
+    ) -> Result<Vec<ZoneDrift>, Error> {
+        let mut zones: Vec<ZoneDrift> = self
+            .zone()
+            .list_summaries()
+            .await?
+            .into_iter()
+            .filter(|zone| matches!(zone.kind, ZoneKind::Master | ZoneKind::Producer))
+            .map(|zone| {
+                let primary = SerialStatus {
+                    serial: zone.serial,
+                    notified_serial: zone.notified_serial,
+                    edited_serial: zone.edited_serial,
+                };
+                ZoneDrift {
+                    notify_pending: primary
+                        .notified_serial
+                        .is_some_and(|notified| serial_lt(notified, primary.expected_serial())),
+                    zone: zone.name,
+                    primary,
+                    lagging: Vec::new(),
+                }
+            })
+            .collect();
+
+        for secondary in secondaries {
+            let summaries: Vec<ZoneSummary> = secondary.zone().list_summaries().await?;
+            for zone in &mut zones {
+                let serial = summaries
+                    .iter()
+                    .find(|summary| summary.name.eq_ignore_ascii_case(&zone.zone))
+                    .map(|summary| summary.serial);
+                let expected = zone.primary.expected_serial();
+                if serial.is_none_or(|serial| serial_lt(serial, expected)) {
+                    zone.lagging.push(SecondaryLag {
+                        base_url: secondary.base_url.clone(),
+                        server: secondary.server_name.clone(),
+                        serial,
+                    });
+                }
+            }
+        }
+
+        zones.retain(|zone| zone.notify_pending || !zone.lagging.is_empty());
+        zones.sort_by(|a, b| a.zone.cmp(&b.zone));
+        Ok(zones)
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::drift::serial_lt;
+    use crate::record_type::RecordType;
+    use crate::zones::{Zone, ZoneKind};
+
+    #[test]
+    fn compare_serials() {
+        assert!(serial_lt(1, 2));
+        assert!(!serial_lt(2, 2));
+        assert!(!serial_lt(3, 2));
+        assert!(serial_lt(u32::MAX, 1));
+        assert!(!serial_lt(1, u32::MAX));
+    }
+
+    #[tokio::test]
+    async fn report_lagging_zones() {
+        let primary = crate::mock::MockServer::start();
+        let secondary = crate::mock::MockServer::start();
+        for name in ["example.com.", "example.net.", "example.org."] {
+            let zone = Zone::builder(name)
+                .kind(ZoneKind::Master)
+                .nameservers(["ns1.example.com."])
+                .build()
+                .unwrap();
+            primary.client().zone().create(zone).await.unwrap();
+        }
+        let native = Zone::builder("internal.example.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        primary.client().zone().create(native).await.unwrap();
+        for name in ["example.com.", "example.net."] {
+            let zone = Zone::builder(name)
+                .kind(ZoneKind::Slave)
+                .masters(["192.0.2.1"])
+                .build()
+                .unwrap();
+            secondary.client().zone().create(zone).await.unwrap();
+        }
+        primary
+            .client()
+            .zone()
+            .replace_rrset(
+                "example.net",
+                "www.example.net.",
+                RecordType::A,
+                300,
+                &["192.0.2.2"],
+            )
+            .await
+            .unwrap();
+
+        let secondaries = [secondary.client()];
+        let drift = primary.client().serial_drift(&secondaries).await.unwrap();
+        assert_eq!(drift.len(), 2);
+        assert_eq!(drift[0].zone, "example.net.");
+        assert_eq!(drift[0].primary.serial, 2);
+        assert_eq!(drift[0].lagging[0].serial, Some(1));
+        assert_eq!(drift[1].zone, "example.org.");
+        assert_eq!(drift[1].lagging[0].serial, None);
+        assert_eq!(drift[1].lagging[0].base_url, secondary.base_url());
+    }
+}
@@ -0,0 +1,145 @@
+//! Preview the requests of zone mutations without sending them, e.g. for
+//! reviewing changes before applying them.
+
+use std::fmt;
+
+use reqwest::Method;
+use serde::Serialize;
+
+use crate::names::IntoZoneId;
+#[allow(deprecated)]
+use crate::zones::{PatchZone, Zone, ZoneClient};
+use crate::{Client, Error};
+
+/// A request that would have been sent, returned by [`ZoneDryRun`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunRequest {
+    pub method: Method,
+    pub url: String,
+    /// The JSON body, if the request has one
+    pub body: Option<serde_json::Value>,
+}
+
+impl fmt::Display for DryRunRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+        if let Some(body) = &self.body {
+            let body = serde_json::to_string_pretty(body).map_err(|_| fmt::Error)?;
+            write!(f, "\n{body}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The mutations of [`ZoneClient`], returning the request they would send
+/// instead of sending it. Created with [`ZoneClient::dry_run`].
+pub struct ZoneDryRun<'a> {
+    api_client: &'a Client,
+}
+
+impl<'a> ZoneClient<'a> {
+    /// Preview mutations of this client without performing them
+    pub fn dry_run(&self) -> ZoneDryRun<'a> {
+        ZoneDryRun {
+            api_client: self.api_client(),
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl ZoneDryRun<'_> {
+    /// The request [`ZoneClient::create`] would send
+    pub fn create(&self, zone: &Zone) -> Result<DryRunRequest, Error> {
+        self.request(Method::POST, String::new(), Some(zone))
+    }
+
+    /// The request [`ZoneClient::patch`] would send. The patch is validated
+    /// like it is before sending.
+    pub fn patch(
+        &self,
+        zone_id: impl IntoZoneId,
+        zone: &PatchZone,
+    ) -> Result<DryRunRequest, Error> {
+        let path = zone_path(zone_id)?;
+        zone.validate()?;
+        self.request(Method::PATCH, path, Some(zone))
+    }
+
+    /// The request [`ZoneClient::delete`] would send
+    pub fn delete(&self, zone_id: impl IntoZoneId) -> Result<DryRunRequest, Error> {
+        self.request(Method::DELETE, zone_path(zone_id)?, None::<&()>)
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        path: String,
+        body: Option<&impl Serialize>,
+    ) -> Result<DryRunRequest, Error> {
+        let request = DryRunRequest {
+            method,
+            url: format!(
+                "{}/servers/{}/zones{path}",
+                self.api_client.api_url, self.api_client.server_name
+            ),
+            body: body.map(serde_json::to_value).transpose()?,
+        };
+        #[cfg(feature = "tracing")]
+        tracing::info!(method = %request.method, url = %request.url, "dry run");
+        Ok(request)
+    }
+}
+
+/// The path of a zone below the zones endpoint, with its id canonicalized
+/// like [`ZoneClient`] does
+fn zone_path(zone_id: impl IntoZoneId) -> Result<String, Error> {
+    let zone_id = zone_id.into_zone_id()?;
+    Ok(format!("/{zone_id}"))
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use reqwest::Method;
+    use serde_json::json;
+
+    use crate::record_type::RecordType;
+    use crate::zones::{PatchZone, RRSetUpdate, Zone, ZoneKind};
+
+    #[tokio::test]
+    async fn dry_run_does_not_send() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let dry_run = client.zone().dry_run();
+
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        let request = dry_run.create(&zone).unwrap();
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.body.unwrap()["name"], "example.com.");
+
+        let patch = PatchZone {
+            rrsets: vec![RRSetUpdate::delete("www.example.com.", RecordType::A)],
+        };
+        let request = dry_run.patch("Example.com", &patch).unwrap();
+        assert!(request.url.ends_with("/zones/example.com."));
+        assert_eq!(
+            request.body,
+            Some(json!({"rrsets": [
+                {"changetype": "DELETE", "name": "www.example.com.", "type": "A"}
+            ]}))
+        );
+
+        assert_eq!(
+            dry_run.delete("example.com").unwrap().method,
+            Method::DELETE
+        );
+        assert!(matches!(
+            dry_run.patch("example com", &patch),
+            Err(crate::Error::InvalidDomain { .. })
+        ));
+        assert!(server.zones().is_empty());
+    }
+}
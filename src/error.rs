@@ -1,21 +1,134 @@
 use std::fmt::{Debug, Display, Formatter};
-use reqwest::StatusCode;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use reqwest::{Method, Response, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use thiserror::Error;
 
+use crate::caa::CaaError;
+use crate::record_type::RecordType;
+use crate::retry::{retry_after, RetryPolicy};
+use crate::validate::PatchValidationError;
+#[cfg(feature = "yaml")]
+use crate::yaml::YamlError;
+use crate::zones::DomainError;
+
 /// Returned when the server encounters an error, either in client input or
 /// internally
 #[derive(Error, Debug)]
-#[serde_with::skip_serializing_none]
+#[non_exhaustive]
 pub enum Error {
-    #[error("powerdns returned error in response: {0:?}")]
-    PowerDNS(#[from] PowerDNSResponseError),
+    /// An error response with a status not covered by the variants below
+    #[error("{context}: {error}")]
+    PowerDNS {
+        context: RequestContext,
+        #[source]
+        error: PowerDNSResponseError,
+    },
+
+    /// 404 Not Found, e.g. the zone or key does not exist
+    #[error("{context}: {error}")]
+    NotFound {
+        context: RequestContext,
+        #[source]
+        error: PowerDNSResponseError,
+    },
+
+    /// 409 Conflict, e.g. the zone to create already exists
+    #[error("{context}: {error}")]
+    Conflict {
+        context: RequestContext,
+        #[source]
+        error: PowerDNSResponseError,
+    },
+
+    /// 422 Unprocessable Entity, the request was rejected, e.g. because of
+    /// an invalid RRSet
+    #[error("{context}: {error}")]
+    Unprocessable {
+        context: RequestContext,
+        #[source]
+        error: PowerDNSResponseError,
+    },
+
+    /// 429 Too Many Requests, usually returned by a proxy rate limiting the
+    /// API
+    #[error("{context}: {error}")]
+    TooManyRequests {
+        context: RequestContext,
+        #[source]
+        error: PowerDNSResponseError,
+    },
 
-    #[error("error while performing request: {}", 0)]
+    #[error("error while performing request: {0}")]
     RequestError(#[from] reqwest::Error),
 
-    #[error("received unexpected status code: {}", 0)]
-    UnexpectedStatusCode(StatusCode),
+    /// The body is truncated, see [`MAX_BODY_LENGTH`]
+    #[error("received unexpected status code: {context}, body: {body:?}")]
+    UnexpectedStatusCode {
+        context: RequestContext,
+        body: String,
+    },
+
+    /// The body is truncated, see [`MAX_BODY_LENGTH`]
+    #[error("could not decode the response of {context}: {source}, body: {body:?}")]
+    Decode {
+        context: RequestContext,
+        source: serde_json::Error,
+        body: String,
+    },
+
+    #[error("invalid base URL {url:?}: {reason}")]
+    InvalidBaseUrl { url: String, reason: String },
+
+    #[error("invalid domain name {domain:?}: {source}")]
+    InvalidDomain {
+        domain: String,
+        source: DomainError,
+    },
+
+    #[error("invalid CAA record: {0}")]
+    InvalidCaa(#[from] CaaError),
+
+    #[error("invalid patch: {0}")]
+    InvalidPatch(#[from] PatchValidationError),
+
+    #[error("no reverse zone for {0} on the server")]
+    NoReverseZone(IpAddr),
+
+    #[error("timed out waiting for serial {expected} of zone {zone_id}, last seen {current:?}")]
+    SerialTimeout {
+        zone_id: String,
+        expected: u32,
+        current: Option<u32>,
+    },
+
+    #[error("zone {zone_id} was changed, expected serial {expected} but found {current:?}")]
+    SerialMismatch {
+        zone_id: String,
+        expected: u32,
+        current: Option<u32>,
+    },
+
+    #[error("could not read {path:?}: {source}")]
+    ReadFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// A zone definition file could not be read, see [`crate::reconcile`]
+    #[cfg(feature = "yaml")]
+    #[error("{path:?}: {source}")]
+    InvalidDefinition {
+        path: PathBuf,
+        source: YamlError,
+    },
+
+    /// Reading or writing a stream failed, e.g. a backup
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
 
     #[error("deserialization error: {0}")]
     DeserializeError(#[from] serde_json::Error),
@@ -24,9 +137,169 @@ pub enum Error {
     Other(#[from] Box<dyn std::error::Error + Send + Sync + 'static>)
 }
 
+impl Error {
+    /// The request the server responded to with this error
+    pub fn context(&self) -> Option<&RequestContext> {
+        match self {
+            Error::PowerDNS { context, .. }
+            | Error::NotFound { context, .. }
+            | Error::Conflict { context, .. }
+            | Error::Unprocessable { context, .. }
+            | Error::TooManyRequests { context, .. }
+            | Error::UnexpectedStatusCode { context, .. }
+            | Error::Decode { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The status code of the response this error was caused by
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::RequestError(e) => e.status(),
+            _ => self.context().map(|context| context.status),
+        }
+    }
+
+    /// Whether the server responded with 404 Not Found, e.g. because the
+    /// zone does not exist
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// Whether the server responded with 409 Conflict, e.g. because the zone
+    /// to create already exists
+    pub fn is_conflict(&self) -> bool {
+        self.status() == Some(StatusCode::CONFLICT)
+    }
+
+    /// Whether the request may succeed when sent again: when the server
+    /// could not be reached, timed out, was rate limited or responded with a
+    /// status retried by the default [`RetryPolicy`]
+    pub fn is_retryable(&self) -> bool {
+        if let Error::RequestError(e) = self {
+            if e.is_connect() || e.is_timeout() {
+                return true;
+            }
+        }
+        self.status()
+            .is_some_and(|status| RetryPolicy::default().retries_status(status))
+    }
+
+    /// How long the server asked to wait before sending the request again,
+    /// see [`RequestContext::retry_after`]
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.context()?.retry_after
+    }
+
+    /// The error for a response with an unexpected status, usually the
+    /// error object returned by the server. For the statuses with their own
+    /// variant, a body that is not an error object becomes the message.
+    pub(crate) async fn from_response(response: Response) -> Error {
+        let context = RequestContext::of(&response);
+        let body = match response.bytes().await {
+            Ok(body) => body,
+            Err(e) => return Error::RequestError(e),
+        };
+        let error = serde_json::from_slice(&body);
+        let error = match (context.status, error) {
+            (
+                StatusCode::NOT_FOUND
+                | StatusCode::CONFLICT
+                | StatusCode::UNPROCESSABLE_ENTITY
+                | StatusCode::TOO_MANY_REQUESTS,
+                Err(_),
+            ) => PowerDNSResponseError {
+                error: truncated_body(&body),
+                errors: None,
+            },
+            (_, Ok(error)) => error,
+            (_, Err(_)) => {
+                return Error::UnexpectedStatusCode {
+                    context,
+                    body: truncated_body(&body),
+                }
+            }
+        };
+        match context.status {
+            StatusCode::NOT_FOUND => Error::NotFound { context, error },
+            StatusCode::CONFLICT => Error::Conflict { context, error },
+            StatusCode::UNPROCESSABLE_ENTITY => Error::Unprocessable { context, error },
+            StatusCode::TOO_MANY_REQUESTS => Error::TooManyRequests { context, error },
+            _ => Error::PowerDNS { context, error },
+        }
+    }
+}
+
+/// Decode the JSON body of a response. An empty body, e.g. of a 204
+/// response, is decoded as `null`.
+pub(crate) async fn decode<T: DeserializeOwned>(response: Response) -> Result<T, Error> {
+    let context = RequestContext::of(&response);
+    let body = response.bytes().await?;
+    let json: &[u8] = if body.is_empty() { b"null" } else { &body };
+    serde_json::from_slice(json).map_err(|source| Error::Decode {
+        context,
+        source,
+        body: truncated_body(&body),
+    })
+}
+
+/// The number of bytes of a response body kept in errors
+pub const MAX_BODY_LENGTH: usize = 1024;
+
+/// The start of a response body, e.g. an HTML error page of a proxy
+fn truncated_body(body: &[u8]) -> String {
+    let body = String::from_utf8_lossy(body);
+    if body.len() <= MAX_BODY_LENGTH {
+        return body.into_owned();
+    }
+    let mut end = MAX_BODY_LENGTH;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...", &body[..end])
+}
+
+/// The request a response belongs to, included in errors to tell which
+/// call failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RequestContext {
+    pub method: Method,
+    /// The path of the URL, e.g. `/api/v1/servers/localhost/zones`
+    pub path: String,
+    pub status: StatusCode,
+    /// The delay requested by the Retry-After header, usually sent with 429
+    /// and 503 responses
+    pub retry_after: Option<Duration>,
+}
+
+/// The method of a request, stored in the extensions of its response
+#[derive(Debug, Clone)]
+pub(crate) struct RequestMethod(pub(crate) Method);
+
+impl RequestContext {
+    pub(crate) fn of(response: &Response) -> Self {
+        RequestContext {
+            method: response
+                .extensions()
+                .get::<RequestMethod>()
+                .map_or(Method::GET, |method| method.0.clone()),
+            path: response.url().path().to_string(),
+            status: response.status(),
+            retry_after: retry_after(response.headers()),
+        }
+    }
+}
+
+impl Display for RequestContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} returned {}", self.method, self.path, self.status)
+    }
+}
 
 /// Represents PowerDNS response error
 #[derive(Default, PartialEq, Debug, Clone, Deserialize)]
+#[non_exhaustive]
 pub struct PowerDNSResponseError {
     /// A human readable error message
     pub error: String,
@@ -34,12 +307,187 @@ pub struct PowerDNSResponseError {
     pub errors: Option<Vec<String>>,
 }
 
+/// A message of a [`PowerDNSResponseError`], with the RRSet it is about
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[non_exhaustive]
+pub struct ErrorDetail {
+    pub message: String,
+    /// The name and type of the RRSet, when the message names one like
+    /// `RRset www.example.com. IN A: Conflicts with pre-existing RRset`
+    pub rrset: Option<(String, RecordType)>,
+}
+
+impl PowerDNSResponseError {
+    /// The messages in `errors`, or `error` if there are none
+    pub fn details(&self) -> Vec<ErrorDetail> {
+        let messages = match &self.errors {
+            Some(errors) if !errors.is_empty() => errors.iter().collect(),
+            _ => vec![&self.error],
+        };
+        messages
+            .into_iter()
+            .map(|message| ErrorDetail {
+                message: message.clone(),
+                rrset: rrset_of(message),
+            })
+            .collect()
+    }
+}
+
+/// The RRSet named in a message, in the `<name> IN <type>` form of the
+/// server
+fn rrset_of(message: &str) -> Option<(String, RecordType)> {
+    let (_, rest) = message.split_once("RRset ")?;
+    let (name, rest) = rest.split_once(" IN ")?;
+    let rtype = rest
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .next()
+        .filter(|rtype| !rtype.is_empty())?;
+    if name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((name.to_string(), RecordType::from(rtype)))
+}
+
 impl Display for PowerDNSResponseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&format!("error occurred: {}", self.error))
+        write!(f, "error occurred: {}", self.error)?;
+        // the errors usually repeat the error first
+        let errors: Vec<&str> = self
+            .errors
+            .iter()
+            .flatten()
+            .filter(|e| **e != self.error)
+            .map(String::as_str)
+            .collect();
+        if !errors.is_empty() {
+            write!(f, " ({})", errors.join("; "))?;
+        }
+        Ok(())
     }
 }
 
 impl std::error::Error for PowerDNSResponseError {
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use reqwest::{Method, StatusCode};
+
+    use crate::error::{
+        truncated_body, ErrorDetail, PowerDNSResponseError, RequestContext, MAX_BODY_LENGTH,
+    };
+    use crate::record_type::RecordType;
+    use crate::Error;
+
+    #[test]
+    fn display_all_errors() {
+        let error = PowerDNSResponseError {
+            error: String::from("Invalid RRsets"),
+            errors: Some(vec![
+                String::from("RRset www.example.com. IN A: Conflicts with pre-existing RRset"),
+                String::from("Record ftp.example.com./TXT 'hello': Not in expected format"),
+            ]),
+        };
+        assert_eq!(
+            error.to_string(),
+            "error occurred: Invalid RRsets (RRset www.example.com. IN A: Conflicts with \
+             pre-existing RRset; Record ftp.example.com./TXT 'hello': Not in expected format)"
+        );
+        assert_eq!(
+            error.details(),
+            [
+                ErrorDetail {
+                    message: String::from(
+                        "RRset www.example.com. IN A: Conflicts with pre-existing RRset"
+                    ),
+                    rrset: Some((String::from("www.example.com."), RecordType::A)),
+                },
+                ErrorDetail {
+                    message: String::from(
+                        "Record ftp.example.com./TXT 'hello': Not in expected format"
+                    ),
+                    rrset: None,
+                },
+            ]
+        );
+
+        let error = PowerDNSResponseError {
+            error: String::from("Not Found"),
+            errors: Some(vec![String::from("Not Found")]),
+        };
+        assert_eq!(error.to_string(), "error occurred: Not Found");
+        assert_eq!(error.details().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn errors_carry_request_context() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+
+        let error = client.zone().delete("example.com").await.unwrap_err();
+        let Error::NotFound { context, .. } = &error else {
+            panic!("{error:?}");
+        };
+        assert_eq!(
+            context,
+            &RequestContext {
+                method: Method::DELETE,
+                path: String::from("/api/v1/servers/localhost/zones/example.com."),
+                status: StatusCode::NOT_FOUND,
+                retry_after: None,
+            }
+        );
+        assert!(error
+            .to_string()
+            .starts_with("DELETE /api/v1/servers/localhost/zones/example.com. returned 404 Not Found: "));
+        assert!(error.source().is_some());
+        assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+        assert!(error.is_not_found());
+        assert!(!error.is_conflict());
+        assert!(!error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn errors_include_body() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let responses = [
+                "HTTP/1.1 502 Bad Gateway\r\ncontent-length: 13\r\nconnection: close\r\n\r\n<h1>oops</h1>",
+                "HTTP/1.1 200 OK\r\ncontent-length: 16\r\nconnection: close\r\n\r\n{\"unexpected\":1}",
+                "HTTP/1.1 429 Too Many Requests\r\nretry-after: 120\r\ncontent-length: 9\r\nconnection: close\r\n\r\nslow down",
+            ];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let _ = stream.read(&mut [0; 4096]);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        let client = crate::Client::builder()
+            .base_url(base_url)
+            .server_name("localhost")
+            .api_key("secret")
+            .build()
+            .unwrap();
+
+        let error = client.zone().list().await.unwrap_err();
+        assert!(matches!(&error, Error::UnexpectedStatusCode { body, .. } if body == "<h1>oops</h1>"));
+        assert!(error.is_retryable());
+        let error = client.zone().list().await.unwrap_err();
+        assert!(matches!(&error, Error::Decode { body, .. } if body == r#"{"unexpected":1}"#));
+        let error = client.zone().list().await.unwrap_err();
+        assert!(matches!(&error, Error::TooManyRequests { error, .. } if error.error == "slow down"));
+        assert_eq!(error.retry_after(), Some(std::time::Duration::from_secs(120)));
+
+        let body = "ä".repeat(MAX_BODY_LENGTH);
+        let truncated = truncated_body(body.as_bytes());
+        assert_eq!(truncated.len(), MAX_BODY_LENGTH + 3);
+        assert!(truncated.ends_with("ä..."));
+    }
+}
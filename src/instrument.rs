@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use reqwest::{Request, Response, ResponseBuilderExt};
+use tracing::field::Empty;
+use tracing::{Level, Span};
+
+/// Headers whose values are never logged
+const REDACTED_HEADERS: [&str; 2] = ["x-api-key", "authorization"];
+
+/// The span an API call is executed in, covering all retries
+pub(crate) fn request_span(request: &Request) -> Span {
+    let path = request.url().path();
+    tracing::info_span!(
+        "powerdns_request",
+        method = %request.method(),
+        path,
+        zone_id = zone_id(path),
+        status = Empty,
+        duration_ms = Empty,
+        otel.kind = "client",
+        trace_id = Empty,
+        span_id = Empty,
+        parent_id = Empty,
+    )
+}
+
+pub(crate) fn record_duration(span: &Span, duration: Duration) {
+    span.record("duration_ms", duration.as_millis() as u64);
+}
+
+/// Log a single attempt of a request, without its credentials
+pub(crate) fn log_request(request: &Request) {
+    if !tracing::enabled!(Level::DEBUG) {
+        return;
+    }
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(String::from_utf8_lossy);
+    tracing::debug!(
+        url = %request.url(),
+        headers = ?redact(request.headers()),
+        body = body.as_deref().unwrap_or_default(),
+        "sending request",
+    );
+}
+
+/// Record the status of a response in the current span and log its body.
+/// When debug logging is enabled the body is buffered and the Response
+/// rebuilt around it, with the same URL, as it can only be read once.
+pub(crate) async fn log_response(response: reqwest::Result<Response>) -> reqwest::Result<Response> {
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::debug!(error = %e, "request failed");
+            return Err(e);
+        }
+    };
+
+    let status = response.status();
+    Span::current().record("status", status.as_u16());
+    if !tracing::enabled!(Level::DEBUG) {
+        return Ok(response);
+    }
+
+    let mut rebuilt = http::Response::builder()
+        .status(status)
+        .version(response.version())
+        .url(response.url().clone());
+    if let Some(headers) = rebuilt.headers_mut() {
+        *headers = response.headers().clone();
+    }
+    let body = response.bytes().await?;
+    tracing::debug!(
+        status = status.as_u16(),
+        body = %String::from_utf8_lossy(&body),
+        "received response",
+    );
+
+    // the parts were copied from a valid response
+    Ok(Response::from(rebuilt.body(body).expect("valid response parts")))
+}
+
+/// The zone id in paths like `/api/v1/servers/localhost/zones/{zone_id}/...`
+fn zone_id(path: &str) -> Option<&str> {
+    let mut segments = path.split('/');
+    segments.find(|segment| *segment == "zones")?;
+    segments.next().filter(|zone_id| !zone_id.is_empty())
+}
+
+fn redact(headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    for name in REDACTED_HEADERS {
+        if let header::Entry::Occupied(mut entry) = headers.entry(name) {
+            entry.insert(HeaderValue::from_static("[redacted]"));
+        }
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::instrument::{redact, zone_id};
+
+    /// A subscriber enabling all levels, so responses are rebuilt
+    struct DebugSubscriber;
+
+    impl Subscriber for DebugSubscriber {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn keep_url_of_logged_response() {
+        let _guard = tracing::subscriber::set_default(DebugSubscriber);
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+
+        let error = client.zone().get("example.com").await.unwrap_err();
+        assert_eq!(
+            error.context().unwrap().path,
+            "/api/v1/servers/localhost/zones/example.com."
+        );
+    }
+
+    #[test]
+    fn zone_id_from_path() {
+        assert_eq!(
+            zone_id("/api/v1/servers/localhost/zones/example.com./rectify"),
+            Some("example.com.")
+        );
+        assert_eq!(zone_id("/api/v1/servers/localhost/zones"), None);
+        assert_eq!(zone_id("/api/v1/servers/localhost/zones/"), None);
+        assert_eq!(zone_id("/api/v1/servers/localhost/statistics"), None);
+    }
+
+    #[test]
+    fn redact_api_key() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-API-Key", HeaderValue::from_static("secret"));
+        headers.insert("Accept", HeaderValue::from_static("application/json"));
+
+        let redacted = redact(&headers);
+        assert_eq!(redacted["x-api-key"], "[redacted]");
+        assert_eq!(redacted["accept"], "application/json");
+    }
+}
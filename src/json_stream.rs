@@ -0,0 +1,134 @@
+use serde::de::Error as _;
+
+/// Splits a JSON array of objects that arrives in chunks into its elements,
+/// so large responses can be deserialized one element at a time instead of
+/// being buffered completely.
+///
+/// The splitter only tracks nesting and strings; the elements themselves are
+/// validated when they are deserialized.
+#[derive(Debug, Default)]
+pub(crate) struct JsonArraySplitter {
+    buffer: Vec<u8>,
+    /// Position up to which `buffer` has been scanned
+    position: usize,
+    /// Nesting depth, 1 inside the outer array
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Start of the element currently being scanned
+    start: Option<usize>,
+    finished: bool,
+}
+
+impl JsonArraySplitter {
+    pub(crate) fn extend(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Whether the closing bracket of the array has been seen
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The next complete element of the array, or None if more data is
+    /// needed or the array is finished
+    pub(crate) fn next_element(&mut self) -> Result<Option<Vec<u8>>, serde_json::Error> {
+        while self.position < self.buffer.len() {
+            let i = self.position;
+            let byte = self.buffer[i];
+            self.position += 1;
+
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            if byte.is_ascii_whitespace() {
+                continue;
+            }
+            if self.finished {
+                return Err(unexpected("trailing characters after array"));
+            }
+
+            match (self.depth, byte) {
+                (0, b'[') => self.depth = 1,
+                (0, _) => return Err(unexpected("expected an array")),
+                (1, b',') => {}
+                (1, b']') => self.finished = true,
+                (1, b'{') => {
+                    self.start = Some(i);
+                    self.depth = 2;
+                }
+                (1, _) => return Err(unexpected("expected an object")),
+                (_, b'"') => self.in_string = true,
+                (_, b'{' | b'[') => self.depth += 1,
+                (_, b'}' | b']') => {
+                    self.depth -= 1;
+                    if self.depth == 1 {
+                        let start = self.start.take().unwrap_or_default();
+                        let element = self.buffer[start..=i].to_vec();
+                        self.buffer.drain(..=i);
+                        self.position = 0;
+                        return Ok(Some(element));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // drop everything scanned before the current element
+        let consumed = self.start.unwrap_or(self.position);
+        self.buffer.drain(..consumed);
+        self.position -= consumed;
+        if let Some(start) = &mut self.start {
+            *start = 0;
+        }
+        Ok(None)
+    }
+}
+
+fn unexpected(message: &str) -> serde_json::Error {
+    serde_json::Error::custom(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_stream::JsonArraySplitter;
+
+    #[test]
+    fn split_chunked_array() {
+        let json = br#" [{"name": "a.", "x": [1, {"y": "}"}]}, {"name": "b\"]."}] "#;
+        let mut splitter = JsonArraySplitter::default();
+        let mut elements = Vec::new();
+        for chunk in json.chunks(3) {
+            splitter.extend(chunk);
+            while let Some(element) = splitter.next_element().unwrap() {
+                elements.push(String::from_utf8(element).unwrap());
+            }
+        }
+
+        assert!(splitter.is_finished());
+        assert_eq!(
+            elements,
+            [
+                r#"{"name": "a.", "x": [1, {"y": "}"}]}"#,
+                r#"{"name": "b\"]."}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_non_arrays() {
+        let mut splitter = JsonArraySplitter::default();
+        splitter.extend(br#"{"error": "Not Found"}"#);
+        assert!(splitter.next_element().is_err());
+
+        let mut splitter = JsonArraySplitter::default();
+        splitter.extend(b"[1]");
+        assert!(splitter.next_element().is_err());
+    }
+}
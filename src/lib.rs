@@ -1,6 +1,54 @@
+pub mod acme;
+pub mod audit;
+pub mod backup;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod caa;
+pub mod changeset;
+pub mod chunked;
 pub mod client;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod cryptokeys;
+#[cfg(feature = "dane")]
+pub mod dane;
+pub mod diff;
+pub mod dnssec;
+pub mod drift;
+pub mod dry_run;
 pub mod error;
+#[cfg(feature = "tracing")]
+mod instrument;
+mod json_stream;
+pub mod mail;
+pub mod metadata;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod middleware;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+pub mod names;
+pub mod observer;
+pub mod operations;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod ptr;
+mod rate_limit;
+mod raw;
+pub mod rdata;
+pub mod record_type;
+#[cfg(feature = "yaml")]
+pub mod reconcile;
+pub mod recursor;
+pub mod retry;
+pub mod search;
 pub mod server;
+pub mod srv;
+pub mod tsigkeys;
+pub mod validate;
+#[cfg(feature = "yaml")]
+pub mod yaml;
+pub mod zonefile;
 pub mod zones;
 
 // re-exports for convenience
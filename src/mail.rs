@@ -0,0 +1,585 @@
+//! Provision the records a domain needs to send and receive mail: MX
+//! targets, an SPF policy, DKIM keys and a DMARC policy.
+//!
+//! ```no_run
+//! # async fn example(client: powerdns::Client, public_key: &str) -> Result<(), powerdns::Error> {
+//! use powerdns::mail::{Dkim, Dmarc, DmarcPolicy, MailSetup, Spf};
+//!
+//! let setup = MailSetup::new()
+//!     .mx(10, "mx1.example.com")
+//!     .mx(20, "mx2.example.com")
+//!     .spf(Spf::new().mx().include("_spf.mailprovider.example"))
+//!     .dkim(Dkim::rsa("2024", public_key))
+//!     .dmarc(Dmarc::new(DmarcPolicy::Quarantine).aggregate_reports("dmarc@example.com"));
+//! client.zone().set_up_mail("example.com", "example.com", &setup).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::changeset::ChangeSet;
+use crate::names::IntoZoneId;
+use crate::rdata::Txt;
+use crate::record_type::RecordType;
+use crate::zones::{absolute_name, ZoneClient};
+use crate::Error;
+
+/// The TTL of the records of a [`MailSetup`], unless set otherwise
+pub const DEFAULT_TTL: u32 = 3600;
+
+/// What an SPF verifier concludes for a sender matching a mechanism
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpfQualifier {
+    Pass,
+    Fail,
+    SoftFail,
+    Neutral,
+}
+
+impl SpfQualifier {
+    fn as_str(self) -> &'static str {
+        match self {
+            SpfQualifier::Pass => "+",
+            SpfQualifier::Fail => "-",
+            SpfQualifier::SoftFail => "~",
+            SpfQualifier::Neutral => "?",
+        }
+    }
+}
+
+/// An SPF policy listing the hosts allowed to send mail for a domain,
+/// formatted as e.g. `v=spf1 mx include:_spf.example.net -all`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spf {
+    mechanisms: Vec<String>,
+    all: SpfQualifier,
+}
+
+impl Default for Spf {
+    fn default() -> Self {
+        Spf {
+            mechanisms: Vec::new(),
+            all: SpfQualifier::Fail,
+        }
+    }
+}
+
+impl Spf {
+    /// A policy allowing no senders, and failing mail from all others
+    pub fn new() -> Self {
+        Spf::default()
+    }
+
+    /// Allow the addresses of the domain itself
+    pub fn a(self) -> Self {
+        self.mechanism(String::from("a"))
+    }
+
+    /// Allow the hosts in the MX records of the domain
+    pub fn mx(self) -> Self {
+        self.mechanism(String::from("mx"))
+    }
+
+    /// Allow an address, or a network when `prefix` is given
+    pub fn ip4(self, address: Ipv4Addr, prefix: Option<u8>) -> Self {
+        self.mechanism(with_prefix("ip4", &address.to_string(), prefix))
+    }
+
+    /// Allow an address, or a network when `prefix` is given
+    pub fn ip6(self, address: Ipv6Addr, prefix: Option<u8>) -> Self {
+        self.mechanism(with_prefix("ip6", &address.to_string(), prefix))
+    }
+
+    /// Allow the senders of the SPF policy of `domain`, e.g. of a mail
+    /// provider
+    pub fn include(self, domain: &str) -> Self {
+        let domain = domain.trim_end_matches('.');
+        self.mechanism(format!("include:{domain}"))
+    }
+
+    /// What to conclude for all other senders, [`SpfQualifier::Fail`] by
+    /// default
+    pub fn all(mut self, qualifier: SpfQualifier) -> Self {
+        self.all = qualifier;
+        self
+    }
+
+    fn mechanism(mut self, mechanism: String) -> Self {
+        if !self.mechanisms.contains(&mechanism) {
+            self.mechanisms.push(mechanism);
+        }
+        self
+    }
+
+    /// Whether the TXT record `txt` is an SPF policy
+    pub fn is_spf(txt: &Txt) -> bool {
+        let value = txt.value();
+        value == "v=spf1" || value.starts_with("v=spf1 ")
+    }
+}
+
+fn with_prefix(mechanism: &str, address: &str, prefix: Option<u8>) -> String {
+    match prefix {
+        Some(prefix) => format!("{mechanism}:{address}/{prefix}"),
+        None => format!("{mechanism}:{address}"),
+    }
+}
+
+impl fmt::Display for Spf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("v=spf1")?;
+        for mechanism in &self.mechanisms {
+            write!(f, " {mechanism}")?;
+        }
+        write!(f, " {}all", self.all.as_str())
+    }
+}
+
+/// A DKIM public key, published at `{selector}._domainkey.{domain}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dkim {
+    selector: String,
+    key_type: &'static str,
+    public_key: String,
+}
+
+impl Dkim {
+    /// An RSA key, given as base64 or in PEM format. PEM armor lines and
+    /// whitespace are removed.
+    pub fn rsa(selector: &str, public_key: &str) -> Self {
+        Dkim::new(selector, "rsa", public_key)
+    }
+
+    /// An Ed25519 key, given as the base64 encoding of its 32 bytes
+    pub fn ed25519(selector: &str, public_key: &str) -> Self {
+        Dkim::new(selector, "ed25519", public_key)
+    }
+
+    fn new(selector: &str, key_type: &'static str, public_key: &str) -> Self {
+        let public_key = public_key
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("-----"))
+            .flat_map(|line| line.split_whitespace())
+            .collect();
+        Dkim {
+            selector: selector.to_string(),
+            key_type,
+            public_key,
+        }
+    }
+
+    pub fn selector(&self) -> &str {
+        &self.selector
+    }
+
+    /// The name of the TXT record of the key for `domain`, with a trailing
+    /// dot
+    pub fn name(&self, domain: &str) -> String {
+        format!("{}._domainkey.{}", self.selector, absolute_name(domain))
+    }
+
+    /// The TXT record of the key, split into strings as RSA keys are
+    /// longer than a single string
+    pub fn to_txt(&self) -> Txt {
+        Txt::from_value(&format!(
+            "v=DKIM1; k={}; p={}",
+            self.key_type, self.public_key
+        ))
+    }
+}
+
+/// What receivers should do with mail failing DMARC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmarcPolicy {
+    /// Only report the failures
+    None,
+    Quarantine,
+    Reject,
+}
+
+impl DmarcPolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            DmarcPolicy::None => "none",
+            DmarcPolicy::Quarantine => "quarantine",
+            DmarcPolicy::Reject => "reject",
+        }
+    }
+}
+
+/// How closely the domains verified by DKIM and SPF must match the sender
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    /// Subdomains of the sender match
+    Relaxed,
+    Strict,
+}
+
+impl Alignment {
+    fn as_str(self) -> &'static str {
+        match self {
+            Alignment::Relaxed => "r",
+            Alignment::Strict => "s",
+        }
+    }
+}
+
+/// A DMARC policy, published at `_dmarc.{domain}` and formatted as e.g.
+/// `v=DMARC1; p=reject; rua=mailto:dmarc@example.com`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dmarc {
+    policy: DmarcPolicy,
+    subdomain_policy: Option<DmarcPolicy>,
+    percent: Option<u8>,
+    aggregate_reports: Vec<String>,
+    failure_reports: Vec<String>,
+    dkim_alignment: Option<Alignment>,
+    spf_alignment: Option<Alignment>,
+}
+
+impl Dmarc {
+    pub fn new(policy: DmarcPolicy) -> Self {
+        Dmarc {
+            policy,
+            subdomain_policy: None,
+            percent: None,
+            aggregate_reports: Vec::new(),
+            failure_reports: Vec::new(),
+            dkim_alignment: None,
+            spf_alignment: None,
+        }
+    }
+
+    /// The policy for subdomains, the policy of the domain by default
+    pub fn subdomain_policy(mut self, policy: DmarcPolicy) -> Self {
+        self.subdomain_policy = Some(policy);
+        self
+    }
+
+    /// Apply the policy to only a percentage of the failing mail, e.g.
+    /// while rolling it out. Values above 100 are capped.
+    pub fn percent(mut self, percent: u8) -> Self {
+        self.percent = Some(percent.min(100));
+        self
+    }
+
+    /// Send daily aggregate reports to `address`, a mail address or a URI
+    pub fn aggregate_reports(mut self, address: &str) -> Self {
+        self.aggregate_reports.push(report_uri(address));
+        self
+    }
+
+    /// Send reports of individual failures to `address`, a mail address or
+    /// a URI
+    pub fn failure_reports(mut self, address: &str) -> Self {
+        self.failure_reports.push(report_uri(address));
+        self
+    }
+
+    pub fn dkim_alignment(mut self, alignment: Alignment) -> Self {
+        self.dkim_alignment = Some(alignment);
+        self
+    }
+
+    pub fn spf_alignment(mut self, alignment: Alignment) -> Self {
+        self.spf_alignment = Some(alignment);
+        self
+    }
+
+    /// The name of the TXT record of the policy for `domain`, with a
+    /// trailing dot
+    pub fn name(domain: &str) -> String {
+        format!("_dmarc.{}", absolute_name(domain))
+    }
+}
+
+fn report_uri(address: &str) -> String {
+    if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("mailto:{address}")
+    }
+}
+
+impl fmt::Display for Dmarc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v=DMARC1; p={}", self.policy.as_str())?;
+        if let Some(policy) = self.subdomain_policy {
+            write!(f, "; sp={}", policy.as_str())?;
+        }
+        if let Some(percent) = self.percent {
+            write!(f, "; pct={percent}")?;
+        }
+        if !self.aggregate_reports.is_empty() {
+            write!(f, "; rua={}", self.aggregate_reports.join(","))?;
+        }
+        if !self.failure_reports.is_empty() {
+            write!(f, "; ruf={}", self.failure_reports.join(","))?;
+        }
+        if let Some(alignment) = self.dkim_alignment {
+            write!(f, "; adkim={}", alignment.as_str())?;
+        }
+        if let Some(alignment) = self.spf_alignment {
+            write!(f, "; aspf={}", alignment.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// The mail records of a domain, set with [`ZoneClient::set_up_mail`].
+/// Records that are not configured are left untouched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailSetup {
+    ttl: u32,
+    mx: Vec<(u16, String)>,
+    spf: Option<Spf>,
+    dkim: Vec<Dkim>,
+    dmarc: Option<Dmarc>,
+}
+
+impl Default for MailSetup {
+    fn default() -> Self {
+        MailSetup {
+            ttl: DEFAULT_TTL,
+            mx: Vec::new(),
+            spf: None,
+            dkim: Vec::new(),
+            dmarc: None,
+        }
+    }
+}
+
+impl MailSetup {
+    pub fn new() -> Self {
+        MailSetup::default()
+    }
+
+    /// The TTL of all records, [`DEFAULT_TTL`] by default
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Receive mail at `exchange`, with lower preferences tried first
+    pub fn mx(mut self, preference: u16, exchange: &str) -> Self {
+        self.mx.push((preference, absolute_name(exchange)));
+        self
+    }
+
+    pub fn spf(mut self, spf: Spf) -> Self {
+        self.spf = Some(spf);
+        self
+    }
+
+    /// Publish a DKIM key. Keys with other selectors are kept, so keys can
+    /// be rotated by publishing the new one before removing the old one.
+    pub fn dkim(mut self, dkim: Dkim) -> Self {
+        self.dkim.push(dkim);
+        self
+    }
+
+    pub fn dmarc(mut self, dmarc: Dmarc) -> Self {
+        self.dmarc = Some(dmarc);
+        self
+    }
+
+    /// The changes setting up mail for `domain`. `txt` are the contents of
+    /// the TXT records of `domain`, of which all but an SPF policy are kept.
+    pub fn changes(&self, domain: &str, txt: &[&str]) -> ChangeSet {
+        let domain = absolute_name(domain);
+        let mut changes = ChangeSet::new();
+
+        if !self.mx.is_empty() {
+            let contents: Vec<String> = self
+                .mx
+                .iter()
+                .map(|(preference, exchange)| format!("{preference} {exchange}"))
+                .collect();
+            let contents: Vec<&str> = contents.iter().map(String::as_str).collect();
+            changes = changes.replace(&domain, RecordType::MX, self.ttl, &contents);
+        }
+
+        if let Some(spf) = &self.spf {
+            let spf = Txt::from_value(&spf.to_string()).to_content();
+            let contents: Vec<&str> = txt
+                .iter()
+                .copied()
+                .filter(|content| {
+                    content
+                        .parse::<Txt>()
+                        .map_or(true, |txt| !Spf::is_spf(&txt))
+                })
+                .chain(std::iter::once(spf.as_str()))
+                .collect();
+            changes = changes.replace(&domain, RecordType::TXT, self.ttl, &contents);
+        }
+
+        for dkim in &self.dkim {
+            let content = dkim.to_txt().to_content();
+            changes = changes.replace(&dkim.name(&domain), RecordType::TXT, self.ttl, &[&content]);
+        }
+
+        if let Some(dmarc) = &self.dmarc {
+            let content = Txt::from_value(&dmarc.to_string()).to_content();
+            changes = changes.replace(
+                &Dmarc::name(&domain),
+                RecordType::TXT,
+                self.ttl,
+                &[&content],
+            );
+        }
+
+        changes
+    }
+}
+
+impl ZoneClient<'_> {
+    /// Set the mail records of `domain`, a name in the zone `zone_id`, in a
+    /// single PATCH. The MX RRSet is replaced, while of the TXT records of
+    /// `domain` only a previous SPF policy is.
+    pub async fn set_up_mail(
+        &self,
+        zone_id: impl IntoZoneId,
+        domain: &str,
+        setup: &MailSetup,
+    ) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let txt = match setup.spf {
+            Some(_) => self
+                .get_rrset(&zone_id, domain, RecordType::TXT)
+                .await?
+                .map(|rrset| rrset.records)
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let txt: Vec<&str> = txt.iter().map(|record| record.content.as_str()).collect();
+        self.commit(&zone_id, &setup.changes(domain, &txt)).await
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use crate::mail::{Alignment, Dkim, Dmarc, DmarcPolicy, MailSetup, Spf, SpfQualifier};
+    use crate::rdata::Txt;
+    use crate::record_type::RecordType;
+    use crate::zones::{RRSet, Record, Zone, ZoneKind};
+
+    #[test]
+    fn format_policies() {
+        let spf = Spf::new()
+            .mx()
+            .mx()
+            .ip4(Ipv4Addr::new(192, 0, 2, 0), Some(24))
+            .include("_spf.example.net.")
+            .all(SpfQualifier::SoftFail);
+        assert_eq!(
+            spf.to_string(),
+            "v=spf1 mx ip4:192.0.2.0/24 include:_spf.example.net ~all"
+        );
+        assert_eq!(Spf::new().to_string(), "v=spf1 -all");
+
+        let dmarc = Dmarc::new(DmarcPolicy::Reject)
+            .subdomain_policy(DmarcPolicy::Quarantine)
+            .percent(150)
+            .aggregate_reports("dmarc@example.com")
+            .aggregate_reports("https://reports.example.net/dmarc")
+            .spf_alignment(Alignment::Strict);
+        assert_eq!(
+            dmarc.to_string(),
+            "v=DMARC1; p=reject; sp=quarantine; pct=100; \
+             rua=mailto:dmarc@example.com,https://reports.example.net/dmarc; aspf=s"
+        );
+        assert_eq!(Dmarc::name("example.com"), "_dmarc.example.com.");
+    }
+
+    #[test]
+    fn dkim_from_pem() {
+        let pem = "-----BEGIN PUBLIC KEY-----\n\
+                   MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8A\n\
+                   MIIBCgKCAQEA\n\
+                   -----END PUBLIC KEY-----\n";
+        let dkim = Dkim::rsa("2024", pem);
+        assert_eq!(dkim.name("example.com."), "2024._domainkey.example.com.");
+        assert_eq!(
+            dkim.to_txt().value(),
+            "v=DKIM1; k=rsa; p=MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEA"
+        );
+
+        let long = Dkim::rsa("long", &"A".repeat(392));
+        assert_eq!(long.to_txt().strings().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn set_up_mail() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .rrset(RRSet::new(
+                "example.com.",
+                RecordType::TXT,
+                300,
+                vec![
+                    Record::new("\"v=spf1 a -all\""),
+                    Record::new("\"site-verification=abc\""),
+                ],
+            ))
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+
+        let setup = MailSetup::new()
+            .mx(10, "mx1.example.com")
+            .mx(20, "mx2.example.com.")
+            .spf(Spf::new().mx())
+            .dkim(Dkim::ed25519(
+                "ed",
+                "11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=",
+            ))
+            .dmarc(Dmarc::new(DmarcPolicy::None));
+        client
+            .zone()
+            .set_up_mail("example.com", "example.com", &setup)
+            .await
+            .unwrap();
+
+        let zones = client.zone();
+        let contents = |rrset: Option<RRSet>| -> Vec<String> {
+            rrset
+                .unwrap()
+                .records
+                .into_iter()
+                .map(|record| record.content)
+                .collect()
+        };
+        let mx = zones
+            .get_rrset("example.com", "example.com.", RecordType::MX)
+            .await
+            .unwrap();
+        assert_eq!(contents(mx), ["10 mx1.example.com.", "20 mx2.example.com."]);
+        let txt = zones
+            .get_rrset("example.com", "example.com.", RecordType::TXT)
+            .await
+            .unwrap();
+        assert_eq!(
+            contents(txt),
+            ["\"site-verification=abc\"", "\"v=spf1 mx -all\""]
+        );
+        let dkim = zones
+            .get_rrset("example.com", "ed._domainkey.example.com.", RecordType::TXT)
+            .await
+            .unwrap();
+        let dkim: Txt = contents(dkim)[0].parse().unwrap();
+        assert!(dkim.value().starts_with("v=DKIM1; k=ed25519; p=11qY"));
+        let dmarc = zones
+            .get_rrset("example.com", "_dmarc.example.com.", RecordType::TXT)
+            .await
+            .unwrap();
+        assert_eq!(contents(dmarc), ["\"v=DMARC1; p=none\""]);
+    }
+}
@@ -0,0 +1,292 @@
+use std::fmt::{Display, Formatter};
+
+use reqwest::StatusCode;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Client;
+use crate::Error;
+use crate::error::decode;
+
+/// Represents zone metadata
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Metadata {
+    /// Name of the metadata
+    pub kind: MetadataKind,
+    /// Array with all values for this metadata kind.
+    pub metadata: Vec<String>,
+}
+
+/// The kind of a zone metadata item. Kinds not known to this crate, such as
+/// custom `X-` kinds, are represented by `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MetadataKind {
+    /// Per-zone AXFR ACLs
+    AllowAxfrFrom,
+    /// IP ranges allowed to perform DNS updates
+    AllowDnsupdateFrom,
+    /// Extra IP addresses to send NOTIFYs to
+    AlsoNotify,
+    /// Whether to rectify the zone on changes via the API
+    ApiRectify,
+    /// The TSIG key to use when retrieving the zone from its master
+    AxfrMasterTsig,
+    /// The source IP address to use when retrieving the zone
+    AxfrSource,
+    /// Whether LUA records are enabled for this zone
+    EnableLuaRecords,
+    /// Whether DNS updates are forwarded to the master
+    ForwardDnsupdate,
+    /// Whether to attempt IXFR when retrieving the zone
+    Ixfr,
+    /// Whether to send a NOTIFY after a successful DNS update
+    NotifyDnsupdate,
+    /// Whether NSEC3 narrow mode is used
+    Nsec3Narrow,
+    /// The NSEC3 parameters of a DNSSEC zone
+    Nsec3Param,
+    /// Whether the zone is pre-signed
+    Presigned,
+    /// Whether to publish CDNSKEY records
+    PublishCdnskey,
+    /// The digest types of CDS records to publish
+    PublishCds,
+    /// Whether to send NOTIFYs after the zone was retrieved from its master
+    SlaveRenotify,
+    /// How the SOA serial is modified in outgoing queries
+    SoaEdit,
+    /// How the SOA serial is modified on changes via the API
+    SoaEditApi,
+    /// How the SOA serial is modified on DNS updates
+    SoaEditDnsupdate,
+    /// The TSIG keys allowed to perform AXFR
+    TsigAllowAxfr,
+    /// The TSIG keys allowed to perform DNS updates
+    TsigAllowDnsupdate,
+    /// Any other metadata kind
+    Other(String),
+}
+
+impl MetadataKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            MetadataKind::AllowAxfrFrom => "ALLOW-AXFR-FROM",
+            MetadataKind::AllowDnsupdateFrom => "ALLOW-DNSUPDATE-FROM",
+            MetadataKind::AlsoNotify => "ALSO-NOTIFY",
+            MetadataKind::ApiRectify => "API-RECTIFY",
+            MetadataKind::AxfrMasterTsig => "AXFR-MASTER-TSIG",
+            MetadataKind::AxfrSource => "AXFR-SOURCE",
+            MetadataKind::EnableLuaRecords => "ENABLE-LUA-RECORDS",
+            MetadataKind::ForwardDnsupdate => "FORWARD-DNSUPDATE",
+            MetadataKind::Ixfr => "IXFR",
+            MetadataKind::NotifyDnsupdate => "NOTIFY-DNSUPDATE",
+            MetadataKind::Nsec3Narrow => "NSEC3NARROW",
+            MetadataKind::Nsec3Param => "NSEC3PARAM",
+            MetadataKind::Presigned => "PRESIGNED",
+            MetadataKind::PublishCdnskey => "PUBLISH-CDNSKEY",
+            MetadataKind::PublishCds => "PUBLISH-CDS",
+            MetadataKind::SlaveRenotify => "SLAVE-RENOTIFY",
+            MetadataKind::SoaEdit => "SOA-EDIT",
+            MetadataKind::SoaEditApi => "SOA-EDIT-API",
+            MetadataKind::SoaEditDnsupdate => "SOA-EDIT-DNSUPDATE",
+            MetadataKind::TsigAllowAxfr => "TSIG-ALLOW-AXFR",
+            MetadataKind::TsigAllowDnsupdate => "TSIG-ALLOW-DNSUPDATE",
+            MetadataKind::Other(kind) => kind,
+        }
+    }
+}
+
+impl From<&str> for MetadataKind {
+    fn from(kind: &str) -> Self {
+        match kind {
+            "ALLOW-AXFR-FROM" => MetadataKind::AllowAxfrFrom,
+            "ALLOW-DNSUPDATE-FROM" => MetadataKind::AllowDnsupdateFrom,
+            "ALSO-NOTIFY" => MetadataKind::AlsoNotify,
+            "API-RECTIFY" => MetadataKind::ApiRectify,
+            "AXFR-MASTER-TSIG" => MetadataKind::AxfrMasterTsig,
+            "AXFR-SOURCE" => MetadataKind::AxfrSource,
+            "ENABLE-LUA-RECORDS" => MetadataKind::EnableLuaRecords,
+            "FORWARD-DNSUPDATE" => MetadataKind::ForwardDnsupdate,
+            "IXFR" => MetadataKind::Ixfr,
+            "NOTIFY-DNSUPDATE" => MetadataKind::NotifyDnsupdate,
+            "NSEC3NARROW" => MetadataKind::Nsec3Narrow,
+            "NSEC3PARAM" => MetadataKind::Nsec3Param,
+            "PRESIGNED" => MetadataKind::Presigned,
+            "PUBLISH-CDNSKEY" => MetadataKind::PublishCdnskey,
+            "PUBLISH-CDS" => MetadataKind::PublishCds,
+            "SLAVE-RENOTIFY" => MetadataKind::SlaveRenotify,
+            "SOA-EDIT" => MetadataKind::SoaEdit,
+            "SOA-EDIT-API" => MetadataKind::SoaEditApi,
+            "SOA-EDIT-DNSUPDATE" => MetadataKind::SoaEditDnsupdate,
+            "TSIG-ALLOW-AXFR" => MetadataKind::TsigAllowAxfr,
+            "TSIG-ALLOW-DNSUPDATE" => MetadataKind::TsigAllowDnsupdate,
+            other => MetadataKind::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for MetadataKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for MetadataKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for MetadataKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let kind = String::deserialize(deserializer)?;
+        Ok(MetadataKind::from(kind.as_str()))
+    }
+}
+
+pub struct MetadataClient<'a> {
+    api_client: &'a Client,
+    zone_id: String,
+}
+
+impl<'a> MetadataClient<'a> {
+    pub fn new(api_client: &'a Client, zone_id: &str) -> Self {
+        MetadataClient {
+            api_client,
+            zone_id: zone_id.to_string(),
+        }
+    }
+
+    fn url(&self) -> String {
+        format!(
+            "{}/servers/{}/zones/{}/metadata",
+            self.api_client.api_url, self.api_client.server_name, self.zone_id
+        )
+    }
+
+    /// Get all the Metadata associated with the zone.
+    pub async fn list(&self) -> Result<Vec<Metadata>, Error> {
+        let response = self.api_client.get(self.url()).send().await?;
+
+        match response.status() {
+            // 200 OK – List of Metadata objects Returns: array of Metadata objects
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<Vec<Metadata>>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Creates a set of metadata entries of given kind for the zone.
+    ///
+    /// Existing metadata entries for the zone with the same kind are not
+    /// overwritten.
+    pub async fn create(&self, metadata: Metadata) -> Result<(), Error> {
+        let response = self
+            .api_client
+            .post(self.url())
+            .json(&metadata)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 204 No Content – OK
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK | StatusCode::CREATED | StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Get the content of a single kind of domain metadata as a Metadata
+    /// object.
+    pub async fn get(&self, kind: &MetadataKind) -> Result<Metadata, Error> {
+        let response = self
+            .api_client
+            .get(format!("{}/{kind}", self.url()))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – Metadata object with list of values Returns: Metadata object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<Metadata>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Replace the content of a single kind of domain metadata.
+    ///
+    /// Creates a set of metadata entries of given kind for the zone. Existing
+    /// metadata entries for the zone with the same kind are removed.
+    pub async fn update(&self, metadata: Metadata) -> Result<Metadata, Error> {
+        let response = self
+            .api_client
+            .put(format!("{}/{}", self.url(), metadata.kind))
+            .json(&metadata)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – Metadata object with list of values Returns: Metadata object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<Metadata>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Delete all items of a single kind of domain metadata.
+    pub async fn delete(&self, kind: &MetadataKind) -> Result<(), Error> {
+        let response = self
+            .api_client
+            .delete(format!("{}/{kind}", self.url()))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 204 No Content – OK
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK | StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metadata::{Metadata, MetadataKind};
+
+    #[test]
+    fn known_kind() {
+        let metadata: Metadata = serde_json::from_value(serde_json::json!({
+            "kind": "ALLOW-AXFR-FROM",
+            "metadata": ["AUTO-NS", "2001:db8::/48"]
+        }))
+        .unwrap();
+        assert_eq!(metadata.kind, MetadataKind::AllowAxfrFrom);
+    }
+
+    #[test]
+    fn custom_kind() {
+        let kind = MetadataKind::from("X-CUSTOM");
+        assert_eq!(kind, MetadataKind::Other(String::from("X-CUSTOM")));
+        assert_eq!(serde_json::to_value(&kind).unwrap(), "X-CUSTOM");
+    }
+}
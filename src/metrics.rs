@@ -0,0 +1,255 @@
+//! Convert the statistics of a server into Prometheus metrics, e.g. to
+//! expose the health of PowerDNS on the `/metrics` endpoint of the
+//! application embedding this crate.
+//!
+//! [`from_statistics`] names the metrics like the exporter built into
+//! PowerDNS: `uptime` becomes the gauge `pdns_auth_uptime`, `udp-queries`
+//! the counter `pdns_auth_udp_queries_total`, and the entries of map
+//! statistics like `response-by-qtype` become labels. [`encode_text`]
+//! renders them in the text exposition format, which can be appended to
+//! the output of any Prometheus client library.
+
+use std::fmt::Write;
+
+use crate::recursor::RecursorClient;
+use crate::server::{ServerClient, Statistic};
+use crate::Error;
+
+/// The namespace of the metrics of an Authoritative Server
+pub const AUTH_NAMESPACE: &str = "pdns_auth";
+/// The namespace of the metrics of a Recursor
+pub const RECURSOR_NAMESPACE: &str = "pdns_recursor";
+
+/// A single sample converted from a [`Statistic`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metric {
+    /// The Prometheus name, e.g. `pdns_auth_udp_queries_total`
+    pub name: String,
+    pub kind: MetricKind,
+    /// Label names and values, e.g. `("qtype", "A")`
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+}
+
+/// The Prometheus type of a [`Metric`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    /// A value that only increases, until the server restarts
+    Counter,
+    /// A value that can go up and down, like a cache size
+    Gauge,
+    /// A bucket, the sum or the count of a histogram, whose names end with
+    /// `_bucket`, `_sum` and `_count`
+    Histogram,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// Statistics that are gauges, besides the sizes of caches
+const GAUGES: [&str; 16] = [
+    "backend-latency",
+    "cache-latency",
+    "concurrent-queries",
+    "fd-usage",
+    "latency",
+    "open-tcp-connections",
+    "qsize-q",
+    "real-memory-usage",
+    "receive-latency",
+    "security-status",
+    "send-latency",
+    "special-memory-usage",
+    "tcp-clients",
+    "threads",
+    "uptime",
+    "xfr-queue",
+];
+
+/// The label of the entries of map statistics, by statistic
+const MAP_LABELS: [(&str, &str); 3] = [
+    ("response-by-qtype", "qtype"),
+    ("response-by-rcode", "rcode"),
+    ("response-sizes", "size"),
+];
+
+/// Convert statistics into metrics named `{namespace}_{name}`, see
+/// [`crate::metrics`]. Ring statistics, which hold the most frequent
+/// queries rather than measurements, and values that are not numbers are
+/// left out.
+pub fn from_statistics(namespace: &str, statistics: &[Statistic]) -> Vec<Metric> {
+    let mut metrics = Vec::new();
+    for statistic in statistics {
+        match statistic {
+            Statistic::StatisticItem { name, value } => {
+                let Ok(value) = value.parse::<f64>() else {
+                    continue;
+                };
+                let kind = kind_of(name);
+                metrics.push(Metric {
+                    name: metric_name(namespace, name, kind),
+                    kind,
+                    labels: Vec::new(),
+                    value,
+                });
+            }
+            Statistic::MapStatisticItem { name, value } => {
+                let label = MAP_LABELS
+                    .iter()
+                    .find(|(statistic, _)| statistic == name)
+                    .map_or("name", |(_, label)| label);
+                let kind = kind_of(name);
+                for item in value {
+                    let Ok(sample) = item.value.parse::<f64>() else {
+                        continue;
+                    };
+                    metrics.push(Metric {
+                        name: metric_name(namespace, name, kind),
+                        kind,
+                        labels: vec![(label.to_string(), item.name.clone())],
+                        value: sample,
+                    });
+                }
+            }
+            Statistic::RingStatisticItem { .. } => {}
+        }
+    }
+    metrics
+}
+
+/// Render metrics in the Prometheus text exposition format, with one
+/// `# TYPE` line per metric family. The metrics of a family must be
+/// adjacent.
+pub fn encode_text(metrics: &[Metric]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&str> = None;
+    for metric in metrics {
+        let family = family(metric);
+        if previous != Some(family) {
+            let _ = writeln!(text, "# TYPE {family} {}", metric.kind.as_str());
+            previous = Some(family);
+        }
+        text.push_str(&metric.name);
+        if !metric.labels.is_empty() {
+            let labels: Vec<String> = metric
+                .labels
+                .iter()
+                .map(|(name, value)| format!("{name}=\"{}\"", escape_label(value)))
+                .collect();
+            let _ = write!(text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(text, " {}", metric.value);
+    }
+    text
+}
+
+/// The name of a metric without the suffixes of histograms
+fn family(metric: &Metric) -> &str {
+    if metric.kind != MetricKind::Histogram {
+        return &metric.name;
+    }
+    ["_bucket", "_sum", "_count"]
+        .iter()
+        .find_map(|suffix| metric.name.strip_suffix(suffix))
+        .unwrap_or(&metric.name)
+}
+
+fn kind_of(statistic: &str) -> MetricKind {
+    let is_size = statistic.ends_with("-size") || statistic.ends_with("-entries");
+    if is_size || GAUGES.contains(&statistic) {
+        MetricKind::Gauge
+    } else {
+        MetricKind::Counter
+    }
+}
+
+/// Replace the characters that are not allowed in metric names, and add the
+/// `_total` suffix of counters
+fn metric_name(namespace: &str, statistic: &str, kind: MetricKind) -> String {
+    let statistic: String = statistic
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match kind {
+        MetricKind::Counter => format!("{namespace}_{statistic}_total"),
+        MetricKind::Gauge | MetricKind::Histogram => format!("{namespace}_{statistic}"),
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl ServerClient<'_> {
+    /// The statistics of the server as metrics in the
+    /// [`AUTH_NAMESPACE`], see [`from_statistics`]
+    pub async fn metrics(&self) -> Result<Vec<Metric>, Error> {
+        let statistics = self.statistics(None, false).await?;
+        Ok(from_statistics(AUTH_NAMESPACE, &statistics))
+    }
+}
+
+impl RecursorClient<'_> {
+    /// The statistics of the recursor as metrics in the
+    /// [`RECURSOR_NAMESPACE`], see [`from_statistics`]
+    pub async fn metrics(&self) -> Result<Vec<Metric>, Error> {
+        let statistics = self.statistics(None, false).await?;
+        Ok(from_statistics(RECURSOR_NAMESPACE, &statistics))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::{encode_text, from_statistics, MetricKind, AUTH_NAMESPACE};
+    use crate::server::Statistic;
+
+    #[test]
+    fn convert_statistics() {
+        let statistics: Vec<Statistic> = serde_json::from_value(serde_json::json!([
+            {"name": "uptime", "type": "StatisticItem", "value": "42"},
+            {"name": "udp-queries", "type": "StatisticItem", "value": "1337"},
+            {"name": "packetcache-size", "type": "StatisticItem", "value": "12"},
+            {"name": "security-status-message", "type": "StatisticItem", "value": "OK"},
+            {
+                "name": "response-by-qtype",
+                "type": "MapStatisticItem",
+                "value": [{"name": "A", "value": "10"}, {"name": "AAAA", "value": "5"}]
+            },
+            {
+                "name": "queries",
+                "type": "RingStatisticItem",
+                "size": 10000,
+                "value": [{"name": "example.com/A", "value": "3"}]
+            }
+        ]))
+        .unwrap();
+
+        let metrics = from_statistics(AUTH_NAMESPACE, &statistics);
+        assert_eq!(metrics.len(), 5);
+        assert_eq!(metrics[0].kind, MetricKind::Gauge);
+        assert_eq!(metrics[1].name, "pdns_auth_udp_queries_total");
+        assert_eq!(metrics[2].kind, MetricKind::Gauge);
+        assert_eq!(
+            encode_text(&metrics),
+            "# TYPE pdns_auth_uptime gauge\n\
+             pdns_auth_uptime 42\n\
+             # TYPE pdns_auth_udp_queries_total counter\n\
+             pdns_auth_udp_queries_total 1337\n\
+             # TYPE pdns_auth_packetcache_size gauge\n\
+             pdns_auth_packetcache_size 12\n\
+             # TYPE pdns_auth_response_by_qtype_total counter\n\
+             pdns_auth_response_by_qtype_total{qtype=\"A\"} 10\n\
+             pdns_auth_response_by_qtype_total{qtype=\"AAAA\"} 5\n"
+        );
+    }
+}
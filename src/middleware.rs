@@ -0,0 +1,147 @@
+use std::fmt;
+use std::sync::Arc;
+
+use reqwest::{Request, Response};
+
+use crate::Error;
+
+/// Hooks that are called around every request sent by a Client, e.g. to add
+/// correlation ids or custom authentication headers, collect metrics, or
+/// inject faults in tests.
+///
+/// Hooks run once per attempt, so a retried request passes through
+/// `on_request` again. Middleware added to a ClientBuilder runs in the order
+/// it was added.
+pub trait Middleware: Send + Sync {
+    /// Called before a request is sent. Returning an error aborts the request
+    /// without sending it.
+    fn on_request(&self, _request: &mut Request) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called when the response headers have been received. Returning an
+    /// error discards the response.
+    fn on_response(&self, _response: &Response) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called when a request could not be sent or no response was received
+    fn on_error(&self, _error: &reqwest::Error) {}
+}
+
+/// The middleware of a Client, in the order it is called
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareStack(Vec<Arc<dyn Middleware>>);
+
+impl MiddlewareStack {
+    pub(crate) fn push(&mut self, middleware: Arc<dyn Middleware>) {
+        self.0.push(middleware);
+    }
+
+    pub(crate) fn on_request(&self, request: &mut Request) -> Result<(), Error> {
+        self.0.iter().try_for_each(|m| m.on_request(request))
+    }
+
+    pub(crate) fn on_result(&self, result: reqwest::Result<Response>) -> Result<Response, Error> {
+        match result {
+            Ok(response) => {
+                self.0.iter().try_for_each(|m| m.on_response(&response))?;
+                Ok(response)
+            }
+            Err(e) => {
+                self.0.iter().for_each(|m| m.on_error(&e));
+                Err(e.into())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for MiddlewareStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MiddlewareStack")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+
+    use reqwest::header::{HeaderName, HeaderValue};
+    use reqwest::{Request, Response, StatusCode};
+
+    use crate::middleware::Middleware;
+    use crate::{Client, Error};
+
+    #[derive(Default)]
+    struct CorrelationId {
+        statuses: Mutex<Vec<StatusCode>>,
+    }
+
+    impl Middleware for CorrelationId {
+        fn on_request(&self, request: &mut Request) -> Result<(), Error> {
+            request.headers_mut().insert(
+                HeaderName::from_static("x-request-id"),
+                HeaderValue::from_static("42"),
+            );
+            Ok(())
+        }
+
+        fn on_response(&self, response: &Response) -> Result<(), Error> {
+            self.statuses.lock().unwrap().push(response.status());
+            Ok(())
+        }
+    }
+
+    struct Unavailable;
+
+    impl Middleware for Unavailable {
+        fn on_request(&self, _request: &mut Request) -> Result<(), Error> {
+            Err(Error::Other("injected fault".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn add_header_and_observe_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let len = stream.read(&mut request).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n[]")
+                .unwrap();
+            String::from_utf8_lossy(&request[..len]).to_lowercase()
+        });
+
+        let middleware = std::sync::Arc::new(CorrelationId::default());
+        let client = Client::builder()
+            .base_url(&base_url)
+            .api_key("secret")
+            .middleware_arc(middleware.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(client.tsigkeys().list().await.unwrap(), vec![]);
+        assert!(server.join().unwrap().contains("x-request-id: 42"));
+        assert_eq!(*middleware.statuses.lock().unwrap(), vec![StatusCode::OK]);
+    }
+
+    #[tokio::test]
+    async fn abort_request() {
+        let client = Client::builder()
+            .base_url("http://127.0.0.1:1")
+            .middleware(Unavailable)
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            client.tsigkeys().list().await,
+            Err(Error::Other(e)) if e.to_string() == "injected fault"
+        ));
+    }
+}
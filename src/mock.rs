@@ -0,0 +1,680 @@
+//! An in-memory mock of the PowerDNS Authoritative Server API, to exercise
+//! code using a [`Client`] in tests without running PowerDNS.
+//!
+//! The mock serves the servers, zones, cryptokeys and metadata endpoints from a
+//! HashMap, which is enough for create, patch and delete flows. Other
+//! endpoints respond with 404 Not Found.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use powerdns::mock::MockServer;
+//! use powerdns::zones::{Zone, ZoneKind};
+//!
+//! let server = MockServer::start();
+//! let client = server.client();
+//! client.zone().create(Zone::builder("example.com.").kind(ZoneKind::Native).build()?).await?;
+//! assert!(server.zone("example.com.").is_some());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::cryptokeys::Cryptokey;
+use crate::metadata::Metadata;
+use crate::record_type::RecordType;
+#[allow(deprecated)]
+use crate::zones::{ChangeType, Comment, RRSet, Record, Zone, ZoneSettings};
+use crate::Client;
+
+/// The API key a MockServer accepts unless configured otherwise
+pub const DEFAULT_API_KEY: &str = "mock-api-key";
+
+/// The id of the only server the mock serves
+const SERVER_ID: &str = "localhost";
+
+/// A mock PowerDNS webserver listening on a random local port. The server is
+/// shut down when this is dropped.
+#[allow(deprecated)]
+pub struct MockServer {
+    addr: SocketAddr,
+    api_key: String,
+    zones: Arc<Mutex<HashMap<String, Zone>>>,
+    cryptokeys: Arc<Mutex<HashMap<String, Vec<Cryptokey>>>>,
+    metadata: Arc<Mutex<HashMap<String, Vec<Metadata>>>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+#[allow(deprecated)]
+impl MockServer {
+    /// Start a mock server accepting [`DEFAULT_API_KEY`]
+    pub fn start() -> Self {
+        MockServer::with_api_key(DEFAULT_API_KEY)
+    }
+
+    /// Start a mock server accepting only `api_key`
+    pub fn with_api_key(api_key: &str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock server");
+        let server = MockServer {
+            addr: listener.local_addr().expect("mock server has no address"),
+            api_key: api_key.to_string(),
+            zones: Arc::default(),
+            cryptokeys: Arc::default(),
+            metadata: Arc::default(),
+            shutdown: Arc::default(),
+        };
+
+        let handler = Handler {
+            api_key: server.api_key.clone(),
+            zones: server.zones.clone(),
+            cryptokeys: server.cryptokeys.clone(),
+            metadata: server.metadata.clone(),
+        };
+        let shutdown = server.shutdown.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let handler = handler.clone();
+                    std::thread::spawn(move || handler.serve(stream));
+                }
+            }
+        });
+
+        server
+    }
+
+    /// The URL to pass as `base_url` to a Client
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// The API key the server accepts
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    /// A Client for this server
+    pub fn client(&self) -> Client {
+        Client::builder()
+            .base_url(self.base_url())
+            .server_name(SERVER_ID)
+            .api_key(&self.api_key)
+            .build()
+            .unwrap()
+    }
+
+    /// The current state of a zone, including its rrsets
+    pub fn zone(&self, zone_id: &str) -> Option<Zone> {
+        self.zones.lock().unwrap().get(zone_id).cloned()
+    }
+
+    /// The current state of all zones, ordered by name
+    pub fn zones(&self) -> Vec<Zone> {
+        let mut zones: Vec<Zone> = self.zones.lock().unwrap().values().cloned().collect();
+        zones.sort_by(|a, b| a.name.cmp(&b.name));
+        zones
+    }
+
+    /// The cryptokeys of a zone, including their private keys
+    pub fn cryptokeys(&self, zone_id: &str) -> Vec<Cryptokey> {
+        let cryptokeys = self.cryptokeys.lock().unwrap();
+        cryptokeys.get(zone_id).cloned().unwrap_or_default()
+    }
+
+    /// Add a zone to the server, bypassing the validation of the API. The
+    /// zone id defaults to its name.
+    ///
+    /// # Panics
+    ///
+    /// If neither the zone id nor its name are set
+    pub fn insert_zone(&self, mut zone: Zone) {
+        let id = zone
+            .id
+            .clone()
+            .or_else(|| zone.name.clone())
+            .expect("zone has no id or name");
+        zone.id = Some(id.clone());
+        self.zones.lock().unwrap().insert(id, zone);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // wake up the listener thread so it notices the shutdown
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+/// A parsed HTTP request
+struct HttpRequest {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+type HttpResponse = (u16, Option<Value>);
+
+#[derive(Clone)]
+#[allow(deprecated)]
+struct Handler {
+    api_key: String,
+    zones: Arc<Mutex<HashMap<String, Zone>>>,
+    cryptokeys: Arc<Mutex<HashMap<String, Vec<Cryptokey>>>>,
+    metadata: Arc<Mutex<HashMap<String, Vec<Metadata>>>>,
+}
+
+#[allow(deprecated)]
+impl Handler {
+    fn serve(&self, stream: TcpStream) {
+        let mut reader = BufReader::new(&stream);
+        let (status, body) = match read_request(&mut reader) {
+            Some(request) => self.handle(&request),
+            None => error(400, "Malformed request"),
+        };
+
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        let response = format!(
+            "HTTP/1.1 {status} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            reason(status),
+            body.len(),
+        );
+        let _ = (&stream).write_all(response.as_bytes());
+    }
+
+    fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        if request.headers.get("x-api-key") != Some(&self.api_key) {
+            return error(401, "Unauthorized");
+        }
+
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["api"]) => (200, Some(json!([{"url": "/api/v1", "version": 1}]))),
+            ("GET", ["api", "v1", "servers"]) => (200, Some(json!([server()]))),
+            ("GET", ["api", "v1", "servers", SERVER_ID]) => (200, Some(server())),
+            (method, ["api", "v1", "servers", SERVER_ID, "zones"]) => match method {
+                "GET" => self.list_zones(request),
+                "POST" => self.create_zone(request),
+                _ => error(405, "Method Not Allowed"),
+            },
+            (method, ["api", "v1", "servers", SERVER_ID, "zones", zone_id]) => {
+                let mut zones = self.zones.lock().unwrap();
+                let Some(zone) = zones.get_mut(*zone_id) else {
+                    return error(404, &format!("Could not find domain '{zone_id}'"));
+                };
+                match method {
+                    "GET" => get_zone(zone, request),
+                    "PUT" => update_zone(zone, request),
+                    "PATCH" => patch_zone(zone, request),
+                    "DELETE" => {
+                        zones.remove(*zone_id);
+                        (204, None)
+                    }
+                    _ => error(405, "Method Not Allowed"),
+                }
+            }
+            (_, ["api", "v1", "servers", SERVER_ID, "zones", zone_id, ..])
+                if !self.zones.lock().unwrap().contains_key(*zone_id) =>
+            {
+                error(404, &format!("Could not find domain '{zone_id}'"))
+            }
+            ("PUT", ["api", "v1", "servers", SERVER_ID, "zones", _, "rectify"]) => {
+                (200, Some(json!({ "result": "Rectified" })))
+            }
+            (
+                method,
+                ["api", "v1", "servers", SERVER_ID, "zones", zone_id, "cryptokeys", rest @ ..],
+            ) => {
+                let mut cryptokeys = self.cryptokeys.lock().unwrap();
+                let cryptokeys = cryptokeys.entry(zone_id.to_string()).or_default();
+                match (method, rest) {
+                    ("GET", []) => {
+                        let listed: Vec<Cryptokey> = cryptokeys
+                            .iter()
+                            .map(|key| Cryptokey {
+                                privatekey: None,
+                                ..key.clone()
+                            })
+                            .collect();
+                        (200, Some(json!(listed)))
+                    }
+                    ("POST", []) => create_cryptokey(cryptokeys, zone_id, request),
+                    (method, [id]) => {
+                        let Some(i) = cryptokeys.iter().position(|key| {
+                            key.id.map(|id| id.to_string()).as_deref() == Some(*id)
+                        }) else {
+                            return error(404, "Could not find cryptokey");
+                        };
+                        match method {
+                            "GET" => (200, Some(json!(cryptokeys[i]))),
+                            "PUT" => update_cryptokey(&mut cryptokeys[i], request),
+                            "DELETE" => {
+                                cryptokeys.remove(i);
+                                (204, None)
+                            }
+                            _ => error(405, "Method Not Allowed"),
+                        }
+                    }
+                    _ => error(405, "Method Not Allowed"),
+                }
+            }
+            (method, ["api", "v1", "servers", SERVER_ID, "zones", zone_id, "metadata"]) => {
+                let mut metadata = self.metadata.lock().unwrap();
+                let metadata = metadata.entry(zone_id.to_string()).or_default();
+                match method {
+                    "GET" => (200, Some(json!(metadata))),
+                    "POST" => create_metadata(metadata, request),
+                    _ => error(405, "Method Not Allowed"),
+                }
+            }
+            _ => error(404, "Not Found"),
+        }
+    }
+
+    fn list_zones(&self, request: &HttpRequest) -> HttpResponse {
+        let filter = request
+            .query
+            .iter()
+            .find(|(key, _)| key == "zone")
+            .map(|(_, value)| value);
+
+        let mut zones: Vec<Zone> = self
+            .zones
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|zone| filter.is_none() || zone.name.as_ref() == filter)
+            .map(|zone| Zone {
+                rrsets: None,
+                ..zone.clone()
+            })
+            .collect();
+        zones.sort_by(|a, b| a.name.cmp(&b.name));
+        (200, Some(json!(zones)))
+    }
+
+    fn create_zone(&self, request: &HttpRequest) -> HttpResponse {
+        let mut zone: Zone = match serde_json::from_slice(&request.body) {
+            Ok(zone) => zone,
+            Err(e) => return error(400, &e.to_string()),
+        };
+
+        let Some(name) = zone.name.clone() else {
+            return error(422, "Domain name is required");
+        };
+        if !name.ends_with('.') {
+            return error(422, &format!("DNS Name '{name}' is not canonical"));
+        }
+        if zone.kind.is_none() {
+            return error(422, "Zone kind is required");
+        }
+
+        let mut zones = self.zones.lock().unwrap();
+        if zones.contains_key(&name) {
+            return error(409, &format!("Domain '{name}' already exists"));
+        }
+
+        if let Some(zonefile) = zone.zone.take() {
+            match crate::zonefile::parse(&zonefile, &name) {
+                Ok(rrsets) => zone.rrsets = Some(rrsets),
+                Err(e) => return error(422, &e.to_string()),
+            }
+        }
+
+        zone.id = Some(name.clone());
+        zone.type_field = Some(String::from("Zone"));
+        zone.url = Some(format!("/api/v1/servers/{SERVER_ID}/zones/{name}"));
+        zone.serial = zone.serial.or(Some(1));
+        let rrsets = zone.rrsets.get_or_insert_with(Vec::new);
+        for rrset in rrsets.iter_mut() {
+            rrset.changetype = None;
+        }
+        zone.nameservers = None;
+
+        zones.insert(name, zone.clone());
+        (201, Some(json!(zone)))
+    }
+}
+
+fn create_cryptokey(
+    cryptokeys: &mut Vec<Cryptokey>,
+    zone_id: &str,
+    request: &HttpRequest,
+) -> HttpResponse {
+    let cryptokey: Cryptokey = match serde_json::from_slice(&request.body) {
+        Ok(cryptokey) => cryptokey,
+        Err(e) => return error(400, &e.to_string()),
+    };
+    if cryptokey.keytype.is_none() {
+        return error(422, "Invalid keytype");
+    }
+
+    let id = cryptokeys
+        .iter()
+        .filter_map(|key| key.id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let cryptokey = Cryptokey {
+        type_field: Some(String::from("Cryptokey")),
+        id: Some(id),
+        active: Some(cryptokey.active.unwrap_or(false)),
+        published: Some(cryptokey.published.unwrap_or(true)),
+        algorithm: Some(
+            cryptokey
+                .algorithm
+                .unwrap_or_else(|| String::from("ECDSAP256SHA256")),
+        ),
+        dnskey: Some(format!("257 3 13 mock-key-{id}")),
+        ds: Some(vec![format!("{id} 13 2 {zone_id}")]),
+        privatekey: Some(String::from("Private-key-format: v1.2")),
+        ..cryptokey
+    };
+    cryptokeys.push(cryptokey.clone());
+    (201, Some(json!(cryptokey)))
+}
+
+/// Add the values of a metadata item to the existing ones of its kind
+fn create_metadata(metadata: &mut Vec<Metadata>, request: &HttpRequest) -> HttpResponse {
+    let item: Metadata = match serde_json::from_slice(&request.body) {
+        Ok(item) => item,
+        Err(e) => return error(400, &e.to_string()),
+    };
+    match metadata
+        .iter_mut()
+        .find(|existing| existing.kind == item.kind)
+    {
+        Some(existing) => existing.metadata.extend(item.metadata),
+        None => metadata.push(item),
+    }
+    (204, None)
+}
+
+fn update_cryptokey(cryptokey: &mut Cryptokey, request: &HttpRequest) -> HttpResponse {
+    let update: Cryptokey = match serde_json::from_slice(&request.body) {
+        Ok(update) => update,
+        Err(e) => return error(400, &e.to_string()),
+    };
+    if update.active.is_some() {
+        cryptokey.active = update.active;
+    }
+    if update.published.is_some() {
+        cryptokey.published = update.published;
+    }
+    (204, None)
+}
+
+#[allow(deprecated)]
+fn update_zone(zone: &mut Zone, request: &HttpRequest) -> HttpResponse {
+    let settings: ZoneSettings = match serde_json::from_slice(&request.body) {
+        Ok(settings) => settings,
+        Err(e) => return error(400, &e.to_string()),
+    };
+
+    macro_rules! apply {
+        ($($field:ident),*) => {
+            $(if settings.$field.is_some() {
+                zone.$field = settings.$field;
+            })*
+        };
+    }
+    apply!(
+        kind,
+        masters,
+        account,
+        soa_edit,
+        soa_edit_api,
+        api_rectify,
+        master_tsig_key_ids,
+        slave_tsig_key_ids,
+        catalog
+    );
+    (204, None)
+}
+
+/// The body of a PATCH request as the server reads it, independent of how
+/// the client models it
+#[derive(Deserialize)]
+struct RawPatchZone {
+    rrsets: Vec<RawRRSet>,
+}
+
+#[derive(Deserialize)]
+struct RawRRSet {
+    name: String,
+    #[serde(rename = "type")]
+    type_field: RecordType,
+    ttl: Option<u32>,
+    changetype: Option<ChangeType>,
+    #[serde(default)]
+    records: Vec<Record>,
+    comments: Option<Vec<Comment>>,
+}
+
+#[allow(deprecated)]
+fn patch_zone(zone: &mut Zone, request: &HttpRequest) -> HttpResponse {
+    let patch: RawPatchZone = match serde_json::from_slice(&request.body) {
+        Ok(patch) => patch,
+        Err(e) => return error(400, &e.to_string()),
+    };
+
+    let zone_name = zone.name.clone().unwrap_or_default();
+    let rrsets = zone.rrsets.get_or_insert_with(Vec::new);
+    for rrset in patch.rrsets {
+        let in_zone = rrset.name == zone_name || rrset.name.ends_with(&format!(".{zone_name}"));
+        if !in_zone {
+            return error(
+                422,
+                &format!(
+                    "RRset {} IN {}: Name is out of zone",
+                    rrset.name, rrset.type_field
+                ),
+            );
+        }
+
+        rrsets.retain(|r| r.name != rrset.name || r.type_field != rrset.type_field);
+        let empty = rrset.records.is_empty() && rrset.comments.as_ref().is_none_or(Vec::is_empty);
+        match rrset.changetype {
+            Some(ChangeType::Replace) if empty => {}
+            Some(ChangeType::Replace) => {
+                let Some(ttl) = rrset.ttl else {
+                    return error(422, "Key 'ttl' not present or not a Number");
+                };
+                rrsets.push(RRSet {
+                    name: rrset.name,
+                    type_field: rrset.type_field,
+                    ttl,
+                    changetype: None,
+                    records: rrset.records,
+                    comments: rrset.comments,
+                    extra: HashMap::new(),
+                });
+            }
+            Some(ChangeType::Delete) => {}
+            None => return error(422, "Changetype not understood"),
+        }
+    }
+
+    zone.serial = Some(zone.serial.unwrap_or_default() + 1);
+    (204, None)
+}
+
+fn read_request(reader: &mut impl BufRead) -> Option<HttpRequest> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line).ok()?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header.split_once(':')?;
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+
+    let length = headers
+        .get("content-length")
+        .map_or(Some(0), |length| length.parse().ok())?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    Some(HttpRequest {
+        method,
+        path: path.to_string(),
+        query,
+        headers,
+        body,
+    })
+}
+
+/// A zone with the RRSets selected by the `rrsets`, `rrset_name` and
+/// `rrset_type` parameters
+#[allow(deprecated)]
+fn get_zone(zone: &Zone, request: &HttpRequest) -> HttpResponse {
+    let param = |key: &str| {
+        request
+            .query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    };
+    let mut zone = zone.clone();
+    if param("rrsets") == Some("false") {
+        zone.rrsets = None;
+    } else if let Some(rrsets) = &mut zone.rrsets {
+        rrsets.retain(|rrset| {
+            param("rrset_name").is_none_or(|name| rrset.name == name)
+                && param("rrset_type").is_none_or(|rtype| rrset.type_field.as_str() == rtype)
+        });
+    }
+    (200, Some(json!(zone)))
+}
+
+fn server() -> Value {
+    json!({
+        "type": "Server",
+        "id": SERVER_ID,
+        "daemon_type": "authoritative",
+        "version": "mock",
+        "url": format!("/api/v1/servers/{SERVER_ID}"),
+        "config_url": format!("/api/v1/servers/{SERVER_ID}/config{{/config_setting}}"),
+        "zones_url": format!("/api/v1/servers/{SERVER_ID}/zones{{/zone}}"),
+    })
+}
+
+fn error(status: u16, message: &str) -> HttpResponse {
+    (status, Some(json!({ "error": message })))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::mock::MockServer;
+    use crate::record_type::RecordType;
+    use crate::zones::{PatchZone, RRSetUpdate, Record, ReplaceRRSet, Zone, ZoneKind};
+    use crate::{Client, Error};
+
+    fn replace(content: &[&str]) -> RRSetUpdate {
+        RRSetUpdate::Replace(ReplaceRRSet {
+            name: String::from("www.example.com."),
+            rtype: RecordType::A,
+            ttl: 300,
+            records: content
+                .iter()
+                .map(|content| Record {
+                    content: content.to_string(),
+                    disabled: Some(false),
+                })
+                .collect(),
+            comments: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn create_patch_delete() {
+        let server = MockServer::start();
+        let zones = server.client();
+        let zones = zones.zone();
+
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        let created = zones.create(zone.clone()).await.unwrap();
+        assert_eq!(created.id.as_deref(), Some("example.com."));
+        assert!(matches!(
+            zones.create(zone).await,
+            Err(Error::Conflict { error, .. }) if error.error == "Domain 'example.com.' already exists"
+        ));
+
+        let patch = PatchZone {
+            rrsets: vec![replace(&["192.0.2.1", "192.0.2.2"])],
+        };
+        zones.patch("example.com.", patch).await.unwrap();
+        let zone = zones.get("example.com.").await.unwrap();
+        assert_eq!(zone.rrsets.unwrap()[0].records.len(), 2);
+        assert_eq!(zone.serial, Some(2));
+
+        let patch = PatchZone {
+            rrsets: vec![RRSetUpdate::delete("www.example.com.", RecordType::A)],
+        };
+        zones.patch("example.com.", patch).await.unwrap();
+        assert_eq!(server.zone("example.com.").unwrap().rrsets, Some(vec![]));
+
+        assert_eq!(zones.list().await.unwrap().len(), 1);
+        zones.delete("example.com.").await.unwrap();
+        assert!(zones.list().await.unwrap().is_empty());
+        assert!(zones.get("example.com.").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn reject_wrong_api_key() {
+        let server = MockServer::start();
+        let client = Client::builder()
+            .base_url(server.base_url())
+            .server_name("localhost")
+            .api_key("wrong")
+            .build()
+            .unwrap();
+        assert!(client.zone().list().await.is_err());
+    }
+}
@@ -0,0 +1,290 @@
+//! Domain names and zone ids that are known to be in canonical form.
+//!
+//! The methods of [`ZoneClient`](crate::zones::ZoneClient) take their zone
+//! id as an [`IntoZoneId`]: a [`ZoneId`], whose errors were reported when it
+//! was constructed, or a string canonicalized when the method is called.
+//! Both newtypes deref to `str`, so they can be passed to methods taking
+//! names as well.
+
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::zones::{absolute_name, canonicalize_domain, DomainError};
+use crate::Error;
+
+/// The longest name in presentation format, including the trailing dot
+const MAX_NAME_LENGTH: usize = 254;
+const MAX_LABEL_LENGTH: usize = 63;
+
+/// An owner name of records, lowercase with a trailing dot and with valid
+/// labels
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DomainName(String);
+
+/// The id of a zone, a canonical domain name as accepted by
+/// [`canonicalize_domain`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ZoneId(String);
+
+impl DomainName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this name is `zone` or below it
+    pub fn is_in(&self, zone: &str) -> bool {
+        let zone = absolute_name(zone);
+        self.0 == zone || self.0.ends_with(&format!(".{zone}"))
+    }
+}
+
+impl ZoneId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The name of the zone
+    pub fn to_name(&self) -> DomainName {
+        DomainName(self.0.clone())
+    }
+}
+
+impl TryFrom<&str> for DomainName {
+    type Error = DomainError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        let name = absolute_name(name);
+        let labels = name.strip_suffix('.').unwrap_or_default();
+        if labels.is_empty() {
+            return Err(DomainError::Empty);
+        }
+        if name.len() > MAX_NAME_LENGTH {
+            return Err(DomainError::TooLong);
+        }
+        for (i, label) in labels.split('.').enumerate() {
+            if label.is_empty() {
+                return Err(DomainError::EmptyLabel);
+            }
+            if label.len() > MAX_LABEL_LENGTH {
+                return Err(DomainError::LabelTooLong(label.to_string()));
+            }
+            if let Some(c) = label.chars().find(|c| !is_label_char(*c)) {
+                return Err(DomainError::InvalidCharacter(c));
+            }
+            // a wildcard is only special as the whole leftmost label
+            if label.contains('*') && (i > 0 || label != "*") {
+                return Err(DomainError::InvalidCharacter('*'));
+            }
+        }
+        Ok(DomainName(name))
+    }
+}
+
+impl TryFrom<&str> for ZoneId {
+    type Error = DomainError;
+
+    fn try_from(zone_id: &str) -> Result<Self, Self::Error> {
+        let name = DomainName::try_from(zone_id)?;
+        canonicalize_domain(&name).map(ZoneId)
+    }
+}
+
+/// A zone id passed to a method of [`ZoneClient`](crate::zones::ZoneClient)
+pub trait IntoZoneId {
+    /// The canonical zone id, or [`Error::InvalidDomain`] when it is not a
+    /// valid domain name
+    fn into_zone_id(self) -> Result<ZoneId, Error>;
+}
+
+impl IntoZoneId for ZoneId {
+    fn into_zone_id(self) -> Result<ZoneId, Error> {
+        Ok(self)
+    }
+}
+
+impl IntoZoneId for &ZoneId {
+    fn into_zone_id(self) -> Result<ZoneId, Error> {
+        Ok(self.clone())
+    }
+}
+
+impl IntoZoneId for &str {
+    fn into_zone_id(self) -> Result<ZoneId, Error> {
+        ZoneId::try_from(self).map_err(|source| Error::InvalidDomain {
+            domain: self.to_string(),
+            source,
+        })
+    }
+}
+
+impl IntoZoneId for &String {
+    fn into_zone_id(self) -> Result<ZoneId, Error> {
+        self.as_str().into_zone_id()
+    }
+}
+
+impl IntoZoneId for String {
+    fn into_zone_id(self) -> Result<ZoneId, Error> {
+        self.as_str().into_zone_id()
+    }
+}
+
+macro_rules! impl_name {
+    ($name:ident) => {
+        impl TryFrom<String> for $name {
+            type Error = DomainError;
+
+            fn try_from(name: String) -> Result<Self, Self::Error> {
+                $name::try_from(name.as_str())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = DomainError;
+
+            fn from_str(name: &str) -> Result<Self, Self::Err> {
+                $name::try_from(name)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(name: $name) -> Self {
+                name.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+impl_name!(DomainName);
+impl_name!(ZoneId);
+
+/// Characters allowed in a label: letters, digits and hyphens, underscores
+/// for service names like `_sip._tcp`, `*` for wildcards and `/` for
+/// classless reverse delegations
+fn is_label_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '*' | '/')
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::names::{DomainName, ZoneId};
+    use crate::zones::DomainError;
+
+    #[test]
+    fn canonical_names() {
+        let name = DomainName::try_from("WWW.Example.com").unwrap();
+        assert_eq!(name, "www.example.com.");
+        assert!(name.is_in("example.com"));
+        assert!(!name.is_in("ample.com."));
+
+        assert_eq!(DomainName::try_from("."), Err(DomainError::Empty));
+        assert_eq!(
+            DomainName::try_from("www..example.com"),
+            Err(DomainError::EmptyLabel)
+        );
+        assert_eq!(
+            DomainName::try_from("a b.example.com"),
+            Err(DomainError::InvalidCharacter(' '))
+        );
+        assert!(matches!(
+            DomainName::try_from(format!("{}.com", "a".repeat(64))),
+            Err(DomainError::LabelTooLong(_))
+        ));
+    }
+
+    #[test]
+    fn wildcard_and_service_names() {
+        for name in [
+            "*.example.com.",
+            "_acme-challenge.example.com.",
+            "_sip._tcp.example.com.",
+            "0/25.2.0.192.in-addr.arpa.",
+        ] {
+            assert_eq!(DomainName::try_from(name).unwrap(), name);
+        }
+        assert_eq!(
+            DomainName::try_from("www.*.example.com"),
+            Err(DomainError::InvalidCharacter('*'))
+        );
+        assert_eq!(
+            DomainName::try_from("a*.example.com"),
+            Err(DomainError::InvalidCharacter('*'))
+        );
+    }
+
+    #[test]
+    fn zone_ids() {
+        let zone_id: ZoneId = "Example.COM".parse().unwrap();
+        assert_eq!(zone_id.as_str(), "example.com.");
+        assert_eq!(zone_id.to_name(), "example.com.");
+        assert_eq!(
+            serde_json::from_str::<ZoneId>(r#""example.com""#).unwrap(),
+            zone_id
+        );
+        assert!(serde_json::from_str::<ZoneId>(r#""example..com""#).is_err());
+    }
+
+    #[tokio::test]
+    async fn pass_zone_id_to_client() {
+        use crate::zones::{Zone, ZoneKind};
+
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+
+        let zone_id = ZoneId::try_from("example.com").unwrap();
+        let zone = client.zone().get(&zone_id).await.unwrap();
+        assert_eq!(zone.id.as_deref(), Some(zone_id.as_str()));
+        assert!(client.zone().get_detail(zone_id).await.is_ok());
+
+        // strings are still accepted, and canonicalized by the method
+        let zone = client.zone().get("Example.com").await.unwrap();
+        assert_eq!(zone.id.as_deref(), Some("example.com."));
+        assert!(matches!(
+            client.zone().get("example..com").await,
+            Err(crate::Error::InvalidDomain { .. })
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+//! Observe the changes made through a Client, e.g. to write audit logs or
+//! invalidate downstream caches, without wrapping every call site.
+//!
+//! Observers are registered with
+//! [`ClientBuilder::on_change`](crate::client::ClientBuilder::on_change) and
+//! called after every successful mutation, including the ones made by
+//! helpers like [`ZoneClient::apply`](crate::zones::ZoneClient::apply).
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::record_type::RecordType;
+use crate::zones::Record;
+
+/// A change made through a Client, passed to [`Observer::on_change`]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ChangeEvent {
+    /// The id of the server the change was made on
+    pub server: String,
+    /// The zone that was changed, with a trailing dot
+    pub zone: String,
+    pub kind: ChangeKind,
+}
+
+/// What a [`ChangeEvent`] changed
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    ZoneCreated,
+    /// The settings of the zone were changed, see
+    /// [`ZoneClient::update`](crate::zones::ZoneClient::update)
+    ZoneUpdated,
+    ZoneDeleted,
+    /// An RRSet was created or replaced by a PATCH, one event per RRSet
+    RRSetReplaced {
+        name: String,
+        rtype: RecordType,
+        ttl: u32,
+        records: Vec<Record>,
+    },
+    RRSetDeleted {
+        name: String,
+        rtype: RecordType,
+    },
+}
+
+/// Receives the changes made through a Client. Closures taking a
+/// `&ChangeEvent` implement this trait.
+///
+/// Observers are called synchronously in the task that made the change, and
+/// in the order they were registered, so they should return quickly, e.g. by
+/// sending the event to a channel.
+pub trait Observer: Send + Sync {
+    fn on_change(&self, event: &ChangeEvent);
+}
+
+impl<F> Observer for F
+where
+    F: Fn(&ChangeEvent) + Send + Sync,
+{
+    fn on_change(&self, event: &ChangeEvent) {
+        self(event)
+    }
+}
+
+/// The observers of a Client, in the order they are called
+#[derive(Clone, Default)]
+pub(crate) struct ObserverList(Vec<Arc<dyn Observer>>);
+
+impl ObserverList {
+    pub(crate) fn push(&mut self, observer: Arc<dyn Observer>) {
+        self.0.push(observer);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn notify(&self, event: &ChangeEvent) {
+        self.0.iter().for_each(|observer| observer.on_change(event));
+    }
+}
+
+impl fmt::Debug for ObserverList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObserverList")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::observer::{ChangeEvent, ChangeKind};
+    use crate::record_type::RecordType;
+    use crate::zones::{Record, Zone, ZoneKind, ZoneSettings};
+    use crate::Client;
+
+    #[tokio::test]
+    async fn observe_changes() {
+        let server = crate::mock::MockServer::start();
+        let events: Arc<Mutex<Vec<ChangeEvent>>> = Arc::default();
+        let observed = events.clone();
+        let client = Client::builder()
+            .base_url(server.base_url())
+            .api_key(server.api_key())
+            .on_change(move |event: &ChangeEvent| observed.lock().unwrap().push(event.clone()))
+            .build()
+            .unwrap();
+
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        zones.create(zone.clone()).await.unwrap();
+        assert!(zones.create(zone).await.is_err());
+        zones
+            .replace_rrset(
+                "example.com",
+                "www.example.com.",
+                RecordType::A,
+                300,
+                &["192.0.2.1"],
+            )
+            .await
+            .unwrap();
+        zones
+            .delete_rrset("example.com", "www.example.com.", RecordType::A)
+            .await
+            .unwrap();
+        let settings = ZoneSettings {
+            account: Some(String::from("ops")),
+            ..Default::default()
+        };
+        zones.update("example.com", settings).await.unwrap();
+        zones.delete("example.com").await.unwrap();
+
+        let events = events.lock().unwrap();
+        let kinds: Vec<&ChangeKind> = events.iter().map(|event| &event.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &ChangeKind::ZoneCreated,
+                &ChangeKind::RRSetReplaced {
+                    name: String::from("www.example.com."),
+                    rtype: RecordType::A,
+                    ttl: 300,
+                    records: vec![Record::new("192.0.2.1")],
+                },
+                &ChangeKind::RRSetDeleted {
+                    name: String::from("www.example.com."),
+                    rtype: RecordType::A,
+                },
+                &ChangeKind::ZoneUpdated,
+                &ChangeKind::ZoneDeleted,
+            ]
+        );
+        assert!(events
+            .iter()
+            .all(|event| event.zone == "example.com." && event.server == "localhost"));
+    }
+}
@@ -0,0 +1,342 @@
+//! Measure the API calls made by a Client, e.g. to monitor the automation
+//! using this crate, independently of the statistics of the server.
+//!
+//! An [`OperationRecorder`] registered with
+//! [`ClientBuilder::record_operations`](crate::client::ClientBuilder::record_operations)
+//! receives an [`Operation`] after every call, with the endpoint it was made
+//! to, its status and its latency including retries. [`OperationStats`]
+//! aggregates them per endpoint, and converts them to Prometheus metrics
+//! with the `metrics` feature.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode};
+
+/// An API call made by a Client
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Operation {
+    pub method: Method,
+    /// The path of the call with its ids replaced by placeholders, e.g.
+    /// `/api/v1/servers/{server_id}/zones/{zone_id}`
+    pub endpoint: String,
+    /// The status of the response, None when no response was received
+    pub status: Option<StatusCode>,
+    /// The time until the response headers were received, including retries
+    /// and failover
+    pub duration: Duration,
+}
+
+/// Receives the API calls made by a Client. Closures taking an `&Operation`
+/// implement this trait.
+///
+/// Recorders are called in the task that made the call, so they should
+/// return quickly.
+pub trait OperationRecorder: Send + Sync {
+    fn record(&self, operation: &Operation);
+}
+
+impl<F> OperationRecorder for F
+where
+    F: Fn(&Operation) + Send + Sync,
+{
+    fn record(&self, operation: &Operation) {
+        self(operation)
+    }
+}
+
+/// The operation recorders of a Client, in the order they are called
+#[derive(Clone, Default)]
+pub(crate) struct RecorderList(Vec<Arc<dyn OperationRecorder>>);
+
+impl RecorderList {
+    pub(crate) fn push(&mut self, recorder: Arc<dyn OperationRecorder>) {
+        self.0.push(recorder);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn record(&self, operation: &Operation) {
+        self.0
+            .iter()
+            .for_each(|recorder| recorder.record(operation));
+    }
+}
+
+impl fmt::Debug for RecorderList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecorderList")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// The upper bounds of the buckets of a [`LatencyHistogram`]
+pub const LATENCY_BUCKETS: [Duration; 10] = [
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// The distribution of the latencies of an endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// The number of calls per bucket of [`LATENCY_BUCKETS`], followed by
+    /// the calls slower than the last bucket. Not cumulative.
+    pub buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    pub sum: Duration,
+    pub count: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        LatencyHistogram {
+            buckets: [0; LATENCY_BUCKETS.len() + 1],
+            sum: Duration::ZERO,
+            count: 0,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn observe(&mut self, duration: Duration) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| duration <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket] += 1;
+        self.sum += duration;
+        self.count += 1;
+    }
+}
+
+/// The calls made to one endpoint with one method
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointStats {
+    pub method: Method,
+    /// See [`Operation::endpoint`]
+    pub endpoint: String,
+    /// The number of responses by status code
+    pub responses: BTreeMap<u16, u64>,
+    /// The number of calls without a response
+    pub failures: u64,
+    pub latency: LatencyHistogram,
+}
+
+/// An [`OperationRecorder`] counting the calls, and the distribution of
+/// their latencies, per endpoint
+#[derive(Debug, Default)]
+pub struct OperationStats {
+    endpoints: Mutex<HashMap<(String, Method), EndpointStats>>,
+}
+
+impl OperationStats {
+    pub fn new() -> Self {
+        OperationStats::default()
+    }
+
+    /// The calls recorded so far, ordered by endpoint and method
+    pub fn snapshot(&self) -> Vec<EndpointStats> {
+        let endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        let mut snapshot: Vec<EndpointStats> = endpoints.values().cloned().collect();
+        snapshot.sort_by(|a, b| {
+            (&a.endpoint, a.method.as_str()).cmp(&(&b.endpoint, b.method.as_str()))
+        });
+        snapshot
+    }
+
+    /// Forget the calls recorded so far
+    pub fn reset(&self) {
+        self.endpoints
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+impl OperationRecorder for OperationStats {
+    fn record(&self, operation: &Operation) {
+        let mut endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = endpoints
+            .entry((operation.endpoint.clone(), operation.method.clone()))
+            .or_insert_with(|| EndpointStats {
+                method: operation.method.clone(),
+                endpoint: operation.endpoint.clone(),
+                responses: BTreeMap::new(),
+                failures: 0,
+                latency: LatencyHistogram::default(),
+            });
+        match operation.status {
+            Some(status) => *stats.responses.entry(status.as_u16()).or_default() += 1,
+            None => stats.failures += 1,
+        }
+        stats.latency.observe(operation.duration);
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl OperationStats {
+    /// The calls as the counter `powerdns_client_requests_total` and the
+    /// histogram `powerdns_client_request_duration_seconds`, labelled with
+    /// the endpoint, method and, for the counter, the status. Calls without
+    /// a response have the status `error`.
+    pub fn metrics(&self) -> Vec<crate::metrics::Metric> {
+        use crate::metrics::{Metric, MetricKind};
+
+        let mut requests = Vec::new();
+        let mut durations = Vec::new();
+        for stats in self.snapshot() {
+            let labels = vec![
+                (String::from("endpoint"), stats.endpoint.clone()),
+                (String::from("method"), stats.method.to_string()),
+            ];
+            let statuses = stats
+                .responses
+                .iter()
+                .map(|(status, count)| (status.to_string(), *count))
+                .chain((stats.failures > 0).then(|| (String::from("error"), stats.failures)));
+            for (status, count) in statuses {
+                let mut labels = labels.clone();
+                labels.push((String::from("status"), status));
+                requests.push(Metric {
+                    name: String::from("powerdns_client_requests_total"),
+                    kind: MetricKind::Counter,
+                    labels,
+                    value: count as f64,
+                });
+            }
+
+            let histogram = |suffix: &str, labels: Vec<(String, String)>, value: f64| Metric {
+                name: format!("powerdns_client_request_duration_seconds{suffix}"),
+                kind: MetricKind::Histogram,
+                labels,
+                value,
+            };
+            let mut cumulative = 0;
+            let bounds = LATENCY_BUCKETS
+                .iter()
+                .map(|bound| bound.as_secs_f64().to_string())
+                .chain(std::iter::once(String::from("+Inf")));
+            for (bound, count) in bounds.zip(stats.latency.buckets) {
+                cumulative += count;
+                let mut labels = labels.clone();
+                labels.push((String::from("le"), bound));
+                durations.push(histogram("_bucket", labels, cumulative as f64));
+            }
+            let sum = stats.latency.sum.as_secs_f64();
+            durations.push(histogram("_sum", labels.clone(), sum));
+            durations.push(histogram("_count", labels, stats.latency.count as f64));
+        }
+        requests.extend(durations);
+        requests
+    }
+}
+
+/// The path of a call with the ids following collections replaced by
+/// placeholders, so calls to different zones count towards one endpoint
+pub(crate) fn endpoint(path: &str) -> String {
+    let mut endpoint = String::new();
+    let mut placeholder = None;
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        endpoint.push('/');
+        match placeholder.take() {
+            Some(placeholder) => endpoint.push_str(placeholder),
+            None => endpoint.push_str(segment),
+        }
+        placeholder = match segment {
+            "servers" => Some("{server_id}"),
+            "zones" => Some("{zone_id}"),
+            "cryptokeys" => Some("{cryptokey_id}"),
+            "metadata" => Some("{metadata_kind}"),
+            "tsigkeys" => Some("{tsigkey_id}"),
+            "config" => Some("{config_name}"),
+            _ => None,
+        };
+    }
+    endpoint
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use reqwest::Method;
+
+    use crate::operations::{endpoint, LatencyHistogram, OperationStats};
+    use crate::Client;
+
+    #[test]
+    fn endpoint_of_path() {
+        assert_eq!(
+            endpoint("/api/v1/servers/localhost/zones/example.com./cryptokeys/3"),
+            "/api/v1/servers/{server_id}/zones/{zone_id}/cryptokeys/{cryptokey_id}"
+        );
+        assert_eq!(
+            endpoint("/api/v1/servers/localhost/zones"),
+            "/api/v1/servers/{server_id}/zones"
+        );
+        assert_eq!(endpoint("/api"), "/api");
+    }
+
+    #[test]
+    fn observe_latency() {
+        let mut histogram = LatencyHistogram::default();
+        histogram.observe(Duration::from_millis(5));
+        histogram.observe(Duration::from_millis(6));
+        histogram.observe(Duration::from_secs(60));
+        assert_eq!(histogram.buckets[0], 1);
+        assert_eq!(histogram.buckets[1], 1);
+        assert_eq!(histogram.buckets[10], 1);
+        assert_eq!(histogram.count, 3);
+    }
+
+    #[tokio::test]
+    async fn record_operations() {
+        let server = crate::mock::MockServer::start();
+        let stats = Arc::new(OperationStats::new());
+        let client = Client::builder()
+            .base_url(server.base_url())
+            .api_key(server.api_key())
+            .record_operations_arc(stats.clone())
+            .build()
+            .unwrap();
+
+        client.zone().list().await.unwrap();
+        client.zone().list().await.unwrap();
+        assert!(client.zone().get("example.com").await.is_err());
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].endpoint, "/api/v1/servers/{server_id}/zones");
+        assert_eq!(snapshot[0].method, Method::GET);
+        assert_eq!(snapshot[0].responses[&200], 2);
+        assert_eq!(snapshot[0].latency.count, 2);
+        assert_eq!(snapshot[1].responses[&404], 1);
+
+        #[cfg(feature = "metrics")]
+        {
+            let text = crate::metrics::encode_text(&stats.metrics());
+            assert!(text.contains(
+                "powerdns_client_requests_total{endpoint=\"/api/v1/servers/{server_id}/zones\",method=\"GET\",status=\"200\"} 2\n"
+            ));
+            assert!(text.contains("# TYPE powerdns_client_request_duration_seconds histogram\n"));
+            assert!(text.contains("le=\"+Inf\"} 2\n"));
+        }
+
+        stats.reset();
+        assert!(stats.snapshot().is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+//! Propagate W3C trace context, so the API calls of a Client show up in the
+//! distributed traces of the service making them.
+//!
+//! Every API call is a client span: it gets a new span id, is sent with a
+//! `traceparent` header and, as this feature enables `tracing`, has its
+//! `trace_id`, `span_id` and `parent_id` recorded on the `powerdns_request`
+//! span. The parent of the calls is set with [`scope`], e.g. from the
+//! `traceparent` header of the request being served; without one, each call
+//! starts a new trace.
+//!
+//! ```no_run
+//! # async fn example(client: powerdns::Client, traceparent: &str) {
+//! use powerdns::otel::{self, TraceContext};
+//!
+//! let parent = TraceContext::from_traceparent(traceparent).unwrap_or_else(TraceContext::new_root);
+//! let zones = otel::scope(parent, client.zone().list()).await;
+//! # }
+//! ```
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+
+use reqwest::header::{HeaderName, HeaderValue};
+use tracing::Span;
+
+tokio::task_local! {
+    static CURRENT: TraceContext;
+}
+
+/// The name of the header the context is sent in
+pub const TRACEPARENT: &str = "traceparent";
+
+/// The position of a span in a trace, as sent in a `traceparent` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    trace_id: u128,
+    span_id: u64,
+    sampled: bool,
+}
+
+impl TraceContext {
+    /// The first span of a new, sampled trace
+    pub fn new_root() -> Self {
+        let trace_id = (u128::from(random_id()) << 64) | u128::from(random_id());
+        TraceContext {
+            trace_id,
+            span_id: random_id(),
+            sampled: true,
+        }
+    }
+
+    /// Parse a `traceparent` header of version 00, e.g.
+    /// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`. Returns
+    /// None when it is invalid, in which case a new trace should be started.
+    pub fn from_traceparent(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let (Some("00"), Some(trace_id), Some(span_id), Some(flags), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return None;
+        };
+        // from_str_radix also accepts uppercase digits and a leading sign
+        fn hex(value: &str, len: usize) -> Option<&str> {
+            let digits = value
+                .bytes()
+                .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+            (value.len() == len && digits).then_some(value)
+        }
+        let trace_id = u128::from_str_radix(hex(trace_id, 32)?, 16).ok()?;
+        let span_id = u64::from_str_radix(hex(span_id, 16)?, 16).ok()?;
+        let flags = u8::from_str_radix(hex(flags, 2)?, 16).ok()?;
+        if trace_id == 0 || span_id == 0 {
+            return None;
+        }
+        Some(TraceContext {
+            trace_id,
+            span_id,
+            sampled: flags & 1 == 1,
+        })
+    }
+
+    /// A new span in the same trace, with this span as its parent
+    pub fn child(&self) -> Self {
+        TraceContext {
+            span_id: random_id(),
+            ..*self
+        }
+    }
+
+    /// The context set by the [`scope`] the current task runs in
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|context| *context).ok()
+    }
+
+    /// The id of the trace, as 32 lowercase hex digits
+    pub fn trace_id(&self) -> String {
+        format!("{:032x}", self.trace_id)
+    }
+
+    /// The id of the span, as 16 lowercase hex digits
+    pub fn span_id(&self) -> String {
+        format!("{:016x}", self.span_id)
+    }
+
+    /// Whether the trace is recorded by the tracing backend
+    pub fn is_sampled(&self) -> bool {
+        self.sampled
+    }
+
+    /// The value of a `traceparent` header for this context
+    pub fn traceparent(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id,
+            self.span_id,
+            u8::from(self.sampled)
+        )
+    }
+}
+
+/// Run `future` with `context` as the parent of the API calls made in it
+pub async fn scope<F: Future>(context: TraceContext, future: F) -> F::Output {
+    CURRENT.scope(context, future).await
+}
+
+/// Add the `traceparent` header of a new client span to `request`, and
+/// record its ids on `span`
+pub(crate) fn propagate(request: &mut reqwest::Request, span: &Span) {
+    let parent = TraceContext::current();
+    let context = parent.map_or_else(TraceContext::new_root, |parent| parent.child());
+    if let Ok(value) = HeaderValue::from_str(&context.traceparent()) {
+        request
+            .headers_mut()
+            .insert(HeaderName::from_static(TRACEPARENT), value);
+    }
+    span.record("trace_id", context.trace_id());
+    span.record("span_id", context.span_id());
+    if let Some(parent) = parent {
+        span.record("parent_id", parent.span_id());
+    }
+}
+
+/// A random, non-zero id
+fn random_id() -> u64 {
+    loop {
+        let id = RandomState::new().build_hasher().finish();
+        if id != 0 {
+            return id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use reqwest::Request;
+
+    use crate::middleware::Middleware;
+    use crate::otel::{scope, TraceContext};
+    use crate::{Client, Error};
+
+    #[test]
+    fn parse_traceparent() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = TraceContext::from_traceparent(header).unwrap();
+        assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.span_id(), "00f067aa0ba902b7");
+        assert!(context.is_sampled());
+        assert_eq!(context.traceparent(), header);
+
+        let child = context.child();
+        assert_eq!(child.trace_id(), context.trace_id());
+        assert_ne!(child.span_id(), context.span_id());
+
+        for invalid in [
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-+0f067aa0ba902b7-01",
+        ] {
+            assert_eq!(TraceContext::from_traceparent(invalid), None, "{invalid}");
+        }
+    }
+
+    #[derive(Default)]
+    struct Headers(Mutex<Vec<String>>);
+
+    impl Middleware for Headers {
+        fn on_request(&self, request: &mut Request) -> Result<(), Error> {
+            let traceparent = request.headers()["traceparent"].to_str().unwrap();
+            self.0.lock().unwrap().push(traceparent.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn send_traceparent() {
+        let server = crate::mock::MockServer::start();
+        let headers = Arc::new(Headers::default());
+        let client = Client::builder()
+            .base_url(server.base_url())
+            .api_key(server.api_key())
+            .middleware_arc(headers.clone())
+            .build()
+            .unwrap();
+
+        let parent = TraceContext::new_root();
+        scope(parent, client.zone().list()).await.unwrap();
+        client.zone().list().await.unwrap();
+
+        let headers = headers.0.lock().unwrap();
+        let first = TraceContext::from_traceparent(&headers[0]).unwrap();
+        assert_eq!(first.trace_id(), parent.trace_id());
+        assert_ne!(first.span_id(), parent.span_id());
+        let second = TraceContext::from_traceparent(&headers[1]).unwrap();
+        assert_ne!(second.trace_id(), parent.trace_id());
+    }
+}
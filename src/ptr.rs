@@ -0,0 +1,223 @@
+//! Keep PTR records in reverse zones in sync with A and AAAA records.
+
+use std::fmt::Write;
+use std::net::IpAddr;
+
+use crate::names::IntoZoneId;
+use crate::record_type::RecordType;
+#[allow(deprecated)]
+use crate::zones::{absolute_name, RRSet, Record, Zone, ZoneClient, ZoneKind};
+use crate::Error;
+
+/// Options for [`ZoneClient::upsert_address`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PtrOptions {
+    /// Create a Native reverse zone when there is none for the address yet,
+    /// covering the /24 of IPv4 and the /64 of IPv6 addresses
+    pub create_reverse_zone: bool,
+}
+
+/// The name of the PTR record for an address in `in-addr.arpa.` or
+/// `ip6.arpa.`, e.g. `1.2.0.192.in-addr.arpa.` for 192.0.2.1
+pub fn reverse_name(address: IpAddr) -> String {
+    match address {
+        IpAddr::V4(address) => {
+            let [a, b, c, d] = address.octets();
+            format!("{d}.{c}.{b}.{a}.in-addr.arpa.")
+        }
+        IpAddr::V6(address) => {
+            let mut name = String::with_capacity(72);
+            for byte in address.octets().iter().rev() {
+                // writing to a String cannot fail
+                let _ = write!(name, "{:x}.{:x}.", byte & 0xf, byte >> 4);
+            }
+            name + "ip6.arpa."
+        }
+    }
+}
+
+/// The reverse zone created for an address, see [`PtrOptions`]
+fn default_reverse_zone(address: IpAddr) -> String {
+    let name = reverse_name(address);
+    let labels = match address {
+        IpAddr::V4(_) => 1,
+        IpAddr::V6(_) => 16,
+    };
+    name.splitn(labels + 1, '.')
+        .last()
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[allow(deprecated)]
+impl ZoneClient<'_> {
+    /// Add an A or AAAA record for `address` to the RRSet `name`, like
+    /// [`ZoneClient::upsert_record`], and point the PTR record of the address
+    /// to `name`. The PTR record is stored in the most specific reverse zone
+    /// on the server.
+    ///
+    /// Fails with [`Error::NoReverseZone`] before changing anything when
+    /// there is no reverse zone and `options` do not allow creating one.
+    pub async fn upsert_address(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        address: IpAddr,
+        ttl: u32,
+        options: &PtrOptions,
+    ) -> Result<(), Error> {
+        let ptr_name = reverse_name(address);
+        let reverse_zone = match self.find_zone(&ptr_name).await? {
+            Some(reverse_zone) => reverse_zone,
+            None if options.create_reverse_zone => {
+                let reverse_zone = Zone {
+                    name: Some(default_reverse_zone(address)),
+                    kind: Some(ZoneKind::Native),
+                    nameservers: Some(Vec::new()),
+                    ..Default::default()
+                };
+                let reverse_zone = self.create(reverse_zone).await?;
+                reverse_zone.id.or(reverse_zone.name).unwrap_or_default()
+            }
+            None => return Err(Error::NoReverseZone(address)),
+        };
+
+        let name = absolute_name(name);
+        self.upsert_record(
+            zone_id,
+            &name,
+            address_type(address),
+            ttl,
+            &address.to_string(),
+        )
+        .await?;
+
+        let record = Record {
+            content: name,
+            disabled: Some(false),
+        };
+        let ptr = RRSet::new(&ptr_name, RecordType::PTR, ttl, vec![record]);
+        self.ensure_rrset(&reverse_zone, ptr).await?;
+        Ok(())
+    }
+
+    /// Remove the A or AAAA record for `address` from the RRSet `name`, like
+    /// [`ZoneClient::remove_record`], and delete the PTR record of the
+    /// address if it points to `name`. Returns whether the A or AAAA record
+    /// was removed.
+    pub async fn remove_address(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        address: IpAddr,
+    ) -> Result<bool, Error> {
+        let name = absolute_name(name);
+        let removed = self
+            .remove_record(zone_id, &name, address_type(address), &address.to_string())
+            .await?;
+
+        let ptr_name = reverse_name(address);
+        if let Some(reverse_zone) = self.find_zone(&ptr_name).await? {
+            self.remove_record(&reverse_zone, &ptr_name, RecordType::PTR, &name)
+                .await?;
+        }
+        Ok(removed)
+    }
+
+    /// The id of the most specific zone on the server containing `name`
+    pub(crate) async fn find_zone(&self, name: &str) -> Result<Option<String>, Error> {
+        let zones = self.list().await?;
+        Ok(zones
+            .into_iter()
+            .filter_map(|zone| Some((zone.name?, zone.id?)))
+            .filter(|(zone, _)| name == zone || name.ends_with(&format!(".{zone}")))
+            .max_by_key(|(zone, _)| zone.len())
+            .map(|(_, id)| id))
+    }
+}
+
+fn address_type(address: IpAddr) -> RecordType {
+    match address {
+        IpAddr::V4(_) => RecordType::A,
+        IpAddr::V6(_) => RecordType::AAAA,
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use std::net::IpAddr;
+
+    use crate::ptr::{default_reverse_zone, reverse_name, PtrOptions};
+    use crate::record_type::RecordType;
+    use crate::zones::{Zone, ZoneKind};
+    use crate::Error;
+
+    #[test]
+    fn reverse_names() {
+        let v4: IpAddr = "192.0.2.1".parse().unwrap();
+        assert_eq!(reverse_name(v4), "1.2.0.192.in-addr.arpa.");
+        assert_eq!(default_reverse_zone(v4), "2.0.192.in-addr.arpa.");
+
+        let v6: IpAddr = "2001:db8::567:89ab".parse().unwrap();
+        assert_eq!(
+            reverse_name(v6),
+            "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."
+        );
+        assert_eq!(
+            default_reverse_zone(v6),
+            "0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."
+        );
+    }
+
+    #[tokio::test]
+    async fn upsert_and_remove_address() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        zones.create(zone).await.unwrap();
+        let address: IpAddr = "192.0.2.1".parse().unwrap();
+
+        let result = zones
+            .upsert_address(
+                "example.com",
+                "www.example.com",
+                address,
+                300,
+                &PtrOptions::default(),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::NoReverseZone(_))));
+        assert_eq!(server.zone("example.com.").unwrap().rrsets, Some(vec![]));
+
+        let options = PtrOptions {
+            create_reverse_zone: true,
+        };
+        zones
+            .upsert_address("example.com", "www.example.com", address, 300, &options)
+            .await
+            .unwrap();
+        let reverse = server
+            .zone("2.0.192.in-addr.arpa.")
+            .unwrap()
+            .rrsets
+            .unwrap();
+        assert_eq!(reverse[0].name, "1.2.0.192.in-addr.arpa.");
+        assert_eq!(reverse[0].type_field, RecordType::PTR);
+        assert_eq!(reverse[0].records[0].content, "www.example.com.");
+
+        assert!(zones
+            .remove_address("example.com", "www.example.com", address)
+            .await
+            .unwrap());
+        assert_eq!(
+            server.zone("2.0.192.in-addr.arpa.").unwrap().rrsets,
+            Some(vec![])
+        );
+        assert_eq!(server.zone("example.com.").unwrap().rrsets, Some(vec![]));
+    }
+}
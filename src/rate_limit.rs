@@ -0,0 +1,85 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// Limits the rate and concurrency of requests made by a Client, so bulk
+/// operations do not exhaust the connection limit of the PowerDNS webserver.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    interval: Option<Duration>,
+    next_slot: Mutex<Option<Instant>>,
+    concurrency: Option<Semaphore>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_second: Option<f64>, max_concurrent: Option<usize>) -> Self {
+        RateLimiter {
+            interval: requests_per_second
+                .filter(|rps| *rps > 0.0)
+                .map(|rps| Duration::from_secs_f64(1.0 / rps)),
+            next_slot: Mutex::new(None),
+            concurrency: max_concurrent.map(|max| Semaphore::new(max.max(1))),
+        }
+    }
+
+    /// Wait until a request may be sent, blocking the thread when `blocking`
+    /// as there is no tokio timer then. The returned permit must be held
+    /// while the request is in flight.
+    pub(crate) async fn acquire(&self, blocking: bool) -> Option<SemaphorePermit<'_>> {
+        let permit = match &self.concurrency {
+            // the semaphore is never closed
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        };
+
+        if let Some(interval) = self.interval {
+            let slot = {
+                let mut next_slot = self.next_slot.lock().unwrap();
+                let now = Instant::now();
+                let slot = next_slot.map_or(now, |next| next.max(now));
+                *next_slot = Some(slot + interval);
+                slot
+            };
+            if blocking {
+                std::thread::sleep(slot.saturating_duration_since(Instant::now()));
+            } else {
+                tokio::time::sleep_until(slot).await;
+            }
+        }
+
+        permit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use crate::rate_limit::RateLimiter;
+
+    #[tokio::test]
+    async fn spaces_requests() {
+        let limiter = RateLimiter::new(Some(100.0), None);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire(false).await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn caps_concurrency() {
+        let limiter = RateLimiter::new(None, Some(1));
+        let permit = limiter.acquire(false).await;
+        assert!(permit.is_some());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), limiter.acquire(false))
+                .await
+                .is_err()
+        );
+    }
+}
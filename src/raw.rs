@@ -0,0 +1,120 @@
+//! Requests to endpoints not modelled by this crate, e.g. ones added by new
+//! PowerDNS versions, sent with the options of a [`Client`].
+
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::decode;
+use crate::{Client, Error};
+
+impl Client {
+    /// Send a GET request to `path` below the API URL, e.g.
+    /// `/servers/localhost/config`, and decode the JSON response.
+    ///
+    /// Error responses are turned into an [`Error`] like those of the other
+    /// methods. An empty response is decoded as `null`, so `()` or an
+    /// `Option` can be used for endpoints without a response body.
+    pub async fn raw_get<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        self.raw(Method::GET, path, None::<&()>).await
+    }
+
+    /// Send a POST request with a JSON body, see [`Client::raw_get`]
+    pub async fn raw_post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, Error> {
+        self.raw(Method::POST, path, Some(body)).await
+    }
+
+    /// Send a PUT request with a JSON body, see [`Client::raw_get`]
+    pub async fn raw_put<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, Error> {
+        self.raw(Method::PUT, path, Some(body)).await
+    }
+
+    /// Send a PATCH request with a JSON body, see [`Client::raw_get`]
+    pub async fn raw_patch<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, Error> {
+        self.raw(Method::PATCH, path, Some(body)).await
+    }
+
+    /// Send a DELETE request, see [`Client::raw_get`]
+    pub async fn raw_delete<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        self.raw(Method::DELETE, path, None::<&()>).await
+    }
+
+    async fn raw<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&impl Serialize>,
+    ) -> Result<T, Error> {
+        let url = format!("{}/{}", self.api_url, path.trim_start_matches('/'));
+        let mut request = self.request(method, url);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send().await?;
+
+        if response.status().is_success() {
+            decode(response).await
+        } else {
+            Err(Error::from_response(response).await)
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::zones::{Zone, ZoneKind};
+    use crate::Error;
+
+    #[tokio::test]
+    async fn raw_requests() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        let created: Value = client
+            .raw_post("/servers/localhost/zones", &zone)
+            .await
+            .unwrap();
+        assert_eq!(created["id"], "example.com.");
+
+        let zones: Vec<Value> = client.raw_get("servers/localhost/zones").await.unwrap();
+        assert_eq!(zones.len(), 1);
+
+        let settings = json!({"account": "alice"});
+        let () = client
+            .raw_put("/servers/localhost/zones/example.com.", &settings)
+            .await
+            .unwrap();
+        assert_eq!(
+            server.zone("example.com.").unwrap().account.as_deref(),
+            Some("alice")
+        );
+
+        let () = client
+            .raw_delete("/servers/localhost/zones/example.com.")
+            .await
+            .unwrap();
+        let result = client
+            .raw_get::<Value>("/servers/localhost/zones/example.com.")
+            .await;
+        assert!(matches!(result, Err(Error::NotFound { .. })));
+    }
+}
@@ -0,0 +1,600 @@
+use std::fmt::{self, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::record_type::RecordType;
+#[allow(deprecated)]
+use crate::zones::{absolute_name, Record, Zone};
+
+/// The typed data of a single record, parsed from `Record::content`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RData {
+    A(Ipv4Addr),
+    AAAA(Ipv6Addr),
+    CNAME(String),
+    NS(String),
+    PTR(String),
+    MX {
+        preference: u16,
+        exchange: String,
+    },
+    SRV {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: String,
+    },
+    /// The character strings making up the record, without quotes or escapes.
+    /// Strings longer than [`MAX_CHARACTER_STRING_LEN`] are split when
+    /// formatted, as with [`Txt::from_value`].
+    TXT(Vec<String>),
+    CAA {
+        flags: u8,
+        tag: String,
+        value: String,
+    },
+    /// A PowerDNS LUA record, whose `code` is evaluated on every query to
+    /// compute records of type `rtype`
+    LUA {
+        rtype: RecordType,
+        /// The code without quotes or escapes
+        code: String,
+    },
+    /// A PowerDNS ALIAS record, answered with the addresses of its target
+    ALIAS(String),
+    /// The unparsed content of a record type without typed support
+    Other(String),
+}
+
+/// Returned when record content does not match the format of its type
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid {rtype} record content: {content:?}")]
+pub struct RDataError {
+    /// The type the content was parsed as
+    pub rtype: RecordType,
+    /// The offending content
+    pub content: String,
+}
+
+impl RData {
+    /// Parse record `content` in the format PowerDNS uses for `rtype`
+    pub fn parse(rtype: &RecordType, content: &str) -> Result<RData, RDataError> {
+        let error = || RDataError {
+            rtype: rtype.clone(),
+            content: content.to_string(),
+        };
+        let fields: Vec<&str> = content.split_whitespace().collect();
+
+        let rdata = match (rtype, fields.as_slice()) {
+            (RecordType::A, [address]) => RData::A(address.parse().map_err(|_| error())?),
+            (RecordType::AAAA, [address]) => RData::AAAA(address.parse().map_err(|_| error())?),
+            (RecordType::CNAME, [target]) => RData::CNAME(target.to_string()),
+            (RecordType::NS, [target]) => RData::NS(target.to_string()),
+            (RecordType::PTR, [target]) => RData::PTR(target.to_string()),
+            (RecordType::MX, [preference, exchange]) => RData::MX {
+                preference: preference.parse().map_err(|_| error())?,
+                exchange: exchange.to_string(),
+            },
+            (RecordType::SRV, [priority, weight, port, target]) => RData::SRV {
+                priority: priority.parse().map_err(|_| error())?,
+                weight: weight.parse().map_err(|_| error())?,
+                port: port.parse().map_err(|_| error())?,
+                target: target.to_string(),
+            },
+            (RecordType::TXT | RecordType::SPF, _) => {
+                RData::TXT(parse_character_strings(content).ok_or_else(error)?)
+            }
+            (RecordType::CAA, [flags, tag, ..]) => {
+                let value = content
+                    .trim_start()
+                    .split_once(char::is_whitespace)
+                    .and_then(|(_, rest)| rest.trim_start().split_once(char::is_whitespace))
+                    .map(|(_, value)| value)
+                    .ok_or_else(error)?;
+                let value = match parse_character_strings(value).as_deref() {
+                    Some([value]) => value.clone(),
+                    _ => return Err(error()),
+                };
+                RData::CAA {
+                    flags: flags.parse().map_err(|_| error())?,
+                    tag: tag.to_string(),
+                    value,
+                }
+            }
+            (RecordType::LUA, [rtype, ..]) => {
+                let code = content.trim_start()[rtype.len()..].trim_start();
+                let rtype = RecordType::from(*rtype);
+                if !code.starts_with('"') || rtype == RecordType::LUA {
+                    return Err(error());
+                }
+                RData::LUA {
+                    rtype,
+                    code: parse_character_strings(code).ok_or_else(error)?.concat(),
+                }
+            }
+            (RecordType::ALIAS, [target]) => RData::ALIAS(target.to_string()),
+            (
+                RecordType::A
+                | RecordType::AAAA
+                | RecordType::CNAME
+                | RecordType::NS
+                | RecordType::PTR
+                | RecordType::MX
+                | RecordType::SRV
+                | RecordType::CAA
+                | RecordType::LUA
+                | RecordType::ALIAS,
+                _,
+            ) => return Err(error()),
+            (_, _) => RData::Other(content.to_string()),
+        };
+
+        Ok(rdata)
+    }
+
+    /// Format the data as record content in the format PowerDNS expects
+    pub fn to_content(&self) -> String {
+        match self {
+            RData::A(address) => address.to_string(),
+            RData::AAAA(address) => address.to_string(),
+            RData::CNAME(target)
+            | RData::NS(target)
+            | RData::PTR(target)
+            | RData::ALIAS(target) => target.clone(),
+            RData::MX {
+                preference,
+                exchange,
+            } => format!("{preference} {exchange}"),
+            RData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => format!("{priority} {weight} {port} {target}"),
+            RData::TXT(strings) => strings
+                .iter()
+                .map(|s| Txt::from_value(s).to_content())
+                .collect::<Vec<_>>()
+                .join(" "),
+            RData::CAA { flags, tag, value } => {
+                format!("{flags} {tag} {}", quote_character_string(value))
+            }
+            RData::LUA { rtype, code } => format!("{rtype} {}", Txt::from_value(code)),
+            RData::Other(content) => content.clone(),
+        }
+    }
+
+    /// A LUA record computing records of type `rtype` with the expression
+    /// `code`, e.g. `ifportup(443, {'192.0.2.1', '192.0.2.2'})`. The code is
+    /// quoted and escaped when formatted.
+    pub fn lua(rtype: RecordType, code: &str) -> RData {
+        RData::LUA {
+            rtype,
+            code: code.to_string(),
+        }
+    }
+
+    /// A LUA record computing records of type `rtype` with a script of
+    /// statements, which must `return` the records. PowerDNS tells scripts
+    /// from expressions by a leading semicolon, which is added.
+    pub fn lua_script(rtype: RecordType, script: &str) -> RData {
+        RData::LUA {
+            rtype,
+            code: format!(";{}", script.trim_start_matches(';')),
+        }
+    }
+
+    /// An ALIAS record pointing to `target`, e.g. at the apex of a zone
+    /// where a CNAME is not allowed
+    pub fn alias(target: &str) -> RData {
+        RData::ALIAS(absolute_name(target))
+    }
+}
+
+impl Record {
+    /// Parse the content of this record as `rtype`
+    pub fn parse(&self, rtype: &RecordType) -> Result<RData, RDataError> {
+        RData::parse(rtype, &self.content)
+    }
+}
+
+/// The content of an SOA record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Soa {
+    /// The primary nameserver of the zone
+    pub mname: String,
+    /// The mailbox of the person responsible for the zone, with the `@`
+    /// replaced by a dot
+    pub rname: String,
+    pub serial: u32,
+    /// Seconds between refreshes of secondaries
+    pub refresh: u32,
+    /// Seconds before a failed refresh is retried
+    pub retry: u32,
+    /// Seconds after which secondaries stop answering without a refresh
+    pub expire: u32,
+    /// The TTL of negative responses, in seconds
+    pub minimum: u32,
+}
+
+impl FromStr for Soa {
+    type Err = RDataError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        let error = || RDataError {
+            rtype: RecordType::SOA,
+            content: content.to_string(),
+        };
+        let fields: Vec<&str> = content.split_whitespace().collect();
+        let [mname, rname, numbers @ ..] = fields.as_slice() else {
+            return Err(error());
+        };
+        let numbers = numbers
+            .iter()
+            .map(|number| number.parse())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| error())?;
+        let [serial, refresh, retry, expire, minimum] = numbers[..] else {
+            return Err(error());
+        };
+
+        Ok(Soa {
+            mname: mname.to_string(),
+            rname: rname.to_string(),
+            serial,
+            refresh,
+            retry,
+            expire,
+            minimum,
+        })
+    }
+}
+
+impl fmt::Display for Soa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {}",
+            self.mname,
+            self.rname,
+            self.serial,
+            self.refresh,
+            self.retry,
+            self.expire,
+            self.minimum
+        )
+    }
+}
+
+/// The longest character string of a TXT record, in bytes
+pub const MAX_CHARACTER_STRING_LEN: usize = 255;
+
+/// The content of a TXT or SPF record, as the character strings it is made
+/// of. Values longer than a single string, like DKIM keys, are split over
+/// several strings, which resolvers join back together.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Txt {
+    strings: Vec<String>,
+}
+
+impl Txt {
+    /// Split `value` into strings of at most [`MAX_CHARACTER_STRING_LEN`]
+    /// bytes, without splitting multi-byte characters
+    pub fn from_value(value: &str) -> Txt {
+        let mut strings = Vec::new();
+        let mut rest = value;
+        while rest.len() > MAX_CHARACTER_STRING_LEN {
+            let mut end = MAX_CHARACTER_STRING_LEN;
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            let (string, tail) = rest.split_at(end);
+            strings.push(string.to_string());
+            rest = tail;
+        }
+        if !rest.is_empty() || strings.is_empty() {
+            strings.push(rest.to_string());
+        }
+        Txt { strings }
+    }
+
+    /// The record from its character strings, which are not split further.
+    /// Returns None when one of them is longer than
+    /// [`MAX_CHARACTER_STRING_LEN`].
+    pub fn from_strings<S: Into<String>>(strings: impl IntoIterator<Item = S>) -> Option<Txt> {
+        let strings: Vec<String> = strings.into_iter().map(Into::into).collect();
+        strings
+            .iter()
+            .all(|string| string.len() <= MAX_CHARACTER_STRING_LEN)
+            .then_some(Txt { strings })
+    }
+
+    /// The character strings of the record, without quotes or escapes
+    pub fn strings(&self) -> &[String] {
+        &self.strings
+    }
+
+    /// The strings joined together, as they are interpreted by e.g. DKIM
+    /// and SPF verifiers
+    pub fn value(&self) -> String {
+        self.strings.concat()
+    }
+
+    /// Format the record as content in the format PowerDNS expects, with
+    /// every string quoted and escaped
+    pub fn to_content(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl FromStr for Txt {
+    type Err = RDataError;
+
+    /// Parse record content made of quoted strings, rejecting strings that
+    /// are longer than resolvers accept
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        parse_character_strings(content)
+            .and_then(Txt::from_strings)
+            .ok_or_else(|| RDataError {
+                rtype: RecordType::TXT,
+                content: content.to_string(),
+            })
+    }
+}
+
+impl fmt::Display for Txt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, string) in self.strings.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            f.write_str(&quote_character_string(string))?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Txt> for RData {
+    fn from(txt: Txt) -> Self {
+        RData::TXT(txt.strings)
+    }
+}
+
+#[allow(deprecated)]
+impl Zone {
+    /// Parse the SOA record of the zone. Returns None if the zone has no SOA
+    /// RRSet, e.g. because it was listed without rrsets.
+    pub fn soa(&self) -> Result<Option<Soa>, RDataError> {
+        let record = self
+            .rrsets
+            .iter()
+            .flatten()
+            .filter(|rrset| rrset.type_field == RecordType::SOA)
+            .find_map(|rrset| rrset.records.first());
+        record.map(|record| record.content.parse()).transpose()
+    }
+}
+
+/// Parse a sequence of quoted character strings (e.g. `"foo" "bar\"baz"`),
+/// resolving escapes. Unquoted strings are accepted as single words.
+pub(crate) fn parse_character_strings(content: &str) -> Option<Vec<String>> {
+    let mut strings = Vec::new();
+    let mut chars = content.trim().chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let quoted = c == '"';
+        if quoted {
+            chars.next();
+        }
+
+        let mut bytes = Vec::new();
+        let mut closed = !quoted;
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted => {
+                    closed = true;
+                    break;
+                }
+                c if c.is_whitespace() && !quoted => break,
+                '\\' => match chars.next()? {
+                    d if d.is_ascii_digit() => {
+                        let mut value = d.to_digit(10)?;
+                        for _ in 0..2 {
+                            value = value * 10 + chars.next()?.to_digit(10)?;
+                        }
+                        bytes.push(u8::try_from(value).ok()?);
+                    }
+                    c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                },
+                c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            }
+        }
+
+        if !closed {
+            return None;
+        }
+        strings.push(String::from_utf8(bytes).ok()?);
+    }
+
+    Some(strings)
+}
+
+/// Quote a character string, escaping quotes, backslashes and non-printable
+/// characters.
+pub(crate) fn quote_character_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b'\\' => {
+                quoted.push('\\');
+                quoted.push(byte as char);
+            }
+            0x20..=0x7e => quoted.push(byte as char),
+            _ => {
+                let _ = write!(quoted, "\\{byte:03}");
+            }
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use crate::rdata::{RData, Soa, Txt, MAX_CHARACTER_STRING_LEN};
+    use crate::record_type::RecordType;
+    use crate::zones::{RRSet, Record, Zone};
+
+    #[test]
+    fn parse_a() {
+        let rdata = RData::parse(&RecordType::A, "192.0.2.1").unwrap();
+        assert_eq!(rdata, RData::A(Ipv4Addr::new(192, 0, 2, 1)));
+        assert!(RData::parse(&RecordType::A, "192.0.2.256").is_err());
+    }
+
+    #[test]
+    fn parse_mx() {
+        let rdata = RData::parse(&RecordType::MX, "10 mail.powerdns.com.").unwrap();
+        assert_eq!(
+            rdata,
+            RData::MX {
+                preference: 10,
+                exchange: String::from("mail.powerdns.com.")
+            }
+        );
+        assert_eq!(rdata.to_content(), "10 mail.powerdns.com.");
+    }
+
+    #[test]
+    fn txt_round_trip() {
+        let content = r#""v=spf1 -all" "say \"hi\"""#;
+        let rdata = RData::parse(&RecordType::TXT, content).unwrap();
+        assert_eq!(
+            rdata,
+            RData::TXT(vec![
+                String::from("v=spf1 -all"),
+                String::from("say \"hi\"")
+            ])
+        );
+        assert_eq!(rdata.to_content(), content);
+        assert!(RData::parse(&RecordType::TXT, r#""unterminated"#).is_err());
+    }
+
+    #[test]
+    fn split_long_txt() {
+        let key = format!("v=DKIM1; k=rsa; p={}", "A".repeat(400));
+        let txt = Txt::from_value(&key);
+        assert_eq!(txt.strings().len(), 2);
+        assert_eq!(txt.strings()[0].len(), MAX_CHARACTER_STRING_LEN);
+        assert_eq!(txt.value(), key);
+
+        let content = txt.to_content();
+        assert!(content.starts_with("\"v=DKIM1; k=rsa; p=AAA"));
+        assert!(content.contains("\" \"AAA"));
+        assert_eq!(content.parse::<Txt>().unwrap(), txt);
+        assert_eq!(RData::TXT(vec![key]).to_content(), content);
+
+        let multibyte = "é".repeat(200);
+        let txt = Txt::from_value(&multibyte);
+        assert_eq!(txt.strings()[0].len(), 254);
+        assert_eq!(txt.value(), multibyte);
+        assert_eq!(txt.to_content().parse::<Txt>().unwrap(), txt);
+
+        assert_eq!(Txt::from_value("").to_content(), "\"\"");
+        assert_eq!(
+            Txt::from_value("say \"hi\"").to_content(),
+            r#""say \"hi\"""#
+        );
+        assert!(format!("\"{}\"", "a".repeat(256)).parse::<Txt>().is_err());
+        assert!(Txt::from_strings(["a".repeat(256)]).is_none());
+    }
+
+    #[test]
+    fn caa_round_trip() {
+        let content = r#"0 issue "letsencrypt.org; validationmethods=dns-01""#;
+        let rdata = RData::parse(&RecordType::CAA, content).unwrap();
+        assert_eq!(
+            rdata,
+            RData::CAA {
+                flags: 0,
+                tag: String::from("issue"),
+                value: String::from("letsencrypt.org; validationmethods=dns-01")
+            }
+        );
+        assert_eq!(rdata.to_content(), content);
+    }
+
+    #[test]
+    fn lua_and_alias_round_trip() {
+        let content = r#"A "ifportup(443, {'192.0.2.1', '192.0.2.2'})""#;
+        let rdata = RData::parse(&RecordType::LUA, content).unwrap();
+        assert_eq!(
+            rdata,
+            RData::lua(RecordType::A, "ifportup(443, {'192.0.2.1', '192.0.2.2'})")
+        );
+        assert_eq!(rdata.to_content(), content);
+
+        let script = RData::lua_script(RecordType::TXT, r#"return "hello""#);
+        assert_eq!(script.to_content(), r#"TXT ";return \"hello\"""#);
+        assert_eq!(
+            RData::parse(&RecordType::LUA, &script.to_content()).unwrap(),
+            script
+        );
+        let split = r#"A "ifportup(443, " "{'192.0.2.1'})""#;
+        assert_eq!(
+            RData::parse(&RecordType::LUA, split).unwrap(),
+            RData::lua(RecordType::A, "ifportup(443, {'192.0.2.1'})")
+        );
+        for invalid in ["A ifportup(443, {'192.0.2.1'})", "A", r#"LUA "1""#] {
+            assert!(
+                RData::parse(&RecordType::LUA, invalid).is_err(),
+                "{invalid}"
+            );
+        }
+
+        let rdata = RData::parse(&RecordType::ALIAS, "cdn.example.net.").unwrap();
+        assert_eq!(rdata, RData::alias("cdn.example.net"));
+        assert_eq!(rdata.to_content(), "cdn.example.net.");
+        assert!(RData::parse(&RecordType::ALIAS, "a. b.").is_err());
+    }
+
+    #[test]
+    fn soa_round_trip() {
+        let content = "ns1.example.com. hostmaster.example.com. 2024010101 10800 3600 604800 3600";
+        let soa: Soa = content.parse().unwrap();
+        assert_eq!(soa.rname, "hostmaster.example.com.");
+        assert_eq!(soa.serial, 2024010101);
+        assert_eq!(soa.minimum, 3600);
+        assert_eq!(soa.to_string(), content);
+        assert!("ns1.example.com. hostmaster.example.com. 1 2 3"
+            .parse::<Soa>()
+            .is_err());
+
+        let mut zone = Zone::default();
+        assert_eq!(zone.soa(), Ok(None));
+        zone.rrsets = Some(vec![RRSet {
+            name: String::from("example.com."),
+            type_field: RecordType::SOA,
+            ttl: 3600,
+            changetype: None,
+            records: vec![Record {
+                content: content.to_string(),
+                disabled: Some(false),
+            }],
+            comments: None,
+            extra: Default::default(),
+        }]);
+        assert_eq!(zone.soa(), Ok(Some(soa)));
+    }
+}
@@ -0,0 +1,316 @@
+//! Reconcile the zones of a server with a directory of YAML zone
+//! definitions, see [`crate::yaml`], e.g. a repository in which DNS changes
+//! are made through pull requests.
+//!
+//! [`ZoneClient::plan_directory`] returns the changes that would be made,
+//! for review, and [`ZoneClient::apply_directory`] makes them. A [`Plan`]
+//! displays like the plan of terraform:
+//!
+//! ```text
+//! + zone example.net.
+//!     + www.example.net. 300 A 192.0.2.3
+//! ~ zone example.com.
+//!     ~ www.example.com. A
+//!         + 192.0.2.2
+//!         - 192.0.2.1
+//! - zone unmanaged.org.
+//!
+//! Plan: 1 to create, 1 to update, 1 to delete.
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::diff::{ApplyOptions, ZoneDiff};
+use crate::yaml::{YamlError, ZoneDefinition};
+use crate::zones::{absolute_name, ZoneClient};
+use crate::Error;
+
+/// Options for [`ZoneClient::plan`] and the directory based variants
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReconcileOptions {
+    /// How the rrsets of existing zones are reconciled
+    pub rrsets: ApplyOptions,
+    /// Whether to delete zones of the server that are not defined. By
+    /// default they are left untouched.
+    pub delete_unmanaged: bool,
+}
+
+/// The changes needed to make a server match a set of zone definitions
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Plan {
+    /// Zones that do not exist yet
+    pub create: Vec<ZoneDefinition>,
+    /// Existing zones whose rrsets differ
+    pub update: Vec<ZoneUpdate>,
+    /// Zones that are not defined, with [`ReconcileOptions::delete_unmanaged`]
+    pub delete: Vec<String>,
+}
+
+/// The changes to the rrsets of an existing zone
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneUpdate {
+    /// Name of the zone, with a trailing dot
+    pub zone: String,
+    pub diff: ZoneDiff,
+}
+
+impl Plan {
+    /// Whether the server already matches the definitions
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty() && self.delete.is_empty()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes.");
+        }
+        for definition in &self.create {
+            writeln!(f, "+ zone {}", definition.name)?;
+            let diff = ZoneDiff {
+                added: definition.rrsets.clone(),
+                ..Default::default()
+            };
+            diff.write_indented(f, "    ")?;
+        }
+        for update in &self.update {
+            writeln!(f, "~ zone {}", update.zone)?;
+            update.diff.write_indented(f, "    ")?;
+        }
+        for zone in &self.delete {
+            writeln!(f, "- zone {zone}")?;
+        }
+        writeln!(
+            f,
+            "\nPlan: {} to create, {} to update, {} to delete.",
+            self.create.len(),
+            self.update.len(),
+            self.delete.len()
+        )
+    }
+}
+
+/// Read the zone definitions of a directory, one per file with a `.yaml` or
+/// `.yml` extension. Other files and subdirectories are ignored, and the
+/// definitions are returned ordered by file name.
+pub fn read_directory(path: impl AsRef<Path>) -> Result<Vec<ZoneDefinition>, Error> {
+    let path = path.as_ref();
+    let read_error = |path: &Path, source| Error::ReadFile {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let mut files: Vec<PathBuf> = Vec::new();
+    for entry in fs::read_dir(path).map_err(|e| read_error(path, e))? {
+        let file = entry.map_err(|e| read_error(path, e))?.path();
+        let is_yaml = file
+            .extension()
+            .is_some_and(|extension| extension == "yaml" || extension == "yml");
+        if is_yaml && file.is_file() {
+            files.push(file);
+        }
+    }
+    files.sort();
+
+    let mut definitions = Vec::new();
+    let mut defined_in: HashMap<String, PathBuf> = HashMap::new();
+    for file in files {
+        let yaml = fs::read_to_string(&file).map_err(|e| read_error(&file, e))?;
+        let definition =
+            ZoneDefinition::from_yaml(&yaml).map_err(|source| Error::InvalidDefinition {
+                path: file.clone(),
+                source,
+            })?;
+        if let Some(other) = defined_in.insert(definition.name.clone(), file.clone()) {
+            return Err(Error::InvalidDefinition {
+                path: file,
+                source: YamlError::Invalid(format!(
+                    "zone {} is already defined in {}",
+                    definition.name,
+                    other.display()
+                )),
+            });
+        }
+        definitions.push(definition);
+    }
+    Ok(definitions)
+}
+
+#[allow(deprecated)]
+impl ZoneClient<'_> {
+    /// The changes needed to make the server match `definitions`: zones to
+    /// create, rrsets to change and, optionally, zones to delete. Nothing is
+    /// changed on the server.
+    pub async fn plan(
+        &self,
+        definitions: &[ZoneDefinition],
+        options: &ReconcileOptions,
+    ) -> Result<Plan, Error> {
+        let existing: HashSet<String> = self
+            .list()
+            .await?
+            .into_iter()
+            .filter_map(|zone| zone.name.or(zone.id))
+            .map(|name| absolute_name(&name))
+            .collect();
+
+        let mut plan = Plan::default();
+        for definition in definitions {
+            let zone = absolute_name(&definition.name);
+            if !existing.contains(&zone) {
+                plan.create.push(definition.clone());
+                continue;
+            }
+            let current = self.get(&zone).await?.rrsets.unwrap_or_default();
+            let mut diff = ZoneDiff::from_rrsets(&current, &definition.rrsets);
+            diff.restrict(&options.rrsets);
+            if !diff.is_empty() {
+                plan.update.push(ZoneUpdate { zone, diff });
+            }
+        }
+
+        if options.delete_unmanaged {
+            let defined: HashSet<String> = definitions
+                .iter()
+                .map(|definition| absolute_name(&definition.name))
+                .collect();
+            plan.delete = existing.difference(&defined).cloned().collect();
+            plan.delete.sort();
+        }
+        Ok(plan)
+    }
+
+    /// Make the changes of a plan: zones are created first, then updated
+    /// and deleted last. Each zone is updated with a single PATCH, but the
+    /// plan as a whole is not applied atomically.
+    pub async fn apply_plan(&self, plan: &Plan) -> Result<(), Error> {
+        for definition in &plan.create {
+            self.create(definition.clone().into_zone()).await?;
+        }
+        for update in &plan.update {
+            self.patch(&update.zone, update.diff.clone().into_patch())
+                .await?;
+        }
+        for zone in &plan.delete {
+            self.delete(zone).await?;
+        }
+        Ok(())
+    }
+
+    /// [`ZoneClient::plan`] for the zone definitions of a directory, see
+    /// [`read_directory`]
+    pub async fn plan_directory(
+        &self,
+        path: impl AsRef<Path>,
+        options: &ReconcileOptions,
+    ) -> Result<Plan, Error> {
+        self.plan(&read_directory(path)?, options).await
+    }
+
+    /// Reconcile the server with the zone definitions of a directory,
+    /// returning the changes that were made
+    pub async fn apply_directory(
+        &self,
+        path: impl AsRef<Path>,
+        options: &ReconcileOptions,
+    ) -> Result<Plan, Error> {
+        let plan = self.plan_directory(path, options).await?;
+        self.apply_plan(&plan).await?;
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use std::fs;
+
+    use crate::reconcile::{read_directory, ReconcileOptions};
+    use crate::record_type::RecordType;
+    use crate::zones::{RRSet, Record, Zone, ZoneKind};
+    use crate::Error;
+
+    #[tokio::test]
+    async fn apply_directory() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        for (name, address) in [
+            ("example.com.", "192.0.2.1"),
+            ("unmanaged.org.", "192.0.2.9"),
+        ] {
+            let zone = Zone::builder(name)
+                .kind(ZoneKind::Native)
+                .rrset(RRSet::new(
+                    &format!("www.{name}"),
+                    RecordType::A,
+                    300,
+                    vec![Record::new(address)],
+                ))
+                .build()
+                .unwrap();
+            client.zone().create(zone).await.unwrap();
+        }
+
+        let dir = std::env::temp_dir().join(format!("powerdns-reconcile-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let zone = |name: &str, address: &str| {
+            format!(
+                "name: {name}\nrrsets:\n  - name: www\n    type: A\n    ttl: 300\n    records:\n      - {address}\n"
+            )
+        };
+        fs::write(
+            dir.join("example.com.yaml"),
+            zone("example.com.", "192.0.2.2"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("example.net.yml"),
+            zone("example.net.", "192.0.2.3"),
+        )
+        .unwrap();
+        fs::write(dir.join("README.md"), "not a zone").unwrap();
+
+        let options = ReconcileOptions {
+            delete_unmanaged: true,
+            ..Default::default()
+        };
+        let plan = client.zone().plan_directory(&dir, &options).await.unwrap();
+        assert_eq!(plan.create.len(), 1);
+        assert_eq!(plan.create[0].name, "example.net.");
+        assert_eq!(plan.update.len(), 1);
+        assert_eq!(plan.update[0].zone, "example.com.");
+        assert_eq!(plan.delete, ["unmanaged.org."]);
+        assert!(server.zone("example.net.").is_none());
+        assert_eq!(
+            plan.to_string(),
+            "+ zone example.net.\n\
+             \x20   + www.example.net. 300 A 192.0.2.3\n\
+             ~ zone example.com.\n\
+             \x20   ~ www.example.com. A\n\
+             \x20       + 192.0.2.2\n\
+             \x20       - 192.0.2.1\n\
+             - zone unmanaged.org.\n\
+             \n\
+             Plan: 1 to create, 1 to update, 1 to delete.\n"
+        );
+
+        client.zone().apply_directory(&dir, &options).await.unwrap();
+        let rrsets = server.zone("example.com.").unwrap().rrsets.unwrap();
+        assert_eq!(rrsets[0].records, [Record::new("192.0.2.2")]);
+        assert!(server.zone("example.net.").is_some());
+        assert!(server.zone("unmanaged.org.").is_none());
+        let plan = client.zone().plan_directory(&dir, &options).await.unwrap();
+        assert_eq!(plan.to_string(), "No changes.\n");
+
+        fs::write(dir.join("copy.yaml"), zone("example.com.", "192.0.2.2")).unwrap();
+        assert!(matches!(
+            read_directory(&dir),
+            Err(Error::InvalidDefinition { .. })
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
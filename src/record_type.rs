@@ -0,0 +1,217 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The type of a resource record. Types not known to this crate are
+/// represented by `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RecordType {
+    /// A host address (IPv4)
+    A,
+    /// A host address (IPv6)
+    AAAA,
+    /// An AFS database location
+    AFSDB,
+    /// A PowerDNS-specific alias at the zone apex
+    ALIAS,
+    /// A certification authority authorization
+    CAA,
+    /// A child copy of a DNSKEY record
+    CDNSKEY,
+    /// A child copy of a DS record
+    CDS,
+    /// A certificate
+    CERT,
+    /// The canonical name for an alias
+    CNAME,
+    /// Child-to-parent synchronization
+    CSYNC,
+    /// A DHCP identifier
+    DHCID,
+    /// A delegation of a subtree
+    DNAME,
+    /// A DNSSEC public key
+    DNSKEY,
+    /// A delegation signer
+    DS,
+    /// Host information
+    HINFO,
+    /// HTTPS service binding
+    HTTPS,
+    /// A security key
+    KEY,
+    /// Location information
+    LOC,
+    /// A PowerDNS-specific Lua record
+    LUA,
+    /// A mail exchange
+    MX,
+    /// A naming authority pointer
+    NAPTR,
+    /// An authoritative name server
+    NS,
+    /// A DNSSEC next secure record
+    NSEC,
+    /// A DNSSEC hashed next secure record
+    NSEC3,
+    /// The NSEC3 parameters
+    NSEC3PARAM,
+    /// An OpenPGP public key
+    OPENPGPKEY,
+    /// A domain name pointer
+    PTR,
+    /// A responsible person
+    RP,
+    /// A DNSSEC signature
+    RRSIG,
+    /// An S/MIME certificate association
+    SMIMEA,
+    /// The start of a zone of authority
+    SOA,
+    /// A sender policy framework record
+    SPF,
+    /// A service locator
+    SRV,
+    /// An SSH public key fingerprint
+    SSHFP,
+    /// A general purpose service binding
+    SVCB,
+    /// A TLSA certificate association
+    TLSA,
+    /// Text strings
+    TXT,
+    /// A uniform resource identifier
+    URI,
+    /// Any other record type
+    Other(String),
+}
+
+impl RecordType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RecordType::A => "A",
+            RecordType::AAAA => "AAAA",
+            RecordType::AFSDB => "AFSDB",
+            RecordType::ALIAS => "ALIAS",
+            RecordType::CAA => "CAA",
+            RecordType::CDNSKEY => "CDNSKEY",
+            RecordType::CDS => "CDS",
+            RecordType::CERT => "CERT",
+            RecordType::CNAME => "CNAME",
+            RecordType::CSYNC => "CSYNC",
+            RecordType::DHCID => "DHCID",
+            RecordType::DNAME => "DNAME",
+            RecordType::DNSKEY => "DNSKEY",
+            RecordType::DS => "DS",
+            RecordType::HINFO => "HINFO",
+            RecordType::HTTPS => "HTTPS",
+            RecordType::KEY => "KEY",
+            RecordType::LOC => "LOC",
+            RecordType::LUA => "LUA",
+            RecordType::MX => "MX",
+            RecordType::NAPTR => "NAPTR",
+            RecordType::NS => "NS",
+            RecordType::NSEC => "NSEC",
+            RecordType::NSEC3 => "NSEC3",
+            RecordType::NSEC3PARAM => "NSEC3PARAM",
+            RecordType::OPENPGPKEY => "OPENPGPKEY",
+            RecordType::PTR => "PTR",
+            RecordType::RP => "RP",
+            RecordType::RRSIG => "RRSIG",
+            RecordType::SMIMEA => "SMIMEA",
+            RecordType::SOA => "SOA",
+            RecordType::SPF => "SPF",
+            RecordType::SRV => "SRV",
+            RecordType::SSHFP => "SSHFP",
+            RecordType::SVCB => "SVCB",
+            RecordType::TLSA => "TLSA",
+            RecordType::TXT => "TXT",
+            RecordType::URI => "URI",
+            RecordType::Other(rtype) => rtype,
+        }
+    }
+}
+
+impl From<&str> for RecordType {
+    fn from(rtype: &str) -> Self {
+        match rtype.to_ascii_uppercase().as_str() {
+            "A" => RecordType::A,
+            "AAAA" => RecordType::AAAA,
+            "AFSDB" => RecordType::AFSDB,
+            "ALIAS" => RecordType::ALIAS,
+            "CAA" => RecordType::CAA,
+            "CDNSKEY" => RecordType::CDNSKEY,
+            "CDS" => RecordType::CDS,
+            "CERT" => RecordType::CERT,
+            "CNAME" => RecordType::CNAME,
+            "CSYNC" => RecordType::CSYNC,
+            "DHCID" => RecordType::DHCID,
+            "DNAME" => RecordType::DNAME,
+            "DNSKEY" => RecordType::DNSKEY,
+            "DS" => RecordType::DS,
+            "HINFO" => RecordType::HINFO,
+            "HTTPS" => RecordType::HTTPS,
+            "KEY" => RecordType::KEY,
+            "LOC" => RecordType::LOC,
+            "LUA" => RecordType::LUA,
+            "MX" => RecordType::MX,
+            "NAPTR" => RecordType::NAPTR,
+            "NS" => RecordType::NS,
+            "NSEC" => RecordType::NSEC,
+            "NSEC3" => RecordType::NSEC3,
+            "NSEC3PARAM" => RecordType::NSEC3PARAM,
+            "OPENPGPKEY" => RecordType::OPENPGPKEY,
+            "PTR" => RecordType::PTR,
+            "RP" => RecordType::RP,
+            "RRSIG" => RecordType::RRSIG,
+            "SMIMEA" => RecordType::SMIMEA,
+            "SOA" => RecordType::SOA,
+            "SPF" => RecordType::SPF,
+            "SRV" => RecordType::SRV,
+            "SSHFP" => RecordType::SSHFP,
+            "SVCB" => RecordType::SVCB,
+            "TLSA" => RecordType::TLSA,
+            "TXT" => RecordType::TXT,
+            "URI" => RecordType::URI,
+            other => RecordType::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for RecordType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for RecordType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RecordType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rtype = String::deserialize(deserializer)?;
+        Ok(RecordType::from(rtype.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record_type::RecordType;
+
+    #[test]
+    fn known_type() {
+        assert_eq!(RecordType::from("AAAA"), RecordType::AAAA);
+        assert_eq!(RecordType::from("cname"), RecordType::CNAME);
+        assert_eq!(serde_json::to_value(RecordType::MX).unwrap(), "MX");
+    }
+
+    #[test]
+    fn unknown_type() {
+        let rtype: RecordType = serde_json::from_value("TYPE65534".into()).unwrap();
+        assert_eq!(rtype, RecordType::Other(String::from("TYPE65534")));
+        assert_eq!(rtype.to_string(), "TYPE65534");
+    }
+}
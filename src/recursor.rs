@@ -0,0 +1,229 @@
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::server::{ServerClient, Statistic};
+use crate::Client;
+use crate::Error;
+use crate::error::decode;
+
+/// A Zone object as served by the PowerDNS Recursor. Recursor zones are
+/// either served from local data or forwarded to other servers, and do not
+/// expose rrsets.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RecursorZone {
+    /// Opaque zone id (string), assigned by the server, should not be
+    /// interpreted by the application. Guaranteed to be safe for embedding in
+    /// URLs.
+    pub id: Option<String>,
+    /// Name of the zone (e.g. “example.com.”) MUST have a trailing dot
+    pub name: Option<String>,
+    /// Set to “Zone”
+    #[serde(rename = "type")]
+    pub type_field: Option<String>,
+    /// API endpoint for this zone
+    pub url: Option<String>,
+    /// Zone kind, one of “Native”, “Forwarded”
+    pub kind: Option<RecursorZoneKind>,
+    /// For zones of type “Forwarded”, addresses to send the queries to
+    pub servers: Option<Vec<String>>,
+    /// For zones of type “Forwarded”, whether or not the RD bit is set on
+    /// forwarded queries
+    pub recursion_desired: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum RecursorZoneKind {
+    Native,
+    Forwarded,
+}
+
+/// The result of a cache flush
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[non_exhaustive]
+pub struct CacheFlushResult {
+    /// Amount of entries flushed
+    pub count: u64,
+    /// A message about the result like "Flushed cache"
+    pub result: String,
+}
+
+pub struct RecursorClient<'a> {
+    api_client: &'a Client,
+}
+
+impl<'a> RecursorClient<'a> {
+    pub fn new(api_client: &'a Client) -> Self {
+        RecursorClient { api_client }
+    }
+
+    fn url(&self) -> String {
+        format!(
+            "{}/servers/{}",
+            self.api_client.api_url, self.api_client.server_name
+        )
+    }
+
+    /// List all Zones in the recursor
+    pub async fn list_zones(&self) -> Result<Vec<RecursorZone>, Error> {
+        let response = self
+            .api_client
+            .get(format!("{}/zones", self.url()))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – An array of Zones Returns: array of Zone objects
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<Vec<RecursorZone>>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Get a zone served by the recursor
+    pub async fn get_zone(&self, zone_id: &str) -> Result<RecursorZone, Error> {
+        let response = self
+            .api_client
+            .get(format!("{}/zones/{zone_id}", self.url()))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – A Zone Returns: Zone object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<RecursorZone>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Creates a new zone in the recursor, returns the Zone on creation.
+    pub async fn create_zone(&self, zone: RecursorZone) -> Result<RecursorZone, Error> {
+        let response = self
+            .api_client
+            .post(format!("{}/zones", self.url()))
+            .json(&zone)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 201 Created – A zone Returns: Zone object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::CREATED => Ok(decode::<RecursorZone>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Modifies basic zone data (kind, servers, recursion_desired).
+    pub async fn update_zone(&self, zone_id: &str, zone: RecursorZone) -> Result<(), Error> {
+        let response = self
+            .api_client
+            .put(format!("{}/zones/{zone_id}", self.url()))
+            .json(&zone)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 204 No Content – Returns 204 No Content on success.
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Deletes this zone from the recursor.
+    pub async fn delete_zone(&self, zone_id: &str) -> Result<(), Error> {
+        let response = self
+            .api_client
+            .delete(format!("{}/zones/{zone_id}", self.url()))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 204 No Content – Returns 204 No Content on success.
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Flush a cache-entry by name
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The domain name to flush from the cache
+    /// * `subtree` - Whether or not to also flush everything below `domain`
+    pub async fn flush_cache(&self, domain: &str, subtree: bool) -> Result<CacheFlushResult, Error> {
+        let response = self
+            .api_client
+            .put(format!("{}/cache/flush", self.url()))
+            .query(&[("domain", domain)])
+            .query(&[("subtree", subtree)])
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – Flush successful Returns: CacheFlushResult object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<CacheFlushResult>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Query recursor statistics, see [`ServerClient::statistics`]
+    pub async fn statistics(
+        &self,
+        statistic: Option<&str>,
+        include_rings: bool,
+    ) -> Result<Vec<Statistic>, Error> {
+        ServerClient::new(self.api_client)
+            .statistics(statistic, include_rings)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::recursor::{RecursorZone, RecursorZoneKind};
+
+    #[test]
+    fn deserialize_forwarded_zone() {
+        let zone: RecursorZone = serde_json::from_value(serde_json::json!({
+            "id": "example.com.",
+            "name": "example.com.",
+            "type": "Zone",
+            "url": "/api/v1/servers/localhost/zones/example.com.",
+            "kind": "Forwarded",
+            "servers": ["192.0.2.53:53"],
+            "recursion_desired": false
+        }))
+        .unwrap();
+
+        assert_eq!(zone.kind, Some(RecursorZoneKind::Forwarded));
+        assert_eq!(zone.recursion_desired, Some(false));
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+
+/// Decides which failed requests are retried, and how long to wait in between
+/// attempts. The default policy does not retry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_backoff: Duration,
+    /// Factor the delay is multiplied with after every attempt
+    pub multiplier: f64,
+    /// Whether to randomize delays, to keep many clients from retrying at
+    /// the same time
+    pub jitter: bool,
+    /// Response status codes that are retried
+    pub retry_statuses: Vec<StatusCode>,
+    /// Whether to retry when the connection could not be established
+    pub retry_connect_errors: bool,
+    /// Whether to retry when the request timed out
+    pub retry_timeouts: bool,
+    /// Whether to retry POST requests. These are not idempotent, so a retry
+    /// may fail with 409 Conflict when the first attempt reached the server.
+    pub retry_post: bool,
+    /// The longest Retry-After delay of a response to wait for before
+    /// retrying, responses asking for a longer delay are returned instead.
+    /// The delay replaces the backoff of the attempt.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+            retry_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_connect_errors: true,
+            retry_timeouts: true,
+            retry_post: false,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The default policy, making up to `max_attempts` attempts
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn retries_method(&self, method: &Method) -> bool {
+        self.retry_post || method != Method::POST
+    }
+
+    pub(crate) fn retries_status(&self, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status)
+    }
+
+    pub(crate) fn retries_error(&self, error: &reqwest::Error) -> bool {
+        (self.retry_connect_errors && error.is_connect()) || (self.retry_timeouts && error.is_timeout())
+    }
+
+    /// The delay before retrying after the given (1-based) attempt failed
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        // clamped before converting back, as mul_f64 panics on overflow
+        let seconds = self.initial_backoff.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        let backoff = Duration::try_from_secs_f64(seconds.min(self.max_backoff.as_secs_f64()))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+
+        if self.jitter {
+            // Equal jitter: a random delay in [backoff / 2, backoff)
+            let random = RandomState::new().build_hasher().finish();
+            backoff / 2 + backoff.mul_f64((random % 1_000) as f64 / 2_000.0)
+        } else {
+            backoff
+        }
+    }
+}
+
+/// The delay requested by the Retry-After header, in seconds or as a date
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use reqwest::{Method, StatusCode};
+
+    use crate::retry::{retry_after, RetryPolicy};
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = RetryPolicy {
+            jitter: false,
+            max_backoff: Duration::from_millis(500),
+            ..RetryPolicy::new(5)
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn clamp_overflowing_backoff() {
+        let policy = RetryPolicy {
+            jitter: false,
+            initial_backoff: Duration::MAX,
+            multiplier: 10.0,
+            ..RetryPolicy::new(50)
+        };
+
+        assert_eq!(policy.backoff(1), policy.max_backoff);
+        assert_eq!(policy.backoff(40), policy.max_backoff);
+    }
+
+    #[test]
+    fn jittered_backoff() {
+        let policy = RetryPolicy::new(5);
+        let backoff = policy.backoff(2);
+        assert!(backoff >= Duration::from_millis(100) && backoff <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn retryable_requests() {
+        let policy = RetryPolicy::new(3);
+        assert!(policy.retries_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!policy.retries_status(StatusCode::UNPROCESSABLE_ENTITY));
+        assert!(policy.retries_method(&Method::PATCH));
+        assert!(!policy.retries_method(&Method::POST));
+    }
+
+    #[test]
+    fn parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&date).unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(25) && delay <= Duration::from_secs(30));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
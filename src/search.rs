@@ -0,0 +1,142 @@
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::record_type::RecordType;
+use crate::Client;
+use crate::Error;
+use crate::error::decode;
+
+/// The type of object to search for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectType {
+    All,
+    Zone,
+    Record,
+    Comment,
+}
+
+impl ObjectType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ObjectType::All => "all",
+            ObjectType::Zone => "zone",
+            ObjectType::Record => "record",
+            ObjectType::Comment => "comment",
+        }
+    }
+}
+
+/// A single search result, depending on the type of object found
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "object_type", rename_all = "lowercase")]
+pub enum SearchResult {
+    /// A zone whose name matched the query
+    Zone {
+        /// Name of the zone
+        name: String,
+        /// Id of the zone
+        zone_id: String,
+    },
+    /// A record whose name or content matched the query
+    Record {
+        /// Name of the record
+        name: String,
+        /// Type of the record
+        #[serde(rename = "type")]
+        type_field: RecordType,
+        /// The content of the record
+        content: String,
+        /// DNS TTL of the record, in seconds
+        ttl: u32,
+        /// Whether or not the record is disabled
+        disabled: bool,
+        /// Name of the zone the record lives in
+        zone: String,
+        /// Id of the zone the record lives in
+        zone_id: String,
+    },
+    /// A comment whose content matched the query
+    Comment {
+        /// Name of the RRSet the comment belongs to
+        name: String,
+        /// The actual comment
+        content: String,
+        /// Name of the zone the comment lives in
+        zone: String,
+        /// Id of the zone the comment lives in
+        zone_id: String,
+    },
+}
+
+impl Client {
+    /// Search the data inside PowerDNS
+    ///
+    /// Search the data inside PowerDNS for `query` and return at most `max`
+    /// results of `object_type`. This includes zones, records and comments.
+    /// The `*` character can be used in `query` as a wildcard character and
+    /// the `?` character can be used as a wildcard for a single character.
+    pub async fn search(
+        &self,
+        query: &str,
+        max: u32,
+        object_type: ObjectType,
+    ) -> Result<Vec<SearchResult>, Error> {
+        let response = self
+            .get(format!(
+                "{}/servers/{}/search-data",
+                self.api_url, self.server_name
+            ))
+            .query(&[
+                ("q", query),
+                ("max", &max.to_string()),
+                ("object_type", object_type.as_str()),
+            ])
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – Returns a JSON array with results Returns: array of SearchResult objects
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<Vec<SearchResult>>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::SearchResult;
+
+    #[test]
+    fn deserialize_results() {
+        let results: Vec<SearchResult> = serde_json::from_value(serde_json::json!([
+            {
+                "object_type": "zone",
+                "name": "powerdns.com.",
+                "zone_id": "powerdns.com."
+            },
+            {
+                "object_type": "record",
+                "name": "www.powerdns.com.",
+                "type": "A",
+                "content": "192.0.2.1",
+                "ttl": 3600,
+                "disabled": false,
+                "zone": "powerdns.com.",
+                "zone_id": "powerdns.com."
+            }
+        ]))
+        .unwrap();
+
+        assert!(matches!(results[0], SearchResult::Zone { .. }));
+        assert!(matches!(
+            &results[1],
+            SearchResult::Record { zone, .. } if zone == "powerdns.com."
+        ));
+    }
+}
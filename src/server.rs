@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
+use reqwest::StatusCode;
 use serde::Deserialize;
 
 use crate::{Client, Error};
-use crate::error::PowerDNSResponseError;
+use crate::error::decode;
 
 /// The server endpoint is the ‘basis’ for all other API operations. In the
 /// PowerDNS Authoritative Server, the server_id is always localhost. However,
@@ -9,6 +12,8 @@ use crate::error::PowerDNSResponseError;
 /// each with their own server_id.
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde_with::skip_serializing_none]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[non_exhaustive]
 pub struct Server {
     /// Set to “Server”
     #[serde(rename = "type")]
@@ -26,6 +31,116 @@ pub struct Server {
     pub config_url: String,
     /// The API endpoint for this server’s zones
     pub zones_url: String,
+    /// Fields not known to this crate, e.g. ones added by newer servers.
+    /// Always empty with the `strict` feature, which rejects them instead.
+    #[cfg_attr(not(feature = "strict"), serde(flatten))]
+    #[cfg_attr(feature = "strict", serde(skip))]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// A statistic item reported by the server
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type")]
+pub enum Statistic {
+    /// A single named value
+    StatisticItem {
+        /// The name of this item (e.g. ‘uptime’)
+        name: String,
+        /// The value of item
+        value: String,
+    },
+    /// A named map of values
+    MapStatisticItem {
+        /// The name of this item (e.g. ‘response-by-qtype’)
+        name: String,
+        /// The named values of this item
+        value: Vec<SimpleStatisticItem>,
+    },
+    /// A ring buffer of the most recent values
+    RingStatisticItem {
+        /// The name of this item (e.g. ‘logmessages’)
+        name: String,
+        /// The size of the ring
+        size: u32,
+        /// The entries of the ring
+        value: Vec<SimpleStatisticItem>,
+    },
+}
+
+/// A single entry of a MapStatisticItem or RingStatisticItem
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[non_exhaustive]
+pub struct SimpleStatisticItem {
+    /// Item name
+    pub name: String,
+    /// Item value
+    pub value: String,
+}
+
+/// A version of the API offered by the webserver, see
+/// [`Client::api_versions`]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[non_exhaustive]
+pub struct ApiVersion {
+    /// The version number, e.g. 1
+    pub version: u32,
+    /// The path of this version, e.g. `/api/v1`
+    pub url: String,
+}
+
+impl Client {
+    /// List the API versions offered by the webserver, from `GET /api`. The
+    /// listing is found next to the configured API path, e.g. at
+    /// `/dns/api` for `/dns/api/v1`.
+    pub async fn api_versions(&self) -> Result<Vec<ApiVersion>, Error> {
+        let response = self.get(self.versions_url()).send().await?;
+        if response.status().is_success() {
+            decode(response).await
+        } else {
+            Err(Error::from_response(response).await)
+        }
+    }
+
+    /// Check that the webserver can be reached and accepts the credentials,
+    /// with a single cheap request. Useful at startup, to fail early on a
+    /// wrong URL or API key.
+    pub async fn ping(&self) -> Result<(), Error> {
+        self.api_versions().await.map(|_| ())
+    }
+
+    fn versions_url(&self) -> String {
+        match self.api_url.rsplit_once('/') {
+            Some((parent, version))
+                if version.strip_prefix('v').is_some_and(|v| v.parse::<u32>().is_ok()) =>
+            {
+                parent.to_string()
+            }
+            _ => format!("{}/api", self.base_url),
+        }
+    }
+}
+
+/// The result of [`ServerClient::health`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Health {
+    /// Whether the webserver responded
+    pub reachable: bool,
+    /// Whether the webserver accepted the credentials
+    pub authorized: bool,
+    /// “authoritative” or “recursor”, when the server was found
+    pub daemon_type: Option<String>,
+    /// The version of the server software, when the server was found
+    pub version: Option<String>,
+    /// The error of the check, if it failed
+    pub error: Option<String>,
+}
+
+impl Health {
+    /// Whether the server was found with the configured credentials
+    pub fn is_healthy(&self) -> bool {
+        self.reachable && self.authorized && self.error.is_none()
+    }
 }
 
 pub struct ServerClient<'a> {
@@ -53,15 +168,13 @@ impl<'a> ServerClient<'a> {
     pub async fn list(&self) -> Result<Vec<Server>, Error> {
         let resp = self
             .api_client
-            .http_client
-            .get(format!("{}/api/v1/servers", self.api_client.base_url))
+            .get(format!("{}/servers", self.api_client.api_url))
             .send()
-            .await
-            .unwrap();
+            .await?;
         if resp.status().is_success() {
-            Ok(resp.json::<Vec<Server>>().await.unwrap())
+            Ok(decode::<Vec<Server>>(resp).await?)
         } else {
-            Err(resp.json::<PowerDNSResponseError>().await?)?
+            Err(Error::from_response(resp).await)
         }
     }
 
@@ -85,36 +198,159 @@ impl<'a> ServerClient<'a> {
     pub async fn get(&self, server_id: &str) -> Result<Server, Error> {
         let resp = self
             .api_client
-            .http_client
             .get(format!(
-                "{}/api/v1/servers/{server_id}",
-                self.api_client.base_url
+                "{}/servers/{server_id}",
+                self.api_client.api_url
             ))
             .send()
-            .await
-            .unwrap();
+            .await?;
         if resp.status().is_success() {
-            Ok(resp.json::<Server>().await.unwrap())
+            Ok(decode::<Server>(resp).await?)
         } else {
-            Err(resp.json::<PowerDNSResponseError>().await?)?
+            Err(Error::from_response(resp).await)
+        }
+    }
+
+    /// Check the server of the Client with a single request, e.g. for
+    /// readiness probes. Failures are reported in the result instead of as
+    /// an error.
+    pub async fn health(&self) -> Health {
+        let error = match self.get(&self.api_client.server_name).await {
+            Ok(server) => {
+                return Health {
+                    reachable: true,
+                    authorized: true,
+                    daemon_type: Some(server.daemon_type),
+                    version: Some(server.version),
+                    error: None,
+                }
+            }
+            Err(error) => error,
+        };
+        let status = error.status();
+        Health {
+            reachable: status.is_some(),
+            authorized: !matches!(
+                status,
+                None | Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+            ),
+            daemon_type: None,
+            version: None,
+            error: Some(error.to_string()),
+        }
+    }
+
+    /// Query statistics
+    ///
+    /// Query PowerDNS internal statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `statistic` - When set to the name of a specific statistic, only
+    ///   this value is returned
+    /// * `include_rings` - Whether or not ring statistics are included
+    pub async fn statistics(
+        &self,
+        statistic: Option<&str>,
+        include_rings: bool,
+    ) -> Result<Vec<Statistic>, Error> {
+        let mut request = self
+            .api_client
+            .get(format!(
+                "{}/servers/{}/statistics",
+                self.api_client.api_url, self.api_client.server_name
+            ))
+            .query(&[("includerings", include_rings)]);
+        if let Some(statistic) = statistic {
+            request = request.query(&[("statistic", statistic)]);
+        }
+        let response = request.send().await?;
+
+        match response.status() {
+            // 200 OK – List of Statistic Items Returns: array of StatisticItem or MapStatisticItem or RingStatisticItem objects
+            // 422 Unprocessable Entity – Returned when a non-existing statistic name has been requested. Contains an error message Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<Vec<Statistic>>(response).await?),
+            _ => Err(Error::from_response(response).await),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::client::Client;
-    use dotenvy::dotenv;
-    use std::env;
+    use crate::mock::MockServer;
+    use crate::server::ApiVersion;
+    use crate::Client;
 
     #[tokio::test]
-    async fn list() {
-        dotenv().ok();
-        let client = Client::new(
-            &env::var("PDNS_HOST").unwrap_or_else(|_| String::from("http://localhost:8081")),
-            &env::var("PDNS_SERVER").unwrap_or_else(|_| String::from("localhost")),
-            &env::var("PDNS_API_KEY").unwrap(),
+    async fn health() {
+        let server = MockServer::start();
+        let health = server.client().server().health().await;
+        assert!(health.is_healthy());
+        assert_eq!(health.daemon_type.as_deref(), Some("authoritative"));
+
+        let client = Client::builder()
+            .base_url(server.base_url())
+            .server_name("localhost")
+            .api_key("wrong")
+            .build()
+            .unwrap();
+        let health = client.server().health().await;
+        assert!(health.reachable && !health.authorized);
+
+        let other = server.client().for_server("other");
+        let health = other.server().health().await;
+        assert!(health.reachable && health.authorized && !health.is_healthy());
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let unreachable = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let health = Client::builder()
+            .base_url(unreachable)
+            .server_name("localhost")
+            .api_key("secret")
+            .build()
+            .unwrap()
+            .server()
+            .health()
+            .await;
+        assert!(!health.reachable && !health.authorized && health.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn api_versions() {
+        let server = MockServer::start();
+        let client = server.client();
+        assert_eq!(
+            client.api_versions().await.unwrap(),
+            [ApiVersion {
+                version: 1,
+                url: String::from("/api/v1"),
+            }]
         );
+        assert!(client.ping().await.is_ok());
+
+        let client = Client::builder()
+            .base_url(server.base_url())
+            .server_name("localhost")
+            .api_key("wrong")
+            .build()
+            .unwrap();
+        assert!(client.ping().await.is_err());
+
+        let client = Client::builder()
+            .base_url("https://proxy.internal")
+            .api_path("/dns/api/v1")
+            .build()
+            .unwrap();
+        assert_eq!(client.versions_url(), "https://proxy.internal/dns/api");
+    }
+
+    #[tokio::test]
+    async fn list() {
+        let server = MockServer::start();
+        let client = server.client();
 
         let server_client = client.server();
 
@@ -125,12 +361,8 @@ mod tests {
 
     #[tokio::test]
     async fn get_localhost() {
-        dotenv().ok();
-        let client = Client::new(
-            &env::var("PDNS_HOST").unwrap_or_else(|_| String::from("http://localhost:8081")),
-            &env::var("PDNS_SERVER").unwrap_or_else(|_| String::from("localhost")),
-            &env::var("PDNS_API_KEY").unwrap(),
-        );
+        let server = MockServer::start();
+        let client = server.client();
 
         let server_client = client.server();
 
@@ -138,4 +370,31 @@ mod tests {
 
         assert_eq!(server.unwrap().id, "localhost");
     }
+
+    #[test]
+    fn deserialize_statistics() {
+        use crate::server::Statistic;
+
+        let statistics: Vec<Statistic> = serde_json::from_value(serde_json::json!([
+            {"name": "uptime", "type": "StatisticItem", "value": "42"},
+            {
+                "name": "response-by-qtype",
+                "type": "MapStatisticItem",
+                "value": [{"name": "A", "value": "7"}]
+            },
+            {
+                "name": "logmessages",
+                "type": "RingStatisticItem",
+                "size": 10000,
+                "value": []
+            }
+        ]))
+        .unwrap();
+
+        assert_eq!(statistics.len(), 3);
+        assert!(matches!(
+            &statistics[2],
+            Statistic::RingStatisticItem { size: 10000, .. }
+        ));
+    }
 }
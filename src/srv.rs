@@ -0,0 +1,277 @@
+//! Publish the instances of a service as SRV records (RFC 2782), e.g. the
+//! LDAP servers of a domain at `_ldap._tcp.example.com`.
+//!
+//! All instances of a service share one RRSet, so instances are added and
+//! withdrawn individually, keeping the ones published by other hosts.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::names::IntoZoneId;
+use crate::rdata::{RData, RDataError};
+use crate::record_type::RecordType;
+use crate::zones::{absolute_name, PatchZone, RRSetUpdate, Record, ReplaceRRSet, ZoneClient};
+use crate::Error;
+
+/// A single instance of a service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Srv {
+    /// Instances with lower priorities are tried first
+    pub priority: u16,
+    /// The share of the connections among instances of the same priority
+    pub weight: u16,
+    pub port: u16,
+    /// The host of the instance, with a trailing dot
+    pub target: String,
+}
+
+impl Srv {
+    pub fn new(priority: u16, weight: u16, port: u16, target: &str) -> Self {
+        Srv {
+            priority,
+            weight,
+            port,
+            target: absolute_name(target),
+        }
+    }
+
+    /// The name of the SRV RRSet of a service, e.g.
+    /// `_ldap._tcp.example.com.`. The leading underscores of `service` and
+    /// `protocol` are optional.
+    pub fn name(service: &str, protocol: &str, domain: &str) -> String {
+        format!(
+            "_{}._{}.{}",
+            service.trim_start_matches('_'),
+            protocol.trim_start_matches('_'),
+            absolute_name(domain)
+        )
+    }
+
+    /// Whether this is the instance at `target` and `port`
+    pub fn is_instance(&self, target: &str, port: u16) -> bool {
+        self.port == port && self.target.eq_ignore_ascii_case(&absolute_name(target))
+    }
+
+    /// Format the record as content in the format PowerDNS expects
+    pub fn to_content(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Srv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.priority, self.weight, self.port, self.target
+        )
+    }
+}
+
+impl FromStr for Srv {
+    type Err = RDataError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        match RData::parse(&RecordType::SRV, content)? {
+            RData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => Ok(Srv {
+                priority,
+                weight,
+                port,
+                target,
+            }),
+            _ => unreachable!("SRV content parses as RData::SRV"),
+        }
+    }
+}
+
+impl From<Srv> for RData {
+    fn from(srv: Srv) -> Self {
+        RData::SRV {
+            priority: srv.priority,
+            weight: srv.weight,
+            port: srv.port,
+            target: srv.target,
+        }
+    }
+}
+
+impl ZoneClient<'_> {
+    /// Add `instance` to the SRV RRSet `name`, see [`Srv::name`], and set
+    /// the TTL of the RRSet to `ttl`. A record for the same target and port
+    /// is replaced, so the priority and weight of an instance can be
+    /// changed; the other instances are kept.
+    ///
+    /// Publishing reads the RRSet first, so instances of the same service
+    /// should not be published concurrently from several processes.
+    pub async fn publish_service(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        ttl: u32,
+        instance: &Srv,
+    ) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let name = absolute_name(name);
+        let mut records = self
+            .get_rrset(&zone_id, &name, RecordType::SRV)
+            .await?
+            .map(|rrset| rrset.records)
+            .unwrap_or_default();
+        records.retain(|record| !is_instance(record, &instance.target, instance.port));
+        records.push(Record {
+            content: instance.to_content(),
+            disabled: Some(false),
+        });
+
+        let rrset = RRSetUpdate::Replace(ReplaceRRSet {
+            name,
+            rtype: RecordType::SRV,
+            ttl,
+            records,
+            comments: None,
+        });
+        self.patch(
+            &zone_id,
+            PatchZone {
+                rrsets: vec![rrset],
+            },
+        )
+        .await
+    }
+
+    /// Remove the instance at `target` and `port` from the SRV RRSet
+    /// `name`, deleting the RRSet when no instances are left. Returns
+    /// whether the instance was published.
+    pub async fn withdraw_service(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        target: &str,
+        port: u16,
+    ) -> Result<bool, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let name = absolute_name(name);
+        let Some(mut rrset) = self.get_rrset(&zone_id, &name, RecordType::SRV).await? else {
+            return Ok(false);
+        };
+        let count = rrset.records.len();
+        rrset
+            .records
+            .retain(|record| !is_instance(record, target, port));
+        if rrset.records.len() == count {
+            return Ok(false);
+        }
+
+        let update = if rrset.records.is_empty() {
+            RRSetUpdate::delete(&name, RecordType::SRV)
+        } else {
+            // leave the comments of the RRSet untouched
+            rrset.comments = None;
+            RRSetUpdate::replace(rrset)
+        };
+        self.patch(
+            &zone_id,
+            PatchZone {
+                rrsets: vec![update],
+            },
+        )
+        .await?;
+        Ok(true)
+    }
+}
+
+/// Whether `record` is an SRV record for `target` and `port`. Records that
+/// cannot be parsed are kept as they are.
+fn is_instance(record: &Record, target: &str, port: u16) -> bool {
+    record
+        .content
+        .parse::<Srv>()
+        .is_ok_and(|srv| srv.is_instance(target, port))
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::record_type::RecordType;
+    use crate::srv::Srv;
+    use crate::zones::{Zone, ZoneKind};
+
+    #[test]
+    fn srv_round_trip() {
+        let srv = Srv::new(10, 60, 389, "ldap1.example.com");
+        assert_eq!(srv.to_content(), "10 60 389 ldap1.example.com.");
+        assert_eq!(srv.to_content().parse::<Srv>().unwrap(), srv);
+        assert!(srv.is_instance("LDAP1.example.com.", 389));
+        assert!(!srv.is_instance("ldap1.example.com", 636));
+        assert!("10 60 ldap1.example.com.".parse::<Srv>().is_err());
+        assert_eq!(
+            Srv::name("_ldap", "tcp", "example.com"),
+            "_ldap._tcp.example.com."
+        );
+    }
+
+    #[tokio::test]
+    async fn publish_and_withdraw() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        client.zone().create(zone).await.unwrap();
+
+        let zones = client.zone();
+        let name = Srv::name("ldap", "tcp", "example.com");
+        for instance in [
+            Srv::new(10, 50, 389, "ldap1.example.com"),
+            Srv::new(10, 50, 389, "ldap2.example.com"),
+            Srv::new(20, 0, 389, "ldap1.example.com"),
+        ] {
+            zones
+                .publish_service("example.com", &name, 300, &instance)
+                .await
+                .unwrap();
+        }
+        let contents = |zone: Zone| -> Vec<String> {
+            zone.rrsets.unwrap()[0]
+                .records
+                .iter()
+                .map(|record| record.content.clone())
+                .collect()
+        };
+        assert_eq!(
+            contents(server.zone("example.com.").unwrap()),
+            [
+                "10 50 389 ldap2.example.com.",
+                "20 0 389 ldap1.example.com."
+            ]
+        );
+
+        assert!(zones
+            .withdraw_service("example.com", &name, "ldap1.example.com", 389)
+            .await
+            .unwrap());
+        assert!(!zones
+            .withdraw_service("example.com", &name, "ldap1.example.com", 389)
+            .await
+            .unwrap());
+        assert_eq!(
+            contents(server.zone("example.com.").unwrap()),
+            ["10 50 389 ldap2.example.com."]
+        );
+        assert!(zones
+            .withdraw_service("example.com", &name, "ldap2.example.com.", 389)
+            .await
+            .unwrap());
+        assert!(zones
+            .get_rrset("example.com", &name, RecordType::SRV)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,168 @@
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::Client;
+use crate::Error;
+use crate::error::decode;
+
+/// A TSIG key that can be used to authenticate NOTIFYs and AXFRs
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct TsigKey {
+    /// The name of the key
+    pub name: Option<String>,
+    /// The ID for this key, used in the TSIGkey URL endpoint.
+    pub id: Option<String>,
+    /// The algorithm of the TSIG key
+    pub algorithm: Option<TsigAlgorithm>,
+    /// The Base64 encoded secret key, empty when listing keys. MAY be empty
+    /// when POSTing to have the server generate the key material
+    pub key: Option<String>,
+    /// Set to “TSIGKey”
+    #[serde(rename = "type")]
+    pub type_field: Option<String>,
+}
+
+/// The HMAC algorithms supported for TSIG keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TsigAlgorithm {
+    #[serde(alias = "hmac-md5.sig-alg.reg.int")]
+    HmacMd5,
+    HmacSha1,
+    HmacSha224,
+    HmacSha256,
+    HmacSha384,
+    HmacSha512,
+}
+
+pub struct TsigKeyClient<'a> {
+    api_client: &'a Client,
+}
+
+impl<'a> TsigKeyClient<'a> {
+    pub fn new(api_client: &'a Client) -> Self {
+        TsigKeyClient { api_client }
+    }
+
+    fn url(&self) -> String {
+        format!(
+            "{}/servers/{}/tsigkeys",
+            self.api_client.api_url, self.api_client.server_name
+        )
+    }
+
+    /// Get all TSIGKeys on the server, except the actual key
+    pub async fn list(&self) -> Result<Vec<TsigKey>, Error> {
+        let response = self.api_client.get(self.url()).send().await?;
+
+        match response.status() {
+            // 200 OK – List of TSIGKey objects Returns: array of TSIGKey objects
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(decode::<Vec<TsigKey>>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Get a specific TSIGKeys on the server, including the actual key
+    pub async fn get(&self, tsigkey_id: &str) -> Result<TsigKey, Error> {
+        let response = self
+            .api_client
+            .get(format!("{}/{tsigkey_id}", self.url()))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – OK. Returns: TSIGKey object
+            // 404 Not Found – Not found. The TSIGKey with the specified tsigkey_id does not exist Returns: Error object
+            // 500 Internal Server Error – Internal Server Error, keys could not be retrieved. Contains error message Returns: Error object
+
+            StatusCode::OK => Ok(decode::<TsigKey>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Add a TSIG key
+    ///
+    /// This methods add a new TSIGKey. The actual key can be generated by the
+    /// server or be provided by the client.
+    pub async fn create(&self, tsigkey: TsigKey) -> Result<TsigKey, Error> {
+        let response = self
+            .api_client
+            .post(self.url())
+            .json(&tsigkey)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 201 Created – Created Returns: TSIGKey object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 409 Conflict – Conflict. A key with this name already exists Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::CREATED => Ok(decode::<TsigKey>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// The TSIGKey at tsigkey_id can be changed in multiple ways: changing
+    /// the name (this also changes the id), the algorithm or the key itself
+    pub async fn update(&self, tsigkey_id: &str, tsigkey: TsigKey) -> Result<TsigKey, Error> {
+        let response = self
+            .api_client
+            .put(format!("{}/{tsigkey_id}", self.url()))
+            .json(&tsigkey)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – OK. TSIGKey is changed Returns: TSIGKey object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Not found. The TSIGKey with the specified tsigkey_id does not exist Returns: Error object
+            // 500 Internal Server Error – Internal Server Error. Contains error message Returns: Error object
+
+            StatusCode::OK => Ok(decode::<TsigKey>(response).await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Delete the TSIGKey with tsigkey_id
+    pub async fn delete(&self, tsigkey_id: &str) -> Result<(), Error> {
+        let response = self
+            .api_client
+            .delete(format!("{}/{tsigkey_id}", self.url()))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 204 No Content – OK, key was deleted
+            // 404 Not Found – Not found. The TSIGKey with the specified tsigkey_id does not exist Returns: Error object
+            // 500 Internal Server Error – Internal Server Error. Contains error message Returns: Error object
+
+            StatusCode::NO_CONTENT => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tsigkeys::TsigAlgorithm;
+
+    #[test]
+    fn algorithm_names() {
+        assert_eq!(
+            serde_json::to_value(TsigAlgorithm::HmacSha256).unwrap(),
+            "hmac-sha256"
+        );
+        assert_eq!(
+            serde_json::from_value::<TsigAlgorithm>("hmac-md5".into()).unwrap(),
+            TsigAlgorithm::HmacMd5
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! Check a [`PatchZone`] for mistakes the server would reject, before it is
+//! sent.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::names::DomainName;
+use crate::rdata::{parse_character_strings, RData};
+use crate::record_type::RecordType;
+use crate::zones::{DomainError, PatchZone, RRSetUpdate};
+
+/// A single mistake found by [`PatchZone::validate`]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PatchProblem {
+    /// See [`PatchZone::normalize_names`]
+    #[error("{name} is not absolute, it must end with a dot")]
+    MissingTrailingDot { name: String },
+
+    #[error("{name} is not a valid owner name: {source}")]
+    InvalidName { name: String, source: DomainError },
+
+    #[error("{name} IN {rtype} is replaced without records or comments, delete it instead")]
+    NoRecords { name: String, rtype: RecordType },
+
+    #[error("{name} IN {rtype} has content that is not quoted: {content}")]
+    UnquotedText {
+        name: String,
+        rtype: RecordType,
+        content: String,
+    },
+
+    #[error("{name} IN {rtype} has content that is not valid for its type: {content}")]
+    InvalidContent {
+        name: String,
+        rtype: RecordType,
+        content: String,
+    },
+
+    #[error("{name} IN CNAME has more than one record")]
+    MultipleCnames { name: String },
+
+    #[error("{name} has a CNAME record and other data")]
+    CnameAndOtherData { name: String },
+
+    #[error("{name} IN {rtype} is changed more than once")]
+    Duplicate { name: String, rtype: RecordType },
+}
+
+/// Returned when a [`PatchZone`] has problems, listing all of them
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct PatchValidationError {
+    pub problems: Vec<PatchProblem>,
+}
+
+impl fmt::Display for PatchValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, problem) in self.problems.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PatchZone {
+    /// Check the patch for mistakes the server would reject with a 422:
+    /// relative or malformed names, replacements without any data, unquoted TXT and SPF
+    /// content, malformed LUA and ALIAS content, CNAMEs next to other data and RRSets
+    /// changed twice.
+    ///
+    /// Only the patch itself is checked, so e.g. a CNAME added next to an
+    /// existing A RRSet is not found. Called by
+    /// [`ZoneClient::patch`](crate::zones::ZoneClient::patch).
+    pub fn validate(&self) -> Result<(), PatchValidationError> {
+        let mut problems = Vec::new();
+        let mut seen = HashSet::new();
+        // per lowercased name, the name as first given, whether a CNAME and
+        // whether other data is replaced
+        let mut data: HashMap<String, (&str, bool, bool)> = HashMap::new();
+
+        for update in &self.rrsets {
+            let (name, rtype) = (update.name(), update.rtype());
+            if !name.ends_with('.') {
+                problems.push(PatchProblem::MissingTrailingDot {
+                    name: name.to_string(),
+                });
+            }
+            if let Err(source) = DomainName::try_from(name) {
+                problems.push(PatchProblem::InvalidName {
+                    name: name.to_string(),
+                    source,
+                });
+            }
+            if !seen.insert((name.to_ascii_lowercase(), rtype.to_string())) {
+                problems.push(PatchProblem::Duplicate {
+                    name: name.to_string(),
+                    rtype: rtype.clone(),
+                });
+            }
+
+            let RRSetUpdate::Replace(rrset) = update else {
+                continue;
+            };
+            if rrset.records.is_empty() {
+                if rrset.comments.as_ref().is_none_or(Vec::is_empty) {
+                    problems.push(PatchProblem::NoRecords {
+                        name: name.to_string(),
+                        rtype: rtype.clone(),
+                    });
+                }
+                continue;
+            }
+
+            if matches!(rtype, RecordType::TXT | RecordType::SPF) {
+                for record in &rrset.records {
+                    let quoted = record.content.trim_start().starts_with('"')
+                        && parse_character_strings(&record.content).is_some();
+                    if !quoted {
+                        problems.push(PatchProblem::UnquotedText {
+                            name: name.to_string(),
+                            rtype: rtype.clone(),
+                            content: record.content.clone(),
+                        });
+                    }
+                }
+            }
+
+            if matches!(rtype, RecordType::LUA | RecordType::ALIAS) {
+                for record in &rrset.records {
+                    if RData::parse(rtype, &record.content).is_err() {
+                        problems.push(PatchProblem::InvalidContent {
+                            name: name.to_string(),
+                            rtype: rtype.clone(),
+                            content: record.content.clone(),
+                        });
+                    }
+                }
+            }
+
+            let entry = data
+                .entry(name.to_ascii_lowercase())
+                .or_insert((name, false, false));
+            if *rtype == RecordType::CNAME {
+                if rrset.records.len() > 1 {
+                    problems.push(PatchProblem::MultipleCnames {
+                        name: name.to_string(),
+                    });
+                }
+                entry.1 = true;
+            } else if !is_dnssec_type(rtype) {
+                entry.2 = true;
+            }
+        }
+
+        let mut conflicts: Vec<_> = data
+            .into_iter()
+            .filter(|(_, (_, cname, other))| *cname && *other)
+            .collect();
+        conflicts.sort_unstable();
+        problems.extend(conflicts.into_iter().map(|(_, (name, _, _))| {
+            PatchProblem::CnameAndOtherData {
+                name: name.to_string(),
+            }
+        }));
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(PatchValidationError { problems })
+        }
+    }
+}
+
+/// Types that may exist next to a CNAME
+fn is_dnssec_type(rtype: &RecordType) -> bool {
+    matches!(
+        rtype,
+        RecordType::RRSIG | RecordType::NSEC | RecordType::NSEC3
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record_type::RecordType;
+    use crate::validate::PatchProblem;
+    use crate::zones::{DomainError, PatchZone, RRSetUpdate, Record, ReplaceRRSet};
+
+    fn replace(name: &str, rtype: RecordType, content: &[&str]) -> RRSetUpdate {
+        RRSetUpdate::Replace(ReplaceRRSet {
+            name: name.to_string(),
+            rtype,
+            ttl: 300,
+            records: content
+                .iter()
+                .map(|content| Record {
+                    content: content.to_string(),
+                    disabled: None,
+                })
+                .collect(),
+            comments: None,
+        })
+    }
+
+    #[test]
+    fn valid_patch() {
+        let patch = PatchZone {
+            rrsets: vec![
+                replace("www.example.com.", RecordType::A, &["192.0.2.1"]),
+                replace("www.example.com.", RecordType::TXT, &[r#""hello" "world""#]),
+                replace("ftp.example.com.", RecordType::CNAME, &["www.example.com."]),
+                RRSetUpdate::delete("old.example.com.", RecordType::A),
+                replace("*.example.com.", RecordType::A, &["192.0.2.2"]),
+                replace(
+                    "_acme-challenge.example.com.",
+                    RecordType::TXT,
+                    &[r#""token""#],
+                ),
+                replace(
+                    "_sip._tcp.example.com.",
+                    RecordType::SRV,
+                    &["10 60 5060 sip.example.com."],
+                ),
+                replace(
+                    "lb.example.com.",
+                    RecordType::LUA,
+                    &[r#"A "ifportup(443, {'192.0.2.1', '192.0.2.2'})""#],
+                ),
+                replace("example.com.", RecordType::ALIAS, &["cdn.example.net."]),
+            ],
+        };
+        assert_eq!(patch.validate(), Ok(()));
+    }
+
+    #[test]
+    fn report_all_problems() {
+        let patch = PatchZone {
+            rrsets: vec![
+                replace("www.example.com", RecordType::A, &["192.0.2.1"]),
+                replace("www.*.example.com.", RecordType::A, &["192.0.2.1"]),
+                replace("txt.example.com.", RecordType::TXT, &["hello"]),
+                replace("lb.example.com.", RecordType::LUA, &["A ifportup(443, {})"]),
+                replace("empty.example.com.", RecordType::A, &[]),
+                replace("Foo.example.com.", RecordType::CNAME, &["www.example.com."]),
+                replace("foo.example.com.", RecordType::A, &["192.0.2.1"]),
+                replace("ftp.example.com.", RecordType::CNAME, &["www.example.com."]),
+                replace("ftp.example.com.", RecordType::A, &["192.0.2.1"]),
+                RRSetUpdate::delete("ftp.example.com.", RecordType::A),
+            ],
+        };
+        let problems = patch.validate().unwrap_err().problems;
+        assert_eq!(
+            problems,
+            [
+                PatchProblem::MissingTrailingDot {
+                    name: String::from("www.example.com")
+                },
+                PatchProblem::InvalidName {
+                    name: String::from("www.*.example.com."),
+                    source: DomainError::InvalidCharacter('*')
+                },
+                PatchProblem::UnquotedText {
+                    name: String::from("txt.example.com."),
+                    rtype: RecordType::TXT,
+                    content: String::from("hello")
+                },
+                PatchProblem::InvalidContent {
+                    name: String::from("lb.example.com."),
+                    rtype: RecordType::LUA,
+                    content: String::from("A ifportup(443, {})")
+                },
+                PatchProblem::NoRecords {
+                    name: String::from("empty.example.com."),
+                    rtype: RecordType::A
+                },
+                PatchProblem::Duplicate {
+                    name: String::from("ftp.example.com."),
+                    rtype: RecordType::A
+                },
+                PatchProblem::CnameAndOtherData {
+                    name: String::from("Foo.example.com.")
+                },
+                PatchProblem::CnameAndOtherData {
+                    name: String::from("ftp.example.com.")
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,405 @@
+//! Read and write zone definitions as YAML, e.g. to review DNS changes in
+//! pull requests before applying them with
+//! [`ZoneClient::apply`](crate::zones::ZoneClient::apply).
+//!
+//! ```yaml
+//! name: example.com.
+//! kind: Native
+//! rrsets:
+//!   - name: www
+//!     type: A
+//!     ttl: 300
+//!     records:
+//!       - 192.0.2.1
+//!       - content: 192.0.2.2
+//!         disabled: true
+//! ```
+//!
+//! Names are relative to the zone unless they end with a dot, `@` being the
+//! zone itself. Written files are deterministic: rrsets are ordered by name,
+//! with SOA and NS first, and records by their content, so the same rrsets
+//! always give the same file regardless of the order the server returns them
+//! in.
+//!
+//! Definitions are read with serde_yaml, so hand-written files may use any
+//! YAML, e.g. flow sequences like `records: [192.0.2.1, 192.0.2.2]`.
+
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::record_type::RecordType;
+use crate::zonefile::{qualify, relative_name};
+#[allow(deprecated)]
+use crate::zones::{absolute_name, Comment, RRSet, Record, Zone, ZoneKind};
+
+/// A zone and the rrsets it should contain, e.g. one file of a repository
+/// with the zones of a server
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneDefinition {
+    /// Name of the zone, with a trailing dot
+    pub name: String,
+    /// The kind of the zone when it is created, Native when unset
+    pub kind: Option<ZoneKind>,
+    /// The rrsets with absolute names
+    pub rrsets: Vec<RRSet>,
+}
+
+/// Returned when a zone definition cannot be read
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum YamlError {
+    /// The text is not valid YAML
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+
+    /// The YAML does not describe a zone, e.g. because a field is missing
+    #[error("invalid zone definition: {0}")]
+    Invalid(String),
+}
+
+#[allow(deprecated)]
+impl ZoneDefinition {
+    /// The definition of an existing zone, with the rrsets it currently has
+    pub fn from_zone(zone: &Zone) -> Self {
+        let name = zone.name.as_ref().or(zone.id.as_ref());
+        ZoneDefinition {
+            name: absolute_name(name.map(String::as_str).unwrap_or_default()),
+            kind: zone.kind,
+            rrsets: zone.rrsets.clone().unwrap_or_default(),
+        }
+    }
+
+    /// A payload creating the zone with its rrsets
+    pub fn into_zone(self) -> Zone {
+        Zone {
+            name: Some(self.name),
+            kind: Some(self.kind.unwrap_or(ZoneKind::Native)),
+            rrsets: Some(self.rrsets),
+            ..Default::default()
+        }
+    }
+
+    /// Write the definition as YAML
+    pub fn to_yaml(&self) -> String {
+        let origin = absolute_name(&self.name);
+        let mut yaml = format!("name: {}\n", scalar(&origin));
+        if let Some(kind) = self.kind {
+            let _ = writeln!(yaml, "kind: {}", kind_name(kind));
+        }
+
+        let mut rrsets: Vec<&RRSet> = self.rrsets.iter().collect();
+        rrsets.sort_by(|a, b| compare_rrsets(a, b));
+        if rrsets.is_empty() {
+            yaml.push_str("rrsets: []\n");
+            return yaml;
+        }
+
+        yaml.push_str("rrsets:\n");
+        for rrset in rrsets {
+            let name = relative_name(&absolute_name(&rrset.name), &origin);
+            // writing to a String cannot fail
+            let _ = writeln!(yaml, "  - name: {}", scalar(&name));
+            let _ = writeln!(yaml, "    type: {}", scalar(rrset.type_field.as_str()));
+            let _ = writeln!(yaml, "    ttl: {}", rrset.ttl);
+
+            let mut records: Vec<&Record> = rrset.records.iter().collect();
+            records.sort_by(|a, b| a.content.cmp(&b.content));
+            if records.is_empty() {
+                yaml.push_str("    records: []\n");
+            } else {
+                yaml.push_str("    records:\n");
+            }
+            for record in records {
+                if record.disabled == Some(true) {
+                    let _ = writeln!(yaml, "      - content: {}", scalar(&record.content));
+                    yaml.push_str("        disabled: true\n");
+                } else {
+                    let _ = writeln!(yaml, "      - {}", scalar(&record.content));
+                }
+            }
+
+            match rrset.comments.as_deref() {
+                None => {}
+                Some([]) => yaml.push_str("    comments: []\n"),
+                Some(comments) => {
+                    yaml.push_str("    comments:\n");
+                    for comment in comments {
+                        let _ = writeln!(yaml, "      - content: {}", scalar(&comment.content));
+                        let _ = writeln!(yaml, "        account: {}", scalar(&comment.account));
+                        if let Some(modified_at) = comment.modified_at {
+                            let _ = writeln!(yaml, "        modified_at: {modified_at}");
+                        }
+                    }
+                }
+            }
+        }
+        yaml
+    }
+
+    /// Read a definition written by [`ZoneDefinition::to_yaml`] or by hand
+    pub fn from_yaml(yaml: &str) -> Result<Self, YamlError> {
+        let value = parse(yaml)?;
+        let definition: RawDefinition =
+            serde_yaml::from_value(value).map_err(|e| YamlError::Invalid(e.to_string()))?;
+
+        let origin = absolute_name(&definition.name);
+        let rrsets = definition
+            .rrsets
+            .into_iter()
+            .map(|rrset| RRSet {
+                comments: rrset.comments,
+                ..RRSet::new(
+                    &absolute_name(&qualify(&rrset.name, &origin)),
+                    rrset.rtype,
+                    rrset.ttl,
+                    rrset.records.into_iter().map(Record::from).collect(),
+                )
+            })
+            .collect();
+        Ok(ZoneDefinition {
+            name: origin,
+            kind: definition.kind,
+            rrsets,
+        })
+    }
+}
+
+/// The structure of a zone definition, before names are qualified
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDefinition {
+    name: String,
+    #[serde(default)]
+    kind: Option<ZoneKind>,
+    #[serde(default)]
+    rrsets: Vec<RawRRSet>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRRSet {
+    name: String,
+    #[serde(rename = "type")]
+    rtype: RecordType,
+    ttl: u32,
+    #[serde(default)]
+    records: Vec<RawRecord>,
+    comments: Option<Vec<Comment>>,
+}
+
+/// A record given by its content only, or with all fields
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawRecord {
+    Content(String),
+    Record(Record),
+}
+
+impl From<RawRecord> for Record {
+    fn from(record: RawRecord) -> Self {
+        match record {
+            RawRecord::Content(content) => Record::new(&content),
+            RawRecord::Record(record) => record,
+        }
+    }
+}
+
+fn kind_name(kind: ZoneKind) -> &'static str {
+    match kind {
+        ZoneKind::Native => "Native",
+        ZoneKind::Master => "Master",
+        ZoneKind::Slave => "Slave",
+        ZoneKind::Producer => "Producer",
+        ZoneKind::Consumer => "Consumer",
+    }
+}
+
+/// Order rrsets by their name, parents before their children, and then by
+/// type, SOA and NS first
+fn compare_rrsets(a: &RRSet, b: &RRSet) -> Ordering {
+    let labels = |rrset: &RRSet| {
+        let name = absolute_name(&rrset.name);
+        name.trim_end_matches('.')
+            .rsplit('.')
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+    let rank = |rtype: &RecordType| match rtype {
+        RecordType::SOA => 0,
+        RecordType::NS => 1,
+        _ => 2,
+    };
+    labels(a)
+        .cmp(&labels(b))
+        .then_with(|| rank(&a.type_field).cmp(&rank(&b.type_field)))
+        .then_with(|| a.type_field.as_str().cmp(b.type_field.as_str()))
+}
+
+/// Write a string as a plain scalar when that is unambiguous, and quoted
+/// otherwise
+fn scalar(value: &str) -> String {
+    if is_plain(value) {
+        value.to_string()
+    } else if !value.chars().any(char::is_control) {
+        format!("'{}'", value.replace('\'', "''"))
+    } else {
+        // JSON strings are valid double quoted YAML scalars
+        serde_json::to_string(value).unwrap_or_default()
+    }
+}
+
+/// Whether a string can be written without quotes, and is read back as the
+/// same string by YAML parsers
+fn is_plain(value: &str) -> bool {
+    let Some(first) = value.chars().next() else {
+        return false;
+    };
+    let keyword = [
+        "true", "false", "null", "~", "yes", "no", "on", "off", "y", "n",
+    ]
+    .iter()
+    .any(|keyword| value.eq_ignore_ascii_case(keyword));
+
+    (first.is_ascii_alphanumeric() || matches!(first, '_' | '.' | '/'))
+        && value == value.trim()
+        && value
+            .chars()
+            .all(|c| (c.is_ascii_graphic() || c == ' ') && !matches!(c, '#' | '\'' | '"' | '`'))
+        && !value.contains(": ")
+        && !value.ends_with(':')
+        && !keyword
+        && value.parse::<f64>().is_err()
+}
+
+/// Parse YAML into a value, reporting syntax errors and duplicate keys with
+/// their line
+fn parse(yaml: &str) -> Result<serde_yaml::Value, YamlError> {
+    let value = serde_yaml::from_str(yaml).map_err(|e| {
+        let message = e.to_string();
+        // the location is part of the error instead
+        let message = match message.split_once(" at line ") {
+            Some((message, _)) => message.to_string(),
+            None => message,
+        };
+        YamlError::Syntax {
+            line: e.location().map_or(1, |location| location.line()),
+            message,
+        }
+    })?;
+    match value {
+        serde_yaml::Value::Null => Err(YamlError::Invalid(String::from("the document is empty"))),
+        value => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record_type::RecordType;
+    use crate::yaml::{YamlError, ZoneDefinition};
+    use crate::zones::{Comment, RRSet, Record, ZoneKind};
+
+    fn definition() -> ZoneDefinition {
+        let rrset = |name: &str, rtype, records: &[&str]| {
+            RRSet::new(
+                name,
+                rtype,
+                300,
+                records.iter().map(|content| Record::new(content)).collect(),
+            )
+        };
+        let mut disabled = Record::new("192.0.2.2");
+        disabled.disabled = Some(true);
+        let mut www = rrset("www.example.com.", RecordType::A, &["192.0.2.1"]);
+        www.records.push(disabled);
+        www.comments = Some(vec![Comment::new("web server: primary", "ops")]);
+
+        ZoneDefinition {
+            name: String::from("example.com."),
+            kind: Some(ZoneKind::Native),
+            rrsets: vec![
+                rrset("*.example.com.", RecordType::A, &["192.0.2.3"]),
+                www,
+                rrset("example.com.", RecordType::TXT, &[r#""v=spf1 -all""#]),
+                rrset("example.com.", RecordType::NS, &["ns1.example.com."]),
+                rrset("v6.example.com.", RecordType::AAAA, &["2001:db8::"]),
+                rrset(
+                    "example.com.",
+                    RecordType::SOA,
+                    &["ns1.example.com. hostmaster.example.com. 1 10800 3600 604800 3600"],
+                ),
+                rrset("mail.other.org.", RecordType::MX, &["10 mx.example.com."]),
+            ],
+        }
+    }
+
+    #[test]
+    fn write_and_read() {
+        let definition = definition();
+        let yaml = definition.to_yaml();
+        assert!(yaml.starts_with(
+            "name: example.com.\nkind: Native\nrrsets:\n  - name: '@'\n    type: SOA\n"
+        ));
+        assert!(yaml.contains("      - '\"v=spf1 -all\"'\n"));
+        assert!(yaml.contains("      - content: 192.0.2.2\n        disabled: true\n"));
+        assert!(yaml.contains("  - name: '*'\n"));
+        assert!(yaml.contains("      - '2001:db8::'\n"));
+        assert!(yaml.contains("  - name: mail.other.org.\n"));
+
+        let read = ZoneDefinition::from_yaml(&yaml).unwrap();
+        let mut expected = definition.rrsets.clone();
+        let mut actual = read.rrsets.clone();
+        for rrsets in [&mut expected, &mut actual] {
+            rrsets.sort_by(|a, b| {
+                (&a.name, a.type_field.as_str()).cmp(&(&b.name, b.type_field.as_str()))
+            });
+        }
+        assert_eq!(actual, expected);
+        assert_eq!(read.to_yaml(), yaml);
+
+        let mut shuffled = definition;
+        shuffled.rrsets.reverse();
+        assert_eq!(shuffled.to_yaml(), yaml);
+    }
+
+    #[test]
+    fn read_hand_written() {
+        let yaml = r#"
+---
+# managed by the platform team
+name: Example.com
+rrsets:
+- name: www   # relative to the zone
+  type: CNAME
+  ttl: 3600
+  records: ["web.example.net."]
+"#;
+        let definition = ZoneDefinition::from_yaml(yaml).unwrap();
+        assert_eq!(definition.name, "example.com.");
+        assert_eq!(definition.kind, None);
+        assert_eq!(definition.rrsets[0].name, "www.example.com.");
+        assert_eq!(
+            definition.rrsets[0].records,
+            [Record::new("web.example.net.")]
+        );
+
+        let yaml = yaml.replace(
+            r#"["web.example.net."]"#,
+            "\n  - \"web.example.net.\"  # external",
+        );
+        assert_eq!(ZoneDefinition::from_yaml(&yaml).unwrap(), definition);
+    }
+
+    #[test]
+    fn reject_invalid_yaml() {
+        let error = ZoneDefinition::from_yaml("name: example.com.\n  kind: Native\n");
+        assert!(matches!(error, Err(YamlError::Syntax { line: 2, .. })));
+        let error = ZoneDefinition::from_yaml("name: example.com.\nname: example.org.\n");
+        assert!(matches!(error, Err(YamlError::Syntax { .. })));
+        let error = ZoneDefinition::from_yaml("name: example.com.\nrrset: []\n");
+        assert!(matches!(error, Err(YamlError::Invalid(_))));
+        let error = ZoneDefinition::from_yaml("name: 'example.com.\n");
+        assert!(matches!(error, Err(YamlError::Syntax { .. })));
+    }
+}
@@ -0,0 +1,449 @@
+//! Parse and write zone files in the BIND format, e.g. as returned by
+//! [`ZoneClient::export`](crate::zones::ZoneClient::export).
+//!
+//! The parser supports the `$TTL` and `$ORIGIN` directives, relative names,
+//! `@`, records spanning multiple lines in parentheses, quoted strings and
+//! comments. `$INCLUDE` and `$GENERATE` are not supported.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use thiserror::Error;
+
+use crate::record_type::RecordType;
+use crate::zones::{absolute_name, RRSet, Record};
+
+/// Returned when a zone file cannot be parsed
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct ZoneFileError {
+    /// The line the erroneous entry starts on, starting at 1
+    pub line: usize,
+    /// What is wrong with the entry
+    pub message: String,
+}
+
+/// An entry of a zone file, after joining lines in parentheses and removing
+/// comments
+struct Entry {
+    line: usize,
+    /// Whether the entry starts with whitespace, i.e. has no owner name
+    inherits_owner: bool,
+    tokens: Vec<String>,
+}
+
+/// Parse the records of a zone file into rrsets. Relative names are
+/// qualified with `origin` until it is changed by an `$ORIGIN` directive.
+///
+/// Records of the same name and type are merged into one RRSet, which gets
+/// the TTL of its first record. Names are returned in lowercase.
+pub fn parse(text: &str, origin: &str) -> Result<Vec<RRSet>, ZoneFileError> {
+    let mut origin = absolute_name(origin);
+    let mut default_ttl = None;
+    let mut last_ttl = None;
+    let mut last_owner: Option<String> = None;
+
+    let mut rrsets: Vec<RRSet> = Vec::new();
+    let mut index: HashMap<(String, RecordType), usize> = HashMap::new();
+
+    for entry in entries(text)? {
+        let line = entry.line;
+        let error = |message: &str| ZoneFileError {
+            line,
+            message: message.to_string(),
+        };
+        let mut tokens = entry.tokens.into_iter().peekable();
+
+        if !entry.inherits_owner {
+            if let Some(directive) = tokens.next_if(|token| token.starts_with('$')) {
+                let argument = tokens.next().ok_or_else(|| error("missing argument"))?;
+                match directive.to_ascii_uppercase().as_str() {
+                    "$TTL" => {
+                        default_ttl =
+                            Some(parse_ttl(&argument).ok_or_else(|| error("invalid TTL"))?)
+                    }
+                    "$ORIGIN" => origin = qualify(&argument, &origin).to_ascii_lowercase(),
+                    _ => return Err(error(&format!("unsupported directive {directive}"))),
+                }
+                continue;
+            }
+        }
+
+        let owner = if entry.inherits_owner {
+            last_owner
+                .clone()
+                .ok_or_else(|| error("missing owner name"))?
+        } else {
+            let owner = tokens.next().ok_or_else(|| error("missing owner name"))?;
+            qualify(&owner, &origin).to_ascii_lowercase()
+        };
+
+        // the TTL and class may appear in either order
+        let mut ttl = None;
+        while let Some(token) = tokens.peek() {
+            if is_class(token) {
+                if !token.eq_ignore_ascii_case("IN") {
+                    return Err(error(&format!("unsupported class {token}")));
+                }
+            } else if let Some(value) = parse_ttl(token) {
+                ttl = Some(value);
+            } else {
+                break;
+            }
+            tokens.next();
+        }
+
+        let rtype = RecordType::from(
+            tokens
+                .next()
+                .ok_or_else(|| error("missing record type"))?
+                .as_str(),
+        );
+        let mut rdata: Vec<String> = tokens.collect();
+        if rdata.is_empty() {
+            return Err(error("missing record data"));
+        }
+        qualify_rdata(&rtype, &mut rdata, &origin).map_err(|message| error(&message))?;
+
+        let ttl = ttl
+            .or(default_ttl)
+            .or(last_ttl)
+            .ok_or_else(|| error("missing TTL"))?;
+        last_ttl = Some(ttl);
+        last_owner = Some(owner.clone());
+
+        let record = Record {
+            content: rdata.join(" "),
+            disabled: Some(false),
+        };
+        match index.get(&(owner.clone(), rtype.clone())) {
+            Some(&i) => rrsets[i].records.push(record),
+            None => {
+                index.insert((owner.clone(), rtype.clone()), rrsets.len());
+                rrsets.push(RRSet {
+                    name: owner,
+                    type_field: rtype,
+                    ttl,
+                    changetype: None,
+                    records: vec![record],
+                    comments: None,
+                    extra: HashMap::new(),
+                });
+            }
+        }
+    }
+
+    Ok(rrsets)
+}
+
+/// Write rrsets as a zone file. Names within `origin` are written relative
+/// to it, and the SOA RRSet is written first. Disabled records are written
+/// as comments, so they are skipped when the file is parsed again.
+pub fn to_string(rrsets: &[RRSet], origin: &str) -> String {
+    let origin = absolute_name(origin);
+    let mut text = format!("$ORIGIN {origin}\n");
+
+    let (soa, others): (Vec<&RRSet>, Vec<&RRSet>) = rrsets
+        .iter()
+        .partition(|rrset| rrset.type_field == RecordType::SOA);
+    for rrset in soa.into_iter().chain(others) {
+        let name = relative_name(&absolute_name(&rrset.name), &origin);
+        for record in &rrset.records {
+            let disabled = if record.disabled.unwrap_or(false) {
+                "; "
+            } else {
+                ""
+            };
+            // writing to a String cannot fail
+            let _ = writeln!(
+                text,
+                "{disabled}{name}\t{}\tIN\t{}\t{}",
+                rrset.ttl, rrset.type_field, record.content
+            );
+        }
+    }
+
+    text
+}
+
+/// Split a zone file into entries
+fn entries(text: &str) -> Result<Vec<Entry>, ZoneFileError> {
+    let mut entries = Vec::new();
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut line = 1;
+    let mut entry_line = 1;
+    let mut at_line_start = true;
+    let mut inherits_owner = false;
+    let mut depth = 0;
+    let mut in_quotes = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if at_line_start && depth == 0 {
+            inherits_owner = c == ' ' || c == '\t';
+            entry_line = line;
+            at_line_start = false;
+        }
+
+        if in_quotes {
+            token.push(c);
+            match c {
+                '\\' => token.extend(chars.next()),
+                '"' => in_quotes = false,
+                '\n' => line += 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '\n' => {
+                finish(&mut tokens, &mut token);
+                line += 1;
+                if depth == 0 {
+                    if !tokens.is_empty() {
+                        entries.push(Entry {
+                            line: entry_line,
+                            inherits_owner,
+                            tokens: std::mem::take(&mut tokens),
+                        });
+                    }
+                    at_line_start = true;
+                }
+            }
+            ';' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '"' => {
+                in_quotes = true;
+                token.push(c);
+            }
+            '(' => {
+                finish(&mut tokens, &mut token);
+                depth += 1;
+            }
+            ')' => {
+                finish(&mut tokens, &mut token);
+                if depth == 0 {
+                    return Err(ZoneFileError {
+                        line,
+                        message: String::from("unbalanced parentheses"),
+                    });
+                }
+                depth -= 1;
+            }
+            ' ' | '\t' | '\r' => finish(&mut tokens, &mut token),
+            '\\' => {
+                token.push(c);
+                token.extend(chars.next());
+            }
+            _ => token.push(c),
+        }
+    }
+
+    if in_quotes || depth > 0 {
+        return Err(ZoneFileError {
+            line: entry_line,
+            message: String::from("unterminated entry"),
+        });
+    }
+    finish(&mut tokens, &mut token);
+    if !tokens.is_empty() {
+        entries.push(Entry {
+            line: entry_line,
+            inherits_owner,
+            tokens,
+        });
+    }
+
+    Ok(entries)
+}
+
+fn finish(tokens: &mut Vec<String>, token: &mut String) {
+    if !token.is_empty() {
+        tokens.push(std::mem::take(token));
+    }
+}
+
+fn is_class(token: &str) -> bool {
+    ["IN", "CH", "HS", "CS"]
+        .iter()
+        .any(|class| token.eq_ignore_ascii_case(class))
+}
+
+/// Parse a TTL in seconds, or with units like `1h30m`
+fn parse_ttl(token: &str) -> Option<u32> {
+    if let Ok(ttl) = token.parse() {
+        return Some(ttl);
+    }
+
+    let mut total: u32 = 0;
+    let mut value: Option<u32> = None;
+    for c in token.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            value = Some(value.unwrap_or(0).checked_mul(10)?.checked_add(digit)?);
+            continue;
+        }
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        total = total.checked_add(value.take()?.checked_mul(unit)?)?;
+    }
+
+    match value {
+        Some(_) => None,
+        None => Some(total),
+    }
+}
+
+/// Qualify a possibly relative name with `origin`
+pub(crate) fn qualify(name: &str, origin: &str) -> String {
+    if name == "@" {
+        origin.to_string()
+    } else if name.ends_with('.') {
+        name.to_string()
+    } else if origin == "." {
+        format!("{name}.")
+    } else {
+        format!("{name}.{origin}")
+    }
+}
+
+/// The shortest way to write `name` in a zone file with `origin`
+pub(crate) fn relative_name(name: &str, origin: &str) -> String {
+    if name == origin {
+        String::from("@")
+    } else {
+        match name.strip_suffix(origin).and_then(|n| n.strip_suffix('.')) {
+            Some(relative) if !relative.is_empty() => relative.to_string(),
+            _ => name.to_string(),
+        }
+    }
+}
+
+/// Qualify the names in the data of a record, and convert the times of an
+/// SOA record to seconds, as the PowerDNS API requires
+fn qualify_rdata(rtype: &RecordType, rdata: &mut [String], origin: &str) -> Result<(), String> {
+    let names: &[usize] = match rtype {
+        RecordType::CNAME
+        | RecordType::DNAME
+        | RecordType::NS
+        | RecordType::PTR
+        | RecordType::ALIAS => &[0],
+        RecordType::MX | RecordType::AFSDB => &[1],
+        RecordType::SRV => &[3],
+        RecordType::SOA => &[0, 1],
+        _ => &[],
+    };
+    for &i in names {
+        let name = rdata
+            .get_mut(i)
+            .ok_or_else(|| format!("incomplete {rtype} record"))?;
+        *name = qualify(name, origin);
+    }
+
+    if *rtype == RecordType::SOA {
+        if rdata.len() != 7 {
+            return Err(String::from("SOA record must have 7 fields"));
+        }
+        for field in &mut rdata[2..] {
+            *field = parse_ttl(field)
+                .ok_or_else(|| format!("invalid SOA field {field}"))?
+                .to_string();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record_type::RecordType;
+    use crate::zonefile::{parse, parse_ttl, to_string};
+
+    const ZONE: &str = r#"
+$TTL 1h
+$ORIGIN example.com.
+@   IN  SOA ns1 hostmaster (
+            2024010101 ; serial
+            3h 1h 1w
+            3600 )
+        IN  NS  ns1
+        IN  NS  ns2.example.net.
+        IN  MX  10 mail
+ns1 300 IN  A   192.0.2.1
+www     IN  A   192.0.2.2
+        IN  A   192.0.2.3
+txt     IN  TXT "v=spf1 -all" "a;b (c)"
+_sip._tcp IN SRV 10 60 5060 sip
+lb      IN  LUA A "ifportup(443, {'192.0.2.1', '192.0.2.2'})"
+cdn     IN  ALIAS cdn.example.net.
+apex    IN  ALIAS cdn
+"#;
+
+    #[test]
+    fn parse_zone() {
+        let rrsets = parse(ZONE, "example.com").unwrap();
+        assert_eq!(rrsets.len(), 10);
+
+        assert_eq!(rrsets[0].name, "example.com.");
+        assert_eq!(rrsets[0].type_field, RecordType::SOA);
+        assert_eq!(rrsets[0].ttl, 3600);
+        assert_eq!(
+            rrsets[0].records[0].content,
+            "ns1.example.com. hostmaster.example.com. 2024010101 10800 3600 604800 3600"
+        );
+
+        let ns: Vec<&str> = rrsets[1]
+            .records
+            .iter()
+            .map(|r| r.content.as_str())
+            .collect();
+        assert_eq!(ns, ["ns1.example.com.", "ns2.example.net."]);
+        assert_eq!(rrsets[2].records[0].content, "10 mail.example.com.");
+        assert_eq!(rrsets[3].ttl, 300);
+        assert_eq!(rrsets[4].records.len(), 2);
+        assert_eq!(rrsets[5].records[0].content, r#""v=spf1 -all" "a;b (c)""#);
+        assert_eq!(rrsets[6].name, "_sip._tcp.example.com.");
+        assert_eq!(
+            rrsets[7].records[0].content,
+            r#"A "ifportup(443, {'192.0.2.1', '192.0.2.2'})""#
+        );
+        assert_eq!(rrsets[9].records[0].content, "cdn.example.com.");
+    }
+
+    #[test]
+    fn round_trip() {
+        let rrsets = parse(ZONE, "example.com.").unwrap();
+        let text = to_string(&rrsets, "example.com.");
+        assert!(text.contains("www\t3600\tIN\tA\t192.0.2.2"));
+        assert_eq!(parse(&text, "example.com.").unwrap(), rrsets);
+    }
+
+    #[test]
+    fn invalid_zones() {
+        assert_eq!(
+            parse("www IN A 192.0.2.1", "example.com.")
+                .unwrap_err()
+                .line,
+            1
+        );
+        assert!(parse("$TTL 60\n\nwww IN A", "example.com.").is_err());
+        assert!(parse("$TTL 60\nwww CH A 192.0.2.1", "example.com.").is_err());
+        assert!(parse("$TTL 60\nwww IN A ( 192.0.2.1", "example.com.").is_err());
+    }
+
+    #[test]
+    fn ttl_units() {
+        assert_eq!(parse_ttl("300"), Some(300));
+        assert_eq!(parse_ttl("1h30m"), Some(5400));
+        assert_eq!(parse_ttl("1W"), Some(604800));
+        assert_eq!(parse_ttl("30x"), None);
+        assert_eq!(parse_ttl("1h30"), None);
+        assert_eq!(parse_ttl("A"), None);
+    }
+}
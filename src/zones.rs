@@ -1,14 +1,39 @@
-use addr::parse_domain_name;
-use reqwest::{StatusCode};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use addr::parse_dns_name;
+use futures_util::stream::{self, Stream, StreamExt};
+use reqwest::{header, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
+use crate::client::Request;
+use crate::diff::{ApplyOptions, ZoneDiff};
 use crate::Client;
 use crate::Error;
-use crate::error::PowerDNSResponseError;
+use crate::error::decode;
+use crate::json_stream::JsonArraySplitter;
+use crate::names::{DomainName, IntoZoneId};
+use crate::observer::ChangeKind;
+use crate::record_type::RecordType;
+use crate::tsigkeys::TsigKey;
 
 /// A Zone object represents an authoritative DNS Zone.
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+///
+/// All fields are optional, as the type mirrors the Zone object of the API
+/// and is used both for responses and for request payloads, e.g. by
+/// [`ZoneClient::get`] and [`ZoneClient::create`]. New code should prefer
+/// [`ZoneSummary`] and [`ZoneDetail`] for responses, which guarantee the
+/// fields the server always sets, and [`CreateZone`] for creating zones;
+/// both responses convert into a Zone.
 #[serde_with::skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[non_exhaustive]
+#[deprecated(
+    note = "use `ZoneSummary` or `ZoneDetail` for responses and `CreateZone` for requests"
+)]
 pub struct Zone {
     /// Opaque zone id (string), assigned by the server, should not be
     /// interpreted by the application. Guaranteed to be safe for embedding in
@@ -21,7 +46,7 @@ pub struct Zone {
     pub type_field: Option<String>,
     /// API endpoint for this zone
     pub url: Option<String>,
-    /// Zone kind, one of “Native”, “Master”, “Slave”
+    /// Zone kind, one of “Native”, “Master”, “Slave”, “Producer”, “Consumer”
     pub kind: Option<ZoneKind>,
     /// RRSets in this zone (for zones/{zone_id} endpoint only; omitted during
     /// GET on the …/zones list endpoint)
@@ -63,20 +88,459 @@ pub struct Zone {
     pub master_tsig_key_ids: Option<Vec<String>>,
     /// The id of the TSIG keys used for slave operation in this zone
     pub slave_tsig_key_ids: Option<Vec<String>>,
+    /// The catalog this zone is a member of
+    pub catalog: Option<String>,
+    /// Fields not known to this crate, e.g. ones added by newer servers.
+    /// Always empty with the `strict` feature, which rejects them instead.
+    #[cfg_attr(not(feature = "strict"), serde(flatten))]
+    #[cfg_attr(feature = "strict", serde(skip))]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// A zone as returned by the zone list, with the fields the server always
+/// sets. Unlike [`Zone`], which doubles as a request payload, the id, name,
+/// kind and serial are guaranteed to be present.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ZoneSummary {
+    /// Opaque zone id, safe for embedding in URLs
+    pub id: String,
+    /// Name of the zone, with a trailing dot
+    pub name: String,
+    /// Zone kind, e.g. Native or Master
+    pub kind: ZoneKind,
+    /// The SOA serial number
+    pub serial: u32,
+    /// API endpoint for this zone
+    pub url: Option<String>,
+    /// The SOA serial notifications have been sent out for
+    pub notified_serial: Option<u32>,
+    /// The SOA serial as seen in query responses
+    pub edited_serial: Option<u32>,
+    /// IP addresses configured as a master for this zone (Slave zones only)
+    pub masters: Option<Vec<String>>,
+    /// Whether or not this zone is DNSSEC signed
+    pub dnssec: Option<bool>,
+    /// The NSEC3PARAM record
+    pub nsec3param: Option<String>,
+    /// Whether or not the zone uses NSEC3 narrow
+    pub nsec3narrow: Option<bool>,
+    /// Whether or not the zone is pre-signed
+    pub presigned: Option<bool>,
+    /// The SOA-EDIT metadata item
+    pub soa_edit: Option<String>,
+    /// The SOA-EDIT-API metadata item
+    pub soa_edit_api: Option<String>,
+    /// Whether or not the zone will be rectified on data changes via the API
+    pub api_rectify: Option<bool>,
+    /// Its value is defined by local policy
+    pub account: Option<String>,
+    /// The id of the TSIG keys used for master operation in this zone
+    pub master_tsig_key_ids: Option<Vec<String>>,
+    /// The id of the TSIG keys used for slave operation in this zone
+    pub slave_tsig_key_ids: Option<Vec<String>>,
+    /// The catalog this zone is a member of
+    pub catalog: Option<String>,
 }
 
+/// A zone as returned by [`ZoneClient::get_detail`], with its rrsets
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct ZoneDetail {
+    #[serde(flatten)]
+    pub summary: ZoneSummary,
+    pub rrsets: Vec<RRSet>,
+}
+
+#[allow(deprecated)]
+impl From<ZoneSummary> for Zone {
+    fn from(summary: ZoneSummary) -> Self {
+        Zone {
+            id: Some(summary.id),
+            name: Some(summary.name),
+            type_field: Some(String::from("Zone")),
+            url: summary.url,
+            kind: Some(summary.kind),
+            rrsets: None,
+            serial: Some(summary.serial),
+            notified_serial: summary.notified_serial,
+            edited_serial: summary.edited_serial,
+            masters: summary.masters,
+            dnssec: summary.dnssec,
+            nsec3param: summary.nsec3param,
+            nsec3narrow: summary.nsec3narrow,
+            presigned: summary.presigned,
+            soa_edit: summary.soa_edit,
+            soa_edit_api: summary.soa_edit_api,
+            api_rectify: summary.api_rectify,
+            zone: None,
+            account: summary.account,
+            nameservers: None,
+            master_tsig_key_ids: summary.master_tsig_key_ids,
+            slave_tsig_key_ids: summary.slave_tsig_key_ids,
+            catalog: summary.catalog,
+            extra: HashMap::new(),
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<ZoneDetail> for Zone {
+    fn from(detail: ZoneDetail) -> Self {
+        Zone {
+            rrsets: Some(detail.rrsets),
+            ..detail.summary.into()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ZoneKind {
     Native,
     Master,
     Slave,
+    Producer,
+    Consumer,
+}
+
+
+#[allow(deprecated)]
+impl Zone {
+    /// Start building a creation payload for the zone `name`
+    pub fn builder(name: &str) -> ZoneBuilder {
+        ZoneBuilder::new(name)
+    }
+}
+
+/// Returned when a ZoneBuilder does not describe a valid zone
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ZoneBuilderError {
+    #[error("name is not absolute, it must end with a dot: {0}")]
+    MissingTrailingDot(String),
+
+    #[error("zone kind must be set")]
+    MissingKind,
+
+    #[error("{0:?} zones need at least one nameserver")]
+    NoNameservers(ZoneKind),
+
+    #[error("{0:?} zones need at least one master")]
+    NoMasters(ZoneKind),
+}
+
+/// Builds a Zone to be passed to [`ZoneClient::create`], validating it
+/// before any request is made.
+#[derive(Debug, Clone)]
+#[allow(deprecated)]
+pub struct ZoneBuilder {
+    zone: Zone,
+}
+
+#[allow(deprecated)]
+impl ZoneBuilder {
+    pub fn new(name: &str) -> Self {
+        ZoneBuilder {
+            zone: Zone {
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Set the zone kind
+    pub fn kind(mut self, kind: ZoneKind) -> Self {
+        self.zone.kind = Some(kind);
+        self
+    }
+
+    /// Add nameservers to the zone, including the trailing dot
+    pub fn nameservers<I, S>(mut self, nameservers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.zone
+            .nameservers
+            .get_or_insert_with(Vec::new)
+            .extend(nameservers.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add masters to a Slave or Consumer zone
+    pub fn masters<I, S>(mut self, masters: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.zone
+            .masters
+            .get_or_insert_with(Vec::new)
+            .extend(masters.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add an RRSet to populate the zone with
+    pub fn rrset(mut self, rrset: RRSet) -> Self {
+        self.zone.rrsets.get_or_insert_with(Vec::new).push(rrset);
+        self
+    }
+
+    /// Set the account of the zone
+    pub fn account(mut self, account: &str) -> Self {
+        self.zone.account = Some(account.to_string());
+        self
+    }
+
+    /// Set the SOA-EDIT-API metadata item
+    pub fn soa_edit_api(mut self, soa_edit_api: &str) -> Self {
+        self.zone.soa_edit_api = Some(soa_edit_api.to_string());
+        self
+    }
+
+    /// Set whether the zone is rectified on data changes via the API
+    pub fn api_rectify(mut self, api_rectify: bool) -> Self {
+        self.zone.api_rectify = Some(api_rectify);
+        self
+    }
+
+    /// Set whether the zone is DNSSEC signed on creation
+    pub fn dnssec(mut self, dnssec: bool) -> Self {
+        self.zone.dnssec = Some(dnssec);
+        self
+    }
+
+    /// Validate and return the creation payload
+    pub fn build(self) -> Result<Zone, ZoneBuilderError> {
+        let zone = self.zone;
+        let name = zone.name.as_deref().unwrap_or_default();
+
+        let rrset_names = zone.rrsets.iter().flatten().map(|rrset| rrset.name.as_str());
+        let nameservers = zone.nameservers.iter().flatten().map(String::as_str);
+        if let Some(name) = std::iter::once(name)
+            .chain(rrset_names)
+            .chain(nameservers)
+            .find(|name| !name.ends_with('.'))
+        {
+            return Err(ZoneBuilderError::MissingTrailingDot(name.to_string()));
+        }
+
+        let kind = zone.kind.ok_or(ZoneBuilderError::MissingKind)?;
+        match kind {
+            ZoneKind::Master => {
+                let has_nameservers = zone.nameservers.as_ref().is_some_and(|ns| !ns.is_empty());
+                let has_ns_rrset = zone.rrsets.iter().flatten().any(|rrset| {
+                    rrset.type_field == RecordType::NS && rrset.name.eq_ignore_ascii_case(name)
+                });
+                if !has_nameservers && !has_ns_rrset {
+                    return Err(ZoneBuilderError::NoNameservers(kind));
+                }
+            }
+            ZoneKind::Slave | ZoneKind::Consumer => {
+                if zone.masters.as_ref().is_none_or(|masters| masters.is_empty()) {
+                    return Err(ZoneBuilderError::NoMasters(kind));
+                }
+            }
+            ZoneKind::Native | ZoneKind::Producer => {}
+        }
+
+        Ok(zone)
+    }
+}
+
+/// The payload of [`ZoneClient::create_zone`]. Unlike [`Zone`], it cannot
+/// carry fields that are set by the server, like the serial or url.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CreateZone {
+    /// Name of the zone, with a trailing dot
+    pub name: String,
+    pub kind: ZoneKind,
+    /// Nameserver names, including the trailing dot. Not required for Slave
+    /// zones, or when `rrsets` contains the NS records.
+    pub nameservers: Vec<String>,
+    /// IP addresses of the masters of a Slave or Consumer zone
+    pub masters: Option<Vec<String>>,
+    /// RRSets to populate the zone with
+    pub rrsets: Option<Vec<RRSet>>,
+    /// A BIND-style zone file to populate the zone with
+    pub zone: Option<String>,
+    /// Its value is defined by local policy
+    pub account: Option<String>,
+    /// Whether the zone is DNSSEC signed on creation
+    pub dnssec: Option<bool>,
+    /// The NSEC3PARAM record, when signing with NSEC3
+    pub nsec3param: Option<String>,
+    /// Whether or not the zone is pre-signed
+    pub presigned: Option<bool>,
+    /// The SOA-EDIT metadata item
+    pub soa_edit: Option<String>,
+    /// The SOA-EDIT-API metadata item
+    pub soa_edit_api: Option<String>,
+    /// Whether or not the zone will be rectified on data changes via the API
+    pub api_rectify: Option<bool>,
+    /// The id of the TSIG keys used for master operation in this zone
+    pub master_tsig_key_ids: Option<Vec<String>>,
+    /// The id of the TSIG keys used for slave operation in this zone
+    pub slave_tsig_key_ids: Option<Vec<String>>,
+    /// The catalog this zone is a member of
+    pub catalog: Option<String>,
 }
 
+impl CreateZone {
+    /// A payload for the zone `name` of the given kind, with all optional
+    /// fields unset
+    pub fn new(name: &str, kind: ZoneKind) -> Self {
+        CreateZone {
+            name: name.to_string(),
+            kind,
+            nameservers: Vec::new(),
+            masters: None,
+            rrsets: None,
+            zone: None,
+            account: None,
+            dnssec: None,
+            nsec3param: None,
+            presigned: None,
+            soa_edit: None,
+            soa_edit_api: None,
+            api_rectify: None,
+            master_tsig_key_ids: None,
+            slave_tsig_key_ids: None,
+            catalog: None,
+        }
+    }
+}
+
+/// The payload of [`ZoneClient::update`]
+pub type UpdateZoneSettings = ZoneSettings;
 
 /// PatchZones used to create zones with PATCH method.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct PatchZone {
-    pub rrsets: Vec<RRSet>
+    pub rrsets: Vec<RRSetUpdate>
+}
+
+impl PatchZone {
+    /// Make the names of all changed rrsets absolute and lowercase, the form
+    /// the server stores them in
+    pub fn normalize_names(&mut self) {
+        for update in &mut self.rrsets {
+            let name = match update {
+                RRSetUpdate::Replace(rrset) => &mut rrset.name,
+                RRSetUpdate::Delete { name, .. } => name,
+            };
+            *name = absolute_name(name);
+        }
+    }
+}
+
+/// A change to one RRSet in a [`PatchZone`]. A deletion cannot carry a TTL,
+/// records or comments, which the server would reject.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "changetype", rename_all = "UPPERCASE")]
+pub enum RRSetUpdate {
+    /// Replace the records, and the comments if set, of an RRSet
+    Replace(ReplaceRRSet),
+    /// Delete an RRSet with all its records and comments
+    Delete {
+        name: String,
+        #[serde(rename = "type")]
+        rtype: RecordType,
+    },
+}
+
+/// The new state of an RRSet replaced by [`RRSetUpdate::Replace`]
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ReplaceRRSet {
+    /// Name for record set, with a trailing dot
+    pub name: String,
+    #[serde(rename = "type")]
+    pub rtype: RecordType,
+    /// DNS TTL of the records, in seconds
+    pub ttl: u32,
+    /// The new records. An empty list deletes all records and comments.
+    pub records: Vec<Record>,
+    /// The new comments, existing comments are kept when unset
+    pub comments: Option<Vec<Comment>>,
+}
+
+impl RRSetUpdate {
+    /// Replace the RRSet with the name and type of `rrset` by it. The
+    /// changetype of `rrset` is ignored.
+    pub fn replace(rrset: RRSet) -> Self {
+        RRSetUpdate::Replace(ReplaceRRSet {
+            name: rrset.name,
+            rtype: rrset.type_field,
+            ttl: rrset.ttl,
+            records: rrset.records,
+            comments: rrset.comments,
+        })
+    }
+
+    /// Delete the RRSet with the given name and type
+    pub fn delete(name: &str, rtype: RecordType) -> Self {
+        RRSetUpdate::Delete {
+            name: name.to_string(),
+            rtype,
+        }
+    }
+
+    /// The name of the changed RRSet
+    pub fn name(&self) -> &str {
+        match self {
+            RRSetUpdate::Replace(rrset) => &rrset.name,
+            RRSetUpdate::Delete { name, .. } => name,
+        }
+    }
+
+    /// The type of the changed RRSet
+    pub fn rtype(&self) -> &RecordType {
+        match self {
+            RRSetUpdate::Replace(rrset) => &rrset.rtype,
+            RRSetUpdate::Delete { rtype, .. } => rtype,
+        }
+    }
+
+    /// The event observers receive once the update was applied
+    fn into_change(self) -> ChangeKind {
+        match self {
+            RRSetUpdate::Replace(rrset) => ChangeKind::RRSetReplaced {
+                name: rrset.name,
+                rtype: rrset.rtype,
+                ttl: rrset.ttl,
+                records: rrset.records,
+            },
+            RRSetUpdate::Delete { name, rtype } => ChangeKind::RRSetDeleted { name, rtype },
+        }
+    }
+}
+
+/// ZoneSettings used to modify the basic settings of a zone with PUT method.
+/// Fields left unset are not changed on the server.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ZoneSettings {
+    /// Zone kind, one of “Native”, “Master”, “Slave”, “Producer”, “Consumer”
+    pub kind: Option<ZoneKind>,
+    /// List of IP addresses configured as a master for this zone (“Slave” type
+    /// zones only)
+    pub masters: Option<Vec<String>>,
+    /// MAY be set. Its value is defined by local policy
+    pub account: Option<String>,
+    /// The SOA-EDIT metadata item
+    pub soa_edit: Option<String>,
+    /// The SOA-EDIT-API metadata item
+    pub soa_edit_api: Option<String>,
+    /// Whether or not the zone will be rectified on data changes via the API
+    pub api_rectify: Option<bool>,
+    /// The id of the TSIG keys used for master operation in this zone
+    pub master_tsig_key_ids: Option<Vec<String>>,
+    /// The id of the TSIG keys used for slave operation in this zone
+    pub slave_tsig_key_ids: Option<Vec<String>>,
+    /// The catalog this zone is a member of
+    pub catalog: Option<String>,
 }
 
 // impl ZoneKind {
@@ -90,14 +554,16 @@ pub struct PatchZone {
 // }
 
 /// This represents a Resource Record Set (all records with the same name and type).
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+#[non_exhaustive]
 pub struct RRSet {
     /// Name for record set (e.g. “www.powerdns.com.”)
     pub name: String,
     #[serde(rename = "type")]
     /// Type of this record (e.g. “A”, “PTR”, “MX”)
-    pub type_field: String,
+    pub type_field: RecordType,
     /// DNS TTL of the records, in seconds. MUST NOT be included when changetype
     /// is set to “DELETE”.
     pub ttl: u32,
@@ -109,7 +575,7 @@ pub struct RRSet {
     /// existing comments will be deleted as well. When comments is present, all
     /// existing comments for the RRs matching name and type will be deleted,
     /// and then new comments given in comments will be created.
-    pub changetype: Option<String>,
+    pub changetype: Option<ChangeType>,
     /// All records in this RRSet. When updating Records, this is the list of
     /// new records (replacing the old ones). Must be empty when changetype is
     /// set to DELETE. An empty list results in deletion of all records (and
@@ -119,11 +585,170 @@ pub struct RRSet {
     /// empty list results in deletion of all comments. modified_at is optional
     /// and defaults to the current server time.
     pub comments: Option<Vec<Comment>>,
+    /// Fields not known to this crate, e.g. ones added by newer servers.
+    /// Always empty with the `strict` feature, which rejects them instead.
+    #[cfg_attr(not(feature = "strict"), serde(flatten))]
+    #[cfg_attr(feature = "strict", serde(skip))]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
-/// The RREntry object represents a single record.
+impl RRSet {
+    /// An RRSet with the given records, and without changetype and comments
+    pub fn new(name: &str, rtype: RecordType, ttl: u32, records: Vec<Record>) -> Self {
+        RRSet {
+            name: name.to_string(),
+            type_field: rtype,
+            ttl,
+            changetype: None,
+            records,
+            comments: None,
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Sets the changetype from its string representation, e.g. “REPLACE”.
+    /// Surrounding whitespace and case are ignored. Returns the input back as
+    /// an error if it is not a valid changetype.
+    #[deprecated(note = "set the `changetype` field to a `ChangeType` instead")]
+    pub fn set_changetype(&mut self, changetype: &str) -> Result<(), String> {
+        self.changetype = Some(match changetype.trim().to_ascii_uppercase().as_str() {
+            "REPLACE" => ChangeType::Replace,
+            "DELETE" => ChangeType::Delete,
+            _ => return Err(changetype.to_string()),
+        });
+        Ok(())
+    }
+
+    /// Whether this RRSet, as stored on the server, already has the TTL,
+    /// records and comments of `desired`. The order of records and comments
+    /// is ignored, as are the comments when `desired` does not set them.
+    pub(crate) fn matches(&self, desired: &RRSet) -> bool {
+        fn records(rrset: &RRSet) -> Vec<(&str, bool)> {
+            let mut records: Vec<_> = rrset
+                .records
+                .iter()
+                .map(|r| (r.content.as_str(), r.disabled.unwrap_or(false)))
+                .collect();
+            records.sort_unstable();
+            records
+        }
+        fn comments(comments: &[Comment]) -> Vec<(&str, &str)> {
+            let mut comments: Vec<_> = comments
+                .iter()
+                .map(|c| (c.content.as_str(), c.account.as_str()))
+                .collect();
+            comments.sort_unstable();
+            comments
+        }
+
+        self.ttl == desired.ttl
+            && records(self) == records(desired)
+            && desired.comments.as_ref().is_none_or(|desired| {
+                comments(desired) == comments(self.comments.as_deref().unwrap_or_default())
+            })
+    }
+}
+
+/// An RRSet as sent in a [`PatchZone`], where the changetype is required
+#[deprecated(note = "use `RRSetUpdate`, which cannot express invalid deletions")]
+#[serde_with::skip_serializing_none]
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RRSetChange {
+    /// Name for record set, with a trailing dot
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_field: RecordType,
+    /// DNS TTL of the records, in seconds. Not sent with DELETE.
+    pub ttl: Option<u32>,
+    pub changetype: ChangeType,
+    /// The new records of the RRSet, empty with DELETE
+    pub records: Vec<Record>,
+    /// The new comments of the RRSet, existing comments are kept when unset
+    pub comments: Option<Vec<Comment>>,
+}
+
+#[allow(deprecated)]
+impl RRSetChange {
+    /// Replace the records, and comments if set, of the RRSet
+    pub fn replace(rrset: RRSet) -> Self {
+        RRSetChange {
+            name: rrset.name,
+            type_field: rrset.type_field,
+            ttl: Some(rrset.ttl),
+            changetype: ChangeType::Replace,
+            records: rrset.records,
+            comments: rrset.comments,
+        }
+    }
+
+    /// Delete the RRSet with all its records and comments
+    pub fn delete(name: &str, type_field: RecordType) -> Self {
+        RRSetChange {
+            name: name.to_string(),
+            type_field,
+            ttl: None,
+            changetype: ChangeType::Delete,
+            records: Vec::new(),
+            comments: None,
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<RRSetChange> for RRSet {
+    fn from(change: RRSetChange) -> Self {
+        RRSet {
+            name: change.name,
+            type_field: change.type_field,
+            ttl: change.ttl.unwrap_or_default(),
+            changetype: Some(change.changetype),
+            records: change.records,
+            comments: change.comments,
+            extra: HashMap::new(),
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<RRSetChange> for RRSetUpdate {
+    fn from(change: RRSetChange) -> Self {
+        match change.changetype {
+            ChangeType::Replace => RRSetUpdate::replace(change.into()),
+            ChangeType::Delete => RRSetUpdate::Delete {
+                name: change.name,
+                rtype: change.type_field,
+            },
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl FromIterator<RRSetChange> for PatchZone {
+    fn from_iter<I: IntoIterator<Item = RRSetChange>>(changes: I) -> Self {
+        changes.into_iter().map(RRSetUpdate::from).collect()
+    }
+}
+
+impl FromIterator<RRSetUpdate> for PatchZone {
+    fn from_iter<I: IntoIterator<Item = RRSetUpdate>>(updates: I) -> Self {
+        PatchZone {
+            rrsets: updates.into_iter().collect(),
+        }
+    }
+}
+
+/// The kind of change applied to an RRSet when patching a zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ChangeType {
+    Replace,
+    Delete,
+}
+
+/// The RREntry object represents a single record.
 #[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct Record {
     /// The content of this record
     pub content: String,
@@ -132,97 +757,614 @@ pub struct Record {
     pub disabled: Option<bool>,
 }
 
+impl Record {
+    /// An enabled record with the given content
+    pub fn new(content: &str) -> Self {
+        Record {
+            content: content.to_string(),
+            disabled: None,
+        }
+    }
+}
+
 /// A comment about an RRSet.
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct Comment {
     /// The actual comment
     pub content: String,
     /// Name of an account that added the comment
     pub account: String,
-    /// Timestamp of the last change to the comment
-    pub modified_at: u32,
+    /// Timestamp of the last change to the comment, in seconds since the
+    /// epoch. Defaults to the current server time when unset on write.
+    pub modified_at: Option<u64>,
+}
+
+impl Comment {
+    /// A comment to add to an RRSet. The modification time is left unset, so
+    /// the server fills in the current time.
+    pub fn new(content: &str, account: &str) -> Self {
+        Comment {
+            content: content.to_string(),
+            account: account.to_string(),
+            modified_at: None,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Comment {
+    /// The time of the last change to the comment
+    pub fn modified_at_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let modified_at = i64::try_from(self.modified_at?).ok()?;
+        chrono::DateTime::from_timestamp(modified_at, 0)
+    }
+
+    /// Set the time of the last change to the comment, times before the
+    /// epoch are sent as unset
+    pub fn set_modified_at_utc(&mut self, modified_at: chrono::DateTime<chrono::Utc>) {
+        self.modified_at = u64::try_from(modified_at.timestamp()).ok();
+    }
+}
+
+/// The state of a stream returned by [`ZoneClient::list_stream`]
+enum ListStream<'a> {
+    /// The request could not be made, e.g. because of an invalid name
+    Failed(Error),
+    Request(Request<'a>),
+    Body(reqwest::Response, JsonArraySplitter),
+    Done,
 }
 
+#[allow(deprecated)]
+impl<'a> ListStream<'a> {
+    async fn next(mut self) -> Option<(Result<Zone, Error>, Self)> {
+        loop {
+            self = match self {
+                ListStream::Done => return None,
+                ListStream::Failed(e) => return Some((Err(e), ListStream::Done)),
+                ListStream::Request(request) => match request.send().await {
+                    Ok(resp) if resp.status().is_success() => {
+                        ListStream::Body(resp, JsonArraySplitter::default())
+                    }
+                    Ok(resp) => {
+                        return Some((Err(Error::from_response(resp).await), ListStream::Done));
+                    }
+                    Err(e) => return Some((Err(e), ListStream::Done)),
+                },
+                ListStream::Body(mut resp, mut splitter) => {
+                    match splitter.next_element() {
+                        Ok(Some(zone)) => {
+                            let zone = serde_json::from_slice(&zone).map_err(Error::from);
+                            return Some((zone, ListStream::Body(resp, splitter)));
+                        }
+                        Ok(None) => {}
+                        Err(e) => return Some((Err(e.into()), ListStream::Done)),
+                    }
+                    match resp.chunk().await {
+                        Ok(Some(chunk)) => {
+                            splitter.extend(&chunk);
+                            ListStream::Body(resp, splitter)
+                        }
+                        Ok(None) if splitter.is_finished() => return None,
+                        Ok(None) => {
+                            let e = <serde_json::Error as serde::de::Error>::custom(
+                                "unexpected end of zone list",
+                            );
+                            return Some((Err(e.into()), ListStream::Done));
+                        }
+                        Err(e) => return Some((Err(e.into()), ListStream::Done)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Restricts the zones returned by [`ZoneClient::list_filtered`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ZoneListFilter {
+    /// Only return the zone with this name
+    pub zone: Option<String>,
+    /// Whether the server fills in the `dnssec` and `edited_serial` fields.
+    /// Setting this to false makes listing a lot faster on large servers.
+    pub dnssec: Option<bool>,
+    /// Only return zones with this account. Filtered by the client, as the
+    /// server does not support it.
+    pub account: Option<String>,
+}
+
+/// Restricts the data returned by [`ZoneClient::get_with`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GetZoneOptions {
+    /// Whether the server includes the RRSets, defaults to true. Without
+    /// them, getting a large zone is a lot faster.
+    pub rrsets: Option<bool>,
+    /// Only include the RRSets with this name
+    pub rrset_name: Option<String>,
+    /// Only include the RRSet of this type, requires `rrset_name`
+    pub rrset_type: Option<RecordType>,
+}
+
+/// How often [`ZoneClient::wait_for_serial`] checks the serial
+const SERIAL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 pub struct ZoneClient<'a> {
     api_client: &'a Client,
 }
 
+#[allow(deprecated)]
 impl<'a> ZoneClient<'a> {
     pub fn new(api_client: &'a Client) -> Self {
         ZoneClient { api_client }
     }
 
+    /// The client used for requests, for workflows spanning several APIs
+    pub(crate) fn api_client(&self) -> &'a Client {
+        self.api_client
+    }
+
     /// List all Zones in a server
     pub async fn list(&self) -> Result<Vec<Zone>, Error> {
         let resp = self
             .api_client
-            .http_client
             .get(format!(
-                "{}/api/v1/servers/{}/zones",
-                self.api_client.base_url, self.api_client.server_name
+                "{}/servers/{}/zones",
+                self.api_client.api_url, self.api_client.server_name
             ))
             .send()
-            .await
-            .unwrap();
+            .await?;
 
         if resp.status().is_success() {
-            Ok(resp.json::<Vec<Zone>>().await?)
+            Ok(decode::<Vec<Zone>>(resp).await?)
         } else {
-            Err(resp.json::<PowerDNSResponseError>().await?)?
+            Err(Error::from_response(resp).await)
         }
     }
 
-    /// Get a zone managed by a server
-    pub async fn get(&self, zone_id: &str) -> Result<Zone, Error> {
-        let zone_id = canonicalize_domain(zone_id).unwrap();
+    /// List all Zones in a server, see [`ZoneSummary`]
+    pub async fn list_summaries(&self) -> Result<Vec<ZoneSummary>, Error> {
         let resp = self
             .api_client
-            .http_client
             .get(format!(
-                "{}/api/v1/servers/{}/zones/{zone_id}",
-                self.api_client.base_url, self.api_client.server_name
+                "{}/servers/{}/zones",
+                self.api_client.api_url, self.api_client.server_name
             ))
             .send()
-            .await
-            .unwrap();
+            .await?;
 
         if resp.status().is_success() {
-            Ok(resp.json::<Zone>().await?)
+            Ok(decode::<Vec<ZoneSummary>>(resp).await?)
         } else {
-            Err(resp.json::<PowerDNSResponseError>().await?)?
+            Err(Error::from_response(resp).await)
         }
     }
 
-    /// Deletes this zone, all attached metadata and rrsets.
-    pub async fn delete(&self, zone_id: &str) -> Result<(), Error> {
-        let zone_id = canonicalize_domain(zone_id).unwrap();
-        let resp = self
-            .api_client
-            .http_client
-            .delete(format!(
-                "{}/api/v1/servers/{}/zones/{zone_id}",
-                self.api_client.base_url, self.api_client.server_name
-            ))
-            .send()
-            .await
-            .unwrap();
+    /// List the Zones in a server matching `filter`
+    pub async fn list_filtered(&self, filter: &ZoneListFilter) -> Result<Vec<Zone>, Error> {
+        let mut request = self.api_client.get(format!(
+            "{}/servers/{}/zones",
+            self.api_client.api_url, self.api_client.server_name
+        ));
+        if let Some(zone) = &filter.zone {
+            let zone = canonicalize_domain(zone).map_err(|source| Error::InvalidDomain {
+                domain: zone.to_string(),
+                source,
+            })?;
+            request = request.query(&[("zone", zone)]);
+        }
+        if let Some(dnssec) = filter.dnssec {
+            request = request.query(&[("dnssec", dnssec)]);
+        }
+        let resp = request.send().await?;
 
         if resp.status().is_success() {
-            Ok(())
+            let mut zones = decode::<Vec<Zone>>(resp).await?;
+            // the server has no account filter
+            if let Some(account) = &filter.account {
+                zones.retain(|zone| zone.account.as_ref() == Some(account));
+            }
+            Ok(zones)
         } else {
-            Err(resp.json::<PowerDNSResponseError>().await?)?
+            Err(Error::from_response(resp).await)
         }
     }
 
+    /// List the Zones in a server as a stream, deserializing each zone as it
+    /// arrives instead of buffering the whole list. This bounds memory use on
+    /// servers with many zones.
+    ///
+    /// When `zone` is given, the server only returns the zone with that name.
+    /// An invalid name is returned as the only item of the stream.
+    pub fn list_stream(&self, zone: Option<&str>) -> impl Stream<Item = Result<Zone, Error>> + '_ {
+        let request = self.api_client.get(format!(
+            "{}/servers/{}/zones",
+            self.api_client.api_url, self.api_client.server_name
+        ));
+        let state = match zone.map(|zone| (zone, canonicalize_domain(zone))) {
+            None => ListStream::Request(request),
+            Some((_, Ok(zone))) => ListStream::Request(request.query(&[("zone", zone)])),
+            Some((zone, Err(source))) => ListStream::Failed(Error::InvalidDomain {
+                domain: zone.to_string(),
+                source,
+            }),
+        };
+
+        stream::unfold(state, ListStream::next)
+    }
+
+    /// Creates a new domain, returns the Zone on creation.
+    ///
+    /// The zone `name` and `kind` are required. `nameservers` MAY be given
+    /// for Native and Master zones, `masters` SHOULD be given for Slave zones,
+    /// and `rrsets` MAY be given to populate the zone on creation.
+    pub async fn create(&self, zone: Zone) -> Result<Zone, Error> {
+        self.post_zone(zone.name.as_deref().unwrap_or_default(), &zone).await
+    }
+
+    /// Creates a new zone from a [`CreateZone`] payload, which only holds the
+    /// fields a client may send. Returns the zone on creation.
+    pub async fn create_zone(&self, zone: &CreateZone) -> Result<ZoneDetail, Error> {
+        self.post_zone(&zone.name, zone).await
+    }
+
+    /// Create the zone `name` from `zone`, a [`Zone`] or [`CreateZone`]
+    async fn post_zone<T: DeserializeOwned>(
+        &self,
+        name: &str,
+        zone: &impl Serialize,
+    ) -> Result<T, Error> {
+        let response = self
+            .api_client
+            .post(format!(
+                "{}/servers/{}/zones",
+                self.api_client.api_url, self.api_client.server_name
+            ))
+            .json(zone)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 201 Created – A zone Returns: Zone object
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 409 Conflict – Zone already exists Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::CREATED => {
+                let zone = decode::<T>(response).await?;
+                self.api_client.emit(&absolute_name(name), ChangeKind::ZoneCreated);
+                Ok(zone)
+            },
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Creates a new zone with the records of a zone file in the BIND
+    /// format, which is parsed by the server. See also
+    /// [`crate::zonefile::parse`] to inspect or modify the records first.
+    pub async fn create_from_zonefile(
+        &self,
+        name: &str,
+        kind: ZoneKind,
+        zonefile: &str,
+    ) -> Result<Zone, Error> {
+        let name = canonicalize_domain(name).map_err(|source| Error::InvalidDomain {
+            domain: name.to_string(),
+            source,
+        })?;
+        self.create(Zone {
+            name: Some(name),
+            kind: Some(kind),
+            zone: Some(zonefile.to_string()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Get a zone managed by a server
+    pub async fn get(&self, zone_id: impl IntoZoneId) -> Result<Zone, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        self.fetch(&zone_id, &[]).await
+    }
+
+    /// Get a zone by its canonical id, passing `query` to the server
+    async fn fetch<T: DeserializeOwned>(
+        &self,
+        zone_id: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, Error> {
+        let resp = self
+            .api_client
+            .get(format!(
+                "{}/servers/{}/zones/{zone_id}",
+                self.api_client.api_url, self.api_client.server_name
+            ))
+            .query(query)
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            Ok(decode::<T>(resp).await?)
+        } else {
+            Err(Error::from_response(resp).await)
+        }
+    }
+
+    /// Get a zone, with only the RRSets selected by `options`
+    pub async fn get_with(
+        &self,
+        zone_id: impl IntoZoneId,
+        options: &GetZoneOptions,
+    ) -> Result<Zone, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let rrset_name = options.rrset_name.as_deref().map(absolute_name);
+        let mut query = Vec::new();
+        if let Some(rrsets) = options.rrsets {
+            query.push(("rrsets", if rrsets { "true" } else { "false" }));
+        }
+        if let Some(rrset_name) = &rrset_name {
+            query.push(("rrset_name", rrset_name));
+        }
+        if let Some(rrset_type) = &options.rrset_type {
+            query.push(("rrset_type", rrset_type.as_str()));
+        }
+        self.fetch(&zone_id, &query).await
+    }
+
+    /// Get a zone with its rrsets, see [`ZoneDetail`]
+    pub async fn get_detail(&self, zone_id: impl IntoZoneId) -> Result<ZoneDetail, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        self.fetch(&zone_id, &[]).await
+    }
+
+    /// Get many zones concurrently, keeping at most `concurrency` requests in
+    /// flight. Returns the result for every zone, keyed by the requested id.
+    pub async fn get_many<I>(
+        &self,
+        zone_ids: I,
+        concurrency: usize,
+    ) -> HashMap<String, Result<Zone, Error>>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        stream::iter(zone_ids)
+            .map(|zone_id| async move {
+                let zone_id = zone_id.into();
+                let zone = self.get(&zone_id).await;
+                (zone_id, zone)
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Get a zone only if its serial differs from `known_serial`, returning
+    /// None when it is unchanged.
+    ///
+    /// The serial is looked up in the zone list first, which is much smaller
+    /// than the zone with all its rrsets. This relies on the serial being
+    /// increased on every change, e.g. by the SOA-EDIT-API setting of the
+    /// zone.
+    pub async fn get_if_changed(
+        &self,
+        zone_id: impl IntoZoneId,
+        known_serial: u32,
+    ) -> Result<Option<Zone>, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+
+        match self.listed_serial(&zone_id).await? {
+            Some(serial) if serial == known_serial => Ok(None),
+            _ => self.get(&zone_id).await.map(Some),
+        }
+    }
+
+    /// Get a zone, reusing the copy fetched by an earlier call when its
+    /// serial has not changed since. See [`ZoneClient::get_if_changed`] for
+    /// how changes are detected.
+    ///
+    /// The cache belongs to the Client and keeps the last copy of every zone
+    /// fetched this way, replacing outdated copies but never evicting any,
+    /// so its size grows with the number of distinct zones. Create a new
+    /// Client to drop it.
+    pub async fn get_cached(&self, zone_id: impl IntoZoneId) -> Result<Zone, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+
+        if let Some(serial) = self.listed_serial(&zone_id).await? {
+            let cache = self.api_client.zone_cache.lock().unwrap_or_else(|e| e.into_inner());
+            let cached = cache.get(zone_id.as_str());
+            if let Some(zone) = cached.filter(|zone| zone.serial == Some(serial)) {
+                return Ok(zone.clone());
+            }
+        }
+
+        let zone = self.get(&zone_id).await?;
+        self.api_client
+            .zone_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(zone_id.into(), zone.clone());
+        Ok(zone)
+    }
+
+    /// Poll a zone until its serial reaches `expected_serial`, e.g. to
+    /// confirm a change has been transferred to a secondary server, using a
+    /// client for that server. Returns the serial seen last.
+    ///
+    /// A zone that does not exist yet is waited for as well. Fails with
+    /// [`Error::SerialTimeout`] when the serial is still lower after
+    /// `timeout`.
+    pub async fn wait_for_serial(
+        &self,
+        zone_id: impl IntoZoneId,
+        expected_serial: u32,
+        timeout: Duration,
+    ) -> Result<u32, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            let serial = self.listed_serial(&zone_id).await?;
+            if let Some(serial) = serial.filter(|serial| *serial >= expected_serial) {
+                return Ok(serial);
+            }
+
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                return Err(Error::SerialTimeout {
+                    zone_id: zone_id.into(),
+                    expected: expected_serial,
+                    current: serial,
+                });
+            }
+            self.api_client.sleep(SERIAL_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    /// The serial of a zone as reported by the zone list, None if the zone
+    /// is not listed
+    async fn listed_serial(&self, zone_id: &str) -> Result<Option<u32>, Error> {
+        let zones = self.list_stream(Some(zone_id));
+        futures_util::pin_mut!(zones);
+        while let Some(zone) = zones.next().await {
+            let zone = zone?;
+            if zone.id.as_deref() == Some(zone_id) {
+                return Ok(zone.serial);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Deletes this zone, all attached metadata and rrsets.
+    pub async fn delete(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let resp = self
+            .api_client
+            .delete(format!(
+                "{}/servers/{}/zones/{zone_id}",
+                self.api_client.api_url, self.api_client.server_name
+            ))
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            self.api_client.emit(&zone_id, ChangeKind::ZoneDeleted);
+            Ok(())
+        } else {
+            Err(Error::from_response(resp).await)
+        }
+    }
+
+    /// Modifies basic zone data. The only fields that will be changed are the
+    /// ones set in `settings`; rrsets cannot be changed this way.
+    pub async fn update(
+        &self,
+        zone_id: impl IntoZoneId,
+        settings: ZoneSettings,
+    ) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let response = self
+            .api_client
+            .put(
+                format!("{}/servers/{}/zones/{zone_id}",
+                        self.api_client.api_url,
+                        self.api_client.server_name,
+                ))
+            .json(&settings)
+            .send()
+            .await?;
+
+        match response.status() {
+            // 204 No Content – Returns 204 No Content on success.
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::NO_CONTENT => {
+                self.api_client.emit(&zone_id, ChangeKind::ZoneUpdated);
+                Ok(())
+            },
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Sets the TSIG keys used for master and slave operation in this zone.
+    pub async fn set_tsig_keys(
+        &self,
+        zone_id: impl IntoZoneId,
+        master_keys: &[TsigKey],
+        slave_keys: &[TsigKey],
+    ) -> Result<(), Error> {
+        let key_ids = |keys: &[TsigKey]| {
+            keys.iter()
+                .filter_map(|key| key.id.clone().or_else(|| key.name.clone()))
+                .collect()
+        };
+        let settings = ZoneSettings {
+            master_tsig_key_ids: Some(key_ids(master_keys)),
+            slave_tsig_key_ids: Some(key_ids(slave_keys)),
+            ..Default::default()
+        };
+        self.update(zone_id, settings).await
+    }
+
     /// Patches zone, by assigning new rrsets to this zone.
-    pub async fn patch(&self, zone_id: &str, zone: PatchZone) -> Result<(), Error> {
+    ///
+    /// The patch is checked with [`PatchZone::validate`] first, failing with
+    /// [`Error::InvalidPatch`] without sending it when it has problems.
+    pub async fn patch(&self, zone_id: impl IntoZoneId, zone: PatchZone) -> Result<(), Error> {
+        zone.validate()?;
+        self.patch_unchecked(zone_id, zone).await
+    }
+
+    /// Like [`ZoneClient::patch`], but only when the serial of the zone is
+    /// still `expected_serial`, e.g. the serial of the zone the changes were
+    /// based on. Fails with [`Error::SerialMismatch`] without sending the
+    /// patch when the zone was changed in the meantime.
+    ///
+    /// The serial is fetched without the rrsets of the zone. The check is not
+    /// atomic with the patch, so a change made between both is not detected.
+    pub async fn patch_if_serial(
+        &self,
+        zone_id: impl IntoZoneId,
+        expected_serial: u32,
+        zone: PatchZone,
+    ) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        zone.validate()?;
+
+        let options = GetZoneOptions {
+            rrsets: Some(false),
+            ..Default::default()
+        };
+        let current = self.get_with(&zone_id, &options).await?.serial;
+        if current != Some(expected_serial) {
+            return Err(Error::SerialMismatch {
+                zone_id: zone_id.into(),
+                expected: expected_serial,
+                current,
+            });
+        }
+        self.patch_unchecked(&zone_id, zone).await
+    }
+
+    /// Like [`ZoneClient::patch`], but without validating the patch, e.g.
+    /// when the server accepts something the validation does not
+    pub async fn patch_unchecked(
+        &self,
+        zone_id: impl IntoZoneId,
+        zone: PatchZone,
+    ) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
         let response = self
             .api_client
-            .http_client
             .patch(
-                format!("{}/api/v1/servers/{}/zones/{zone_id}",
-                        self.api_client.base_url,
+                format!("{}/servers/{}/zones/{zone_id}",
+                        self.api_client.api_url,
                         self.api_client.server_name,
                 ))
             .json(&zone)
@@ -236,39 +1378,423 @@ impl<'a> ZoneClient<'a> {
             // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
             // 500 Internal Server Error – Internal server error Returns: Error object
 
-            StatusCode::NO_CONTENT => Ok(()),
-            StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND |
-            StatusCode::UNPROCESSABLE_ENTITY | StatusCode::INTERNAL_SERVER_ERROR => {
-                Err(Error::PowerDNS(response.json().await?))
+            StatusCode::NO_CONTENT => {
+                if self.api_client.has_observers() {
+                    for update in zone.rrsets {
+                        self.api_client.emit(&zone_id, update.into_change());
+                    }
+                }
+                Ok(())
             },
-            status => Err(Error::UnexpectedStatusCode(status)),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Send a DNS NOTIFY to all slaves.
+    ///
+    /// Fails when zone kind is not Master or Slave, or master and slave are
+    /// disabled in the configuration. Only works for Slave if renotify is on.
+    pub async fn notify(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let response = self
+            .api_client
+            .put(
+                format!("{}/servers/{}/zones/{zone_id}/notify",
+                        self.api_client.api_url,
+                        self.api_client.server_name,
+                ))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – OK
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Retrieve slave zone from its master.
+    ///
+    /// Fails when zone kind is not Slave, or slave is disabled in the
+    /// configuration. Clients MUST NOT send a body.
+    pub async fn axfr_retrieve(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let response = self
+            .api_client
+            .put(
+                format!("{}/servers/{}/zones/{zone_id}/axfr-retrieve",
+                        self.api_client.api_url,
+                        self.api_client.server_name,
+                ))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – OK
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Returns the zone in AXFR format.
+    pub async fn export(&self, zone_id: impl IntoZoneId) -> Result<String, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let response = self
+            .api_client
+            .get(
+                format!("{}/servers/{}/zones/{zone_id}/export",
+                        self.api_client.api_url,
+                        self.api_client.server_name,
+                ))
+            .header(header::ACCEPT, header::HeaderValue::from_static("text/plain"))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – OK Returns: the zone in AXFR format
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(response.text().await?),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// Rectify the zone data.
+    ///
+    /// This does not take into account the API-RECTIFY metadata. Fails on
+    /// slave zones and zones that do not have DNSSEC.
+    pub async fn rectify(&self, zone_id: impl IntoZoneId) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let response = self
+            .api_client
+            .put(
+                format!("{}/servers/{}/zones/{zone_id}/rectify",
+                        self.api_client.api_url,
+                        self.api_client.server_name,
+                ))
+            .send()
+            .await?;
+
+        match response.status() {
+            // 200 OK – OK
+            // 400 Bad Request – The supplied request was not valid Returns: Error object
+            // 404 Not Found – Requested item was not found Returns: Error object
+            // 422 Unprocessable Entity – The input to the operation was not valid Returns: Error object
+            // 500 Internal Server Error – Internal server error Returns: Error object
+
+            StatusCode::OK => Ok(()),
+            _ => Err(Error::from_response(response).await),
+        }
+    }
+
+    /// The RRSet with the given name and type, if it exists. Only that RRSet
+    /// is requested from the server.
+    pub async fn get_rrset(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+    ) -> Result<Option<RRSet>, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        self.find_rrset(&zone_id, &absolute_name(name), &rtype).await
+    }
+
+    /// Replace the RRSet with the given name and type with one record per
+    /// entry of `contents`, creating it if it does not exist. Use
+    /// [`ZoneClient::delete_rrset`] to remove all records.
+    pub async fn replace_rrset(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+        ttl: u32,
+        contents: &[&str],
+    ) -> Result<(), Error> {
+        let rrset = RRSetUpdate::Replace(ReplaceRRSet {
+            name: absolute_name(name),
+            rtype,
+            ttl,
+            records: contents.iter().map(|content| Record::new(content)).collect(),
+            comments: None,
+        });
+        self.patch(zone_id, PatchZone { rrsets: vec![rrset] }).await
+    }
+
+    /// Delete the RRSet with the given name and type, including its
+    /// comments. Deleting an RRSet that does not exist succeeds.
+    pub async fn delete_rrset(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+    ) -> Result<(), Error> {
+        let rrset = RRSetUpdate::delete(&absolute_name(name), rtype);
+        self.patch(zone_id, PatchZone { rrsets: vec![rrset] }).await
+    }
+
+    /// Add a record to the RRSet with the given name and type, keeping the
+    /// records already in it, and set the TTL of the RRSet to `ttl`. The
+    /// RRSet is created if it does not exist yet.
+    pub async fn upsert_record(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+        ttl: u32,
+        content: &str,
+    ) -> Result<(), Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let name = absolute_name(name);
+
+        let mut records = self
+            .find_rrset(&zone_id, &name, &rtype)
+            .await?
+            .map(|rrset| rrset.records)
+            .unwrap_or_default();
+        if !records.iter().any(|record| record.content == content) {
+            records.push(Record::new(content));
+        }
+
+        let rrset = RRSetUpdate::Replace(ReplaceRRSet {
+            name,
+            rtype,
+            ttl,
+            records,
+            comments: None,
+        });
+        self.patch(&zone_id, PatchZone { rrsets: vec![rrset] }).await
+    }
+
+    /// Remove the record with the given content from the RRSet with the given
+    /// name and type, keeping the other records in it. The RRSet is deleted
+    /// when no records are left. Returns whether a record was removed.
+    pub async fn remove_record(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+        content: &str,
+    ) -> Result<bool, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let name = absolute_name(name);
+
+        let Some(mut rrset) = self.find_rrset(&zone_id, &name, &rtype).await? else {
+            return Ok(false);
+        };
+        let count = rrset.records.len();
+        rrset.records.retain(|record| record.content != content);
+        if rrset.records.len() == count {
+            return Ok(false);
+        }
+
+        let update = if rrset.records.is_empty() {
+            RRSetUpdate::delete(&name, rtype)
+        } else {
+            // leave the comments of the RRSet untouched
+            rrset.comments = None;
+            RRSetUpdate::replace(rrset)
+        };
+        self.patch(&zone_id, PatchZone { rrsets: vec![update] }).await?;
+        Ok(true)
+    }
+
+    /// Disable or enable the record with the given content in the RRSet with
+    /// the given name and type, keeping it in the zone. The zone is only
+    /// patched when the flag changes. Returns whether the record exists.
+    pub async fn set_record_disabled(
+        &self,
+        zone_id: impl IntoZoneId,
+        name: &str,
+        rtype: RecordType,
+        content: &str,
+        disabled: bool,
+    ) -> Result<bool, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        let name = absolute_name(name);
+
+        let Some(mut rrset) = self.find_rrset(&zone_id, &name, &rtype).await? else {
+            return Ok(false);
+        };
+        let Some(record) = rrset.records.iter_mut().find(|record| record.content == content) else {
+            return Ok(false);
+        };
+        if record.disabled.unwrap_or(false) == disabled {
+            return Ok(true);
+        }
+        record.disabled = Some(disabled);
+
+        // leave the comments of the RRSet untouched
+        rrset.comments = None;
+        let update = RRSetUpdate::replace(rrset);
+        self.patch(&zone_id, PatchZone { rrsets: vec![update] }).await?;
+        Ok(true)
+    }
+
+    /// Make the RRSet with the name and type of `desired` match it, patching
+    /// the zone only if the TTL, records or comments differ from the current
+    /// state. An RRSet without records is deleted. Returns whether the zone
+    /// was changed.
+    ///
+    /// The comments are left untouched when `desired` does not set them.
+    pub async fn ensure_rrset(
+        &self,
+        zone_id: impl IntoZoneId,
+        mut desired: RRSet,
+    ) -> Result<bool, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+        desired.name = absolute_name(&desired.name);
+
+        let current = self
+            .find_rrset(&zone_id, &desired.name, &desired.type_field)
+            .await?;
+        let update = match current {
+            None if desired.records.is_empty() => return Ok(false),
+            Some(current) if current.matches(&desired) => return Ok(false),
+            Some(_) if desired.records.is_empty() => {
+                RRSetUpdate::delete(&desired.name, desired.type_field)
+            }
+            _ => RRSetUpdate::replace(desired),
+        };
+        self.patch(&zone_id, PatchZone { rrsets: vec![update] }).await?;
+        Ok(true)
+    }
+
+    /// Reconcile the rrsets of a zone with `desired`, applying the minimal
+    /// set of changes in a single PATCH. RRSets not in `desired` are only
+    /// deleted when [`ApplyOptions::prune`] is set. Returns the changes that
+    /// were applied, which are empty when the zone was already up to date.
+    pub async fn apply(
+        &self,
+        zone_id: impl IntoZoneId,
+        desired: Vec<RRSet>,
+        options: ApplyOptions,
+    ) -> Result<ZoneDiff, Error> {
+        let zone_id = zone_id.into_zone_id()?;
+
+        let current: Zone = self.fetch(&zone_id, &[]).await?;
+        let current = current.rrsets.unwrap_or_default();
+        let mut diff = ZoneDiff::from_rrsets(&current, &desired);
+        diff.restrict(&options);
+        if !diff.is_empty() {
+            self.patch(&zone_id, diff.clone().into_patch()).await?;
         }
+        Ok(diff)
+    }
+
+    /// The RRSet with the given name and type in a zone, if it exists. Only
+    /// that RRSet is requested from servers supporting it.
+    async fn find_rrset(
+        &self,
+        zone_id: &str,
+        name: &str,
+        rtype: &RecordType,
+    ) -> Result<Option<RRSet>, Error> {
+        let query = [("rrset_name", name), ("rrset_type", rtype.as_str())];
+        let zone: Zone = self.fetch(zone_id, &query).await?;
+        Ok(zone
+            .rrsets
+            .unwrap_or_default()
+            .into_iter()
+            .find(|rrset| rrset.name == name && rrset.type_field == *rtype))
     }
 }
 
-/// Ensure a domain is canonical and top-level
-fn canonicalize_domain(domain: &str) -> Result<String, ()> {
-    let parsed = match parse_domain_name(domain) {
-        Ok(p) => p,
-        Err(_) => return Err(()),
-    };
+/// A record name as returned by the server: lowercase with a trailing dot
+pub(crate) fn absolute_name(name: &str) -> String {
+    let name = name.to_ascii_lowercase();
+    if name.ends_with('.') {
+        name
+    } else {
+        name + "."
+    }
+}
+
+/// Returned when a domain name cannot be canonicalized
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DomainError {
+    #[error("domain name is empty")]
+    Empty,
+
+    #[error("domain name contains an empty label")]
+    EmptyLabel,
+
+    #[error("domain name could not be parsed: {0}")]
+    Unparseable(String),
+
+    #[error("domain name does not end in a known public suffix")]
+    UnknownSuffix,
+
+    #[error("domain name is longer than 253 characters")]
+    TooLong,
+
+    #[error("domain name label is longer than 63 characters: {0}")]
+    LabelTooLong(String),
+
+    #[error("domain name contains an invalid character: {0:?}")]
+    InvalidCharacter(char),
+}
+
+/// Ensure a domain is canonical: syntactically valid and with a trailing
+/// dot. Domains in private TLDs like `corp.internal.` are accepted, see
+/// [`canonicalize_public_domain`] to reject them.
+pub fn canonicalize_domain(domain: &str) -> Result<String, DomainError> {
+    canonicalize(domain, false)
+}
 
-    let mut root = parsed.as_str().to_string();
+/// Like [`canonicalize_domain`], but also require the domain to end in a
+/// known public suffix
+pub fn canonicalize_public_domain(domain: &str) -> Result<String, DomainError> {
+    canonicalize(domain, true)
+}
+
+fn canonicalize(domain: &str, public_suffix: bool) -> Result<String, DomainError> {
+    let name = domain.strip_suffix('.').unwrap_or(domain);
+    if name.is_empty() {
+        return Err(DomainError::Empty);
+    }
+    if name.split('.').any(str::is_empty) {
+        return Err(DomainError::EmptyLabel);
+    }
 
-    if !parsed.has_known_suffix() {
-        return Err(());
+    // the labels are checked like owner names, as zones may have service
+    // labels like `_msdcs` or be classless reverse zones like `0/25`, which
+    // a hostname parser rejects. Only wildcards cannot be zones.
+    let root = DomainName::try_from(domain)?;
+    if root.split('.').any(|label| label.contains('*')) {
+        return Err(DomainError::InvalidCharacter('*'));
     }
 
-    if !root.ends_with('.') {
-        root += ".";
+    if public_suffix {
+        let parsed = parse_dns_name(&root).map_err(|e| DomainError::Unparseable(e.to_string()))?;
+        if !parsed.has_known_suffix() {
+            return Err(DomainError::UnknownSuffix);
+        }
     }
 
-    Ok(root)
+    Ok(root.into())
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
-    use crate::zones::canonicalize_domain;
+    use crate::record_type::RecordType;
+    use crate::zones::{
+        canonicalize_domain, canonicalize_public_domain, ChangeType, DomainError, PatchZone, RRSet,
+        RRSetUpdate, Record, Zone, ZoneBuilderError, ZoneKind,
+    };
 
     #[test]
     fn already_canonical() {
@@ -287,4 +1813,702 @@ mod tests {
         let root = canonicalize_domain("doc.powerdns.com").unwrap();
         assert_eq!(root, "doc.powerdns.com.")
     }
+
+    #[test]
+    fn create_payload_omits_unset_fields() {
+        let zone = Zone {
+            name: Some(String::from("powerdns.com.")),
+            kind: Some(ZoneKind::Native),
+            nameservers: Some(vec![String::from("ns1.powerdns.com.")]),
+            ..Default::default()
+        };
+        let payload = serde_json::to_value(&zone).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "name": "powerdns.com.",
+                "kind": "Native",
+                "nameservers": ["ns1.powerdns.com."],
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn keep_unknown_fields() {
+        let json = serde_json::json!({
+            "name": "powerdns.com.",
+            "kind": "Native",
+            "new_setting": {"enabled": true},
+            "rrsets": [{
+                "name": "powerdns.com.",
+                "type": "A",
+                "ttl": 300,
+                "records": [],
+                "new_flag": 1
+            }]
+        });
+        let zone: Zone = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(zone.extra["new_setting"], serde_json::json!({"enabled": true}));
+        assert_eq!(zone.rrsets.as_ref().unwrap()[0].extra["new_flag"], 1);
+        assert_eq!(serde_json::to_value(&zone).unwrap(), json);
+    }
+
+    #[test]
+    #[cfg(feature = "strict")]
+    fn reject_unknown_fields() {
+        let json = serde_json::json!({"name": "powerdns.com.", "new_setting": true});
+        assert!(serde_json::from_value::<Zone>(json).is_err());
+    }
+
+    #[test]
+    fn construct_rrset() {
+        let rrset = RRSet::new(
+            "www.powerdns.com.",
+            RecordType::A,
+            300,
+            vec![Record::new("192.0.2.1")],
+        );
+        assert_eq!(
+            serde_json::to_value(&rrset).unwrap(),
+            serde_json::json!({
+                "name": "www.powerdns.com.",
+                "type": "A",
+                "ttl": 300,
+                "records": [{"content": "192.0.2.1"}],
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_catalog_member() {
+        let zone: Zone = serde_json::from_value(serde_json::json!({
+            "name": "catalog.powerdns.com.",
+            "kind": "Producer",
+            "catalog": ""
+        }))
+        .unwrap();
+        assert_eq!(zone.kind, Some(ZoneKind::Producer));
+        assert_eq!(zone.catalog, Some(String::new()));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn changetype_from_string() {
+        let mut rrset = RRSet {
+            name: String::from("www.powerdns.com."),
+            type_field: RecordType::A,
+            ttl: 3600,
+            changetype: None,
+            records: vec![],
+            comments: None,
+            extra: Default::default(),
+        };
+        rrset.set_changetype("Replace ").unwrap();
+        assert_eq!(rrset.changetype, Some(ChangeType::Replace));
+        assert_eq!(serde_json::to_value(&rrset).unwrap()["changetype"], "REPLACE");
+        assert_eq!(rrset.set_changetype("UPSERT"), Err(String::from("UPSERT")));
+    }
+
+    #[test]
+    fn build_master_zone() {
+        let zone = Zone::builder("powerdns.com.")
+            .kind(ZoneKind::Master)
+            .nameservers(["ns1.powerdns.com.", "ns2.powerdns.com."])
+            .build()
+            .unwrap();
+        assert_eq!(zone.nameservers.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn build_invalid_zones() {
+        assert_eq!(
+            Zone::builder("powerdns.com").kind(ZoneKind::Native).build(),
+            Err(ZoneBuilderError::MissingTrailingDot(String::from("powerdns.com")))
+        );
+        assert_eq!(
+            Zone::builder("powerdns.com.").kind(ZoneKind::Master).build(),
+            Err(ZoneBuilderError::NoNameservers(ZoneKind::Master))
+        );
+        assert_eq!(
+            Zone::builder("powerdns.com.").kind(ZoneKind::Slave).build(),
+            Err(ZoneBuilderError::NoMasters(ZoneKind::Slave))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_invalid_domain() {
+        let client = crate::Client::builder()
+            .base_url("http://127.0.0.1:9")
+            .server_name("localhost")
+            .api_key("secret")
+            .build()
+            .unwrap();
+
+        let zone = client.zone().get("powerdns..com").await;
+
+        assert!(matches!(
+            zone,
+            Err(crate::Error::InvalidDomain { domain, source: DomainError::EmptyLabel })
+                if domain == "powerdns..com"
+        ));
+    }
+
+    #[test]
+    fn invalid_domains() {
+        assert_eq!(canonicalize_domain(""), Err(DomainError::Empty));
+        assert_eq!(canonicalize_domain("."), Err(DomainError::Empty));
+        assert_eq!(canonicalize_domain("doc..powerdns.com"), Err(DomainError::EmptyLabel));
+        assert_eq!(canonicalize_public_domain("powerdns.invalidtld"), Err(DomainError::UnknownSuffix));
+    }
+
+    #[test]
+    fn normalize_patch_names() {
+        use crate::zones::{PatchZone, RRSetUpdate};
+
+        let mut patch = PatchZone {
+            rrsets: vec![RRSetUpdate::delete("WWW.example.com", RecordType::A)],
+        };
+        patch.normalize_names();
+        assert_eq!(patch.rrsets[0].name(), "www.example.com.");
+    }
+
+    #[tokio::test]
+    async fn patch_canonicalizes_zone_id() {
+        use crate::zones::{PatchZone, RRSetUpdate};
+
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        client.zone().create(zone).await.unwrap();
+
+        let patch = PatchZone {
+            rrsets: vec![RRSetUpdate::delete("www.example.com.", RecordType::A)],
+        };
+        client.zone().patch("example.com", patch).await.unwrap();
+        assert!(matches!(
+            client.zone().rectify("example..com").await,
+            Err(crate::Error::InvalidDomain { .. })
+        ));
+    }
+
+    #[test]
+    fn private_tlds() {
+        assert_eq!(canonicalize_domain("corp.internal").unwrap(), "corp.internal.");
+        assert_eq!(canonicalize_domain("lab.lan.").unwrap(), "lab.lan.");
+        assert_eq!(canonicalize_public_domain("powerdns.com").unwrap(), "powerdns.com.");
+    }
+
+    #[test]
+    fn service_and_classless_zones() {
+        assert_eq!(canonicalize_domain("_msdcs.example.com").unwrap(), "_msdcs.example.com.");
+        assert_eq!(
+            canonicalize_domain("0/25.2.0.192.in-addr.arpa").unwrap(),
+            "0/25.2.0.192.in-addr.arpa."
+        );
+        assert_eq!(
+            canonicalize_public_domain("_acme-challenge.example.com.").unwrap(),
+            "_acme-challenge.example.com."
+        );
+        assert_eq!(canonicalize_domain("*.example.com"), Err(DomainError::InvalidCharacter('*')));
+    }
+
+    #[tokio::test]
+    async fn single_rrset() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        zones.create(zone).await.unwrap();
+
+        let rrset = zones.get_rrset("example.com", "www.example.com", RecordType::A).await;
+        assert_eq!(rrset.unwrap(), None);
+
+        let contents = ["192.0.2.1", "192.0.2.2"];
+        zones
+            .replace_rrset("example.com", "www.example.com", RecordType::A, 300, &contents)
+            .await
+            .unwrap();
+        let rrset = zones
+            .get_rrset("example.com", "www.example.com", RecordType::A)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rrset.ttl, 300);
+        assert_eq!(rrset.records.len(), 2);
+
+        zones
+            .delete_rrset("example.com", "www.example.com", RecordType::A)
+            .await
+            .unwrap();
+        assert_eq!(server.zone("example.com.").unwrap().rrsets, Some(vec![]));
+    }
+
+    #[tokio::test]
+    async fn get_with_options() {
+        use crate::zones::{GetZoneOptions, Record};
+
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .build()
+            .unwrap();
+        zones.create(zone).await.unwrap();
+        let rrsets = [
+            ("www.example.com.", RecordType::A, "192.0.2.1"),
+            ("www.example.com.", RecordType::AAAA, "2001:db8::1"),
+            ("ftp.example.com.", RecordType::A, "192.0.2.2"),
+        ];
+        for (name, rtype, content) in rrsets {
+            let rrset = RRSet::new(name, rtype, 300, vec![Record::new(content)]);
+            assert!(zones.ensure_rrset("example.com", rrset).await.unwrap());
+        }
+
+        let options = GetZoneOptions {
+            rrsets: Some(false),
+            ..Default::default()
+        };
+        let zone = zones.get_with("example.com", &options).await.unwrap();
+        assert_eq!(zone.rrsets, None);
+
+        let options = GetZoneOptions {
+            rrset_name: Some(String::from("WWW.example.com")),
+            ..Default::default()
+        };
+        let zone = zones.get_with("example.com", &options).await.unwrap();
+        assert_eq!(zone.rrsets.unwrap().len(), 2);
+
+        let options = GetZoneOptions {
+            rrset_name: Some(String::from("www.example.com.")),
+            rrset_type: Some(RecordType::AAAA),
+            ..Default::default()
+        };
+        let rrsets = zones.get_with("example.com", &options).await.unwrap().rrsets.unwrap();
+        assert_eq!(rrsets.len(), 1);
+        assert_eq!(rrsets[0].type_field, RecordType::AAAA);
+    }
+
+    #[tokio::test]
+    async fn list_stream() {
+        use futures_util::TryStreamExt;
+
+        let server = crate::mock::MockServer::start();
+        for name in ["a.example.", "b.example.", "c.example."] {
+            server.insert_zone(Zone {
+                name: Some(name.to_string()),
+                kind: Some(ZoneKind::Native),
+                ..Default::default()
+            });
+        }
+        let client = server.client();
+
+        let zones: Vec<Zone> = client.zone().list_stream(None).try_collect().await.unwrap();
+        assert_eq!(zones.len(), 3);
+
+        let zones: Vec<Zone> = client
+            .zone()
+            .list_stream(Some("B.Example"))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(zones.len(), 1);
+        assert_eq!(zones[0].name.as_deref(), Some("b.example."));
+
+        let invalid: Result<Vec<Zone>, _> = client.zone().list_stream(Some("b example.")).try_collect().await;
+        assert!(matches!(invalid, Err(crate::Error::InvalidDomain { .. })));
+    }
+
+    #[tokio::test]
+    async fn get_many() {
+        let server = crate::mock::MockServer::start();
+        server.insert_zone(Zone {
+            name: Some(String::from("example.com.")),
+            ..Default::default()
+        });
+        let client = server.client();
+
+        let zones = client
+            .zone()
+            .get_many(["example.com.", "example.org."], 2)
+            .await;
+        assert_eq!(zones.len(), 2);
+        assert!(zones["example.com."].is_ok());
+        assert!(matches!(zones["example.org."], Err(crate::Error::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn get_if_changed() {
+        use crate::zones::{PatchZone, RRSetUpdate, Record, ReplaceRRSet};
+
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        let serial = zones.create(zone).await.unwrap().serial.unwrap();
+
+        assert_eq!(zones.get_if_changed("example.com", serial).await.unwrap(), None);
+        let cached = zones.get_cached("example.com").await.unwrap();
+        assert_eq!(zones.get_cached("example.com").await.unwrap(), cached);
+
+        let patch = PatchZone {
+            rrsets: vec![RRSetUpdate::Replace(ReplaceRRSet {
+                name: String::from("www.example.com."),
+                rtype: RecordType::A,
+                ttl: 300,
+                records: vec![Record {
+                    content: String::from("192.0.2.1"),
+                    disabled: None,
+                }],
+                comments: None,
+            })],
+        };
+        zones.patch("example.com.", patch).await.unwrap();
+
+        let zone = zones.get_if_changed("example.com", serial).await.unwrap();
+        assert_eq!(zone.unwrap().rrsets.unwrap().len(), 1);
+        assert_ne!(zones.get_cached("example.com").await.unwrap(), cached);
+    }
+
+    #[tokio::test]
+    async fn set_record_disabled() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        zones.create(zone).await.unwrap();
+        for content in ["192.0.2.1", "192.0.2.2"] {
+            zones
+                .upsert_record("example.com", "www.example.com", RecordType::A, 300, content)
+                .await
+                .unwrap();
+        }
+
+        assert!(zones
+            .set_record_disabled("example.com", "www.example.com", RecordType::A, "192.0.2.2", true)
+            .await
+            .unwrap());
+        assert!(!zones
+            .set_record_disabled("example.com", "www.example.com", RecordType::A, "192.0.2.3", true)
+            .await
+            .unwrap());
+
+        let rrsets = server.zone("example.com.").unwrap().rrsets.unwrap();
+        let disabled: Vec<_> = rrsets[0].records.iter().map(|record| record.disabled).collect();
+        assert_eq!(disabled, [None, Some(true)]);
+    }
+
+    #[tokio::test]
+    async fn list_filtered() {
+        use crate::zones::ZoneListFilter;
+
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        for (name, account) in [("example.com.", "alice"), ("example.org.", "bob")] {
+            let zone = Zone::builder(name).kind(ZoneKind::Native).account(account).build().unwrap();
+            zones.create(zone).await.unwrap();
+        }
+
+        let filter = ZoneListFilter {
+            account: Some(String::from("bob")),
+            dnssec: Some(false),
+            ..Default::default()
+        };
+        let listed = zones.list_filtered(&filter).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name.as_deref(), Some("example.org."));
+
+        let filter = ZoneListFilter {
+            zone: Some(String::from("example.com")),
+            ..Default::default()
+        };
+        let listed = zones.list_filtered(&filter).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].account.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn list_summaries_and_get_detail() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        zones.create(zone).await.unwrap();
+        zones
+            .upsert_record("example.com", "www.example.com", RecordType::A, 300, "192.0.2.1")
+            .await
+            .unwrap();
+
+        let summaries = zones.list_summaries().await.unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].name, "example.com.");
+        assert_eq!(summaries[0].kind, ZoneKind::Native);
+
+        let detail = zones.get_detail("example.com").await.unwrap();
+        assert_eq!(detail.summary.id, summaries[0].id);
+        assert_eq!(detail.rrsets.len(), 1);
+        assert_eq!(Zone::from(detail), zones.get("example.com").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn create_zone_and_patch_changes() {
+        use crate::zones::{CreateZone, PatchZone, RRSetUpdate, Record};
+
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let mut zone = CreateZone::new("example.com.", ZoneKind::Native);
+        zone.account = Some(String::from("alice"));
+        let created = zones.create_zone(&zone).await.unwrap();
+        assert_eq!(created.summary.name, "example.com.");
+        assert_eq!(created.summary.account.as_deref(), Some("alice"));
+
+        let www = RRSet {
+            name: String::from("www.example.com."),
+            type_field: RecordType::A,
+            ttl: 300,
+            changetype: None,
+            records: vec![Record {
+                content: String::from("192.0.2.1"),
+                disabled: None,
+            }],
+            comments: None,
+            extra: Default::default(),
+        };
+        let patch: PatchZone = [RRSetUpdate::replace(www)].into_iter().collect();
+        zones.patch("example.com.", patch).await.unwrap();
+        assert_eq!(zones.get_detail("example.com").await.unwrap().rrsets.len(), 1);
+
+        let patch = [RRSetUpdate::delete("www.example.com.", RecordType::A)].into_iter().collect();
+        zones.patch("example.com.", patch).await.unwrap();
+        assert!(zones.get_detail("example.com").await.unwrap().rrsets.is_empty());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn rrset_change_into_update() {
+        use crate::zones::{RRSetChange, RRSetUpdate};
+
+        let delete = RRSetChange::delete("www.example.com.", RecordType::A);
+        assert_eq!(
+            RRSetUpdate::from(delete),
+            RRSetUpdate::delete("www.example.com.", RecordType::A)
+        );
+    }
+
+    #[test]
+    fn comment_timestamps() {
+        use crate::zones::Comment;
+
+        let comment: Comment =
+            serde_json::from_str(r#"{"content": "beyond 2106", "account": "", "modified_at": 4500000000}"#)
+                .unwrap();
+        assert_eq!(comment.modified_at, Some(4_500_000_000));
+        #[cfg(feature = "chrono")]
+        assert_eq!(comment.modified_at_utc().unwrap().timestamp(), 4_500_000_000);
+
+        let comment: Comment = serde_json::from_str(r#"{"content": "new", "account": ""}"#).unwrap();
+        assert_eq!(comment, Comment::new("new", ""));
+        assert!(!serde_json::to_string(&comment).unwrap().contains("modified_at"));
+    }
+
+    #[tokio::test]
+    async fn wait_for_serial() {
+        use std::time::Duration;
+
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        let serial = zones.create(zone).await.unwrap().serial.unwrap();
+
+        let timeout = Duration::from_millis(50);
+        assert_eq!(zones.wait_for_serial("example.com", serial, timeout).await.unwrap(), serial);
+        let result = zones.wait_for_serial("example.com", serial + 1, timeout).await;
+        assert!(matches!(
+            result,
+            Err(crate::Error::SerialTimeout { current: Some(current), .. }) if current == serial
+        ));
+    }
+
+    #[tokio::test]
+    async fn patch_if_serial() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        let serial = zones.create(zone).await.unwrap().serial.unwrap();
+
+        let patch = || PatchZone { rrsets: vec![RRSetUpdate::delete("www.example.com.", RecordType::A)] };
+        zones.patch_if_serial("example.com", serial, patch()).await.unwrap();
+        let result = zones.patch_if_serial("example.com", serial, patch()).await;
+        assert!(matches!(
+            result,
+            Err(crate::Error::SerialMismatch { expected, current: Some(current), .. })
+                if expected == serial && current == serial + 1
+        ));
+    }
+
+    #[tokio::test]
+    async fn upsert_record() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        zones.create(zone).await.unwrap();
+
+        for content in ["192.0.2.1", "192.0.2.2", "192.0.2.1"] {
+            zones
+                .upsert_record("example.com", "WWW.example.com", RecordType::A, 60, content)
+                .await
+                .unwrap();
+        }
+
+        let rrsets = server.zone("example.com.").unwrap().rrsets.unwrap();
+        assert_eq!(rrsets.len(), 1);
+        assert_eq!(rrsets[0].name, "www.example.com.");
+        assert_eq!(rrsets[0].ttl, 60);
+        let content: Vec<&str> = rrsets[0].records.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(content, ["192.0.2.1", "192.0.2.2"]);
+    }
+
+    #[tokio::test]
+    async fn remove_record() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        zones.create(zone).await.unwrap();
+        for content in ["192.0.2.1", "192.0.2.2"] {
+            zones
+                .upsert_record("example.com", "www.example.com", RecordType::A, 60, content)
+                .await
+                .unwrap();
+        }
+
+        let remove = |content| {
+            zones.remove_record("example.com", "www.example.com", RecordType::A, content)
+        };
+        assert!(remove("192.0.2.1").await.unwrap());
+        assert!(!remove("192.0.2.1").await.unwrap());
+        let rrsets = server.zone("example.com.").unwrap().rrsets.unwrap();
+        assert_eq!(rrsets[0].records.len(), 1);
+
+        assert!(remove("192.0.2.2").await.unwrap());
+        assert_eq!(server.zone("example.com.").unwrap().rrsets, Some(vec![]));
+    }
+
+    #[tokio::test]
+    async fn ensure_rrset() {
+        use crate::zones::Record;
+
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.").kind(ZoneKind::Native).build().unwrap();
+        zones.create(zone).await.unwrap();
+
+        let mut desired = RRSet {
+            name: String::from("www.example.com."),
+            type_field: RecordType::A,
+            ttl: 300,
+            changetype: None,
+            records: ["192.0.2.1", "192.0.2.2"]
+                .map(|content| Record {
+                    content: content.to_string(),
+                    disabled: None,
+                })
+                .to_vec(),
+            comments: None,
+            extra: Default::default(),
+        };
+        assert!(zones.ensure_rrset("example.com", desired.clone()).await.unwrap());
+        desired.records.reverse();
+        assert!(!zones.ensure_rrset("example.com", desired.clone()).await.unwrap());
+
+        desired.ttl = 60;
+        assert!(zones.ensure_rrset("example.com", desired.clone()).await.unwrap());
+        assert_eq!(server.zone("example.com.").unwrap().rrsets.unwrap()[0].ttl, 60);
+
+        desired.records.clear();
+        assert!(zones.ensure_rrset("example.com", desired.clone()).await.unwrap());
+        assert!(!zones.ensure_rrset("example.com", desired).await.unwrap());
+        assert_eq!(server.zone("example.com.").unwrap().rrsets, Some(vec![]));
+    }
+
+    #[tokio::test]
+    async fn apply() {
+        use crate::diff::ApplyOptions;
+        use crate::zones::Record;
+
+        let rrset = |name: &str, content: &str| RRSet {
+            name: name.to_string(),
+            type_field: RecordType::A,
+            ttl: 300,
+            changetype: None,
+            records: vec![Record {
+                content: content.to_string(),
+                disabled: None,
+            }],
+            comments: None,
+            extra: Default::default(),
+        };
+
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zones = client.zone();
+        let zone = Zone::builder("example.com.")
+            .kind(ZoneKind::Native)
+            .rrset(rrset("unmanaged.example.com.", "192.0.2.9"))
+            .build()
+            .unwrap();
+        zones.create(zone).await.unwrap();
+
+        let desired = vec![
+            rrset("www.example.com.", "192.0.2.1"),
+            rrset("mail.example.com.", "192.0.2.2"),
+        ];
+        let diff = zones
+            .apply("example.com", desired.clone(), ApplyOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(diff.added.len(), 2);
+        assert!(diff.removed.is_empty());
+        assert_eq!(server.zone("example.com.").unwrap().rrsets.unwrap().len(), 3);
+
+        let prune = ApplyOptions {
+            prune: true,
+            ..Default::default()
+        };
+        let diff = zones.apply("example.com", desired.clone(), prune.clone()).await.unwrap();
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(server.zone("example.com.").unwrap().rrsets.unwrap().len(), 2);
+
+        let diff = zones.apply("example.com", desired, prune).await.unwrap();
+        assert!(diff.is_empty());
+    }
+
+    #[tokio::test]
+    async fn create_from_zonefile() {
+        let server = crate::mock::MockServer::start();
+        let client = server.client();
+        let zonefile = "$TTL 300
+@ IN SOA ns1 hostmaster 1 3600 600 86400 300
+  IN NS ns1
+ns1 IN A 192.0.2.1
+";
+
+        let zone = client
+            .zone()
+            .create_from_zonefile("example.com", ZoneKind::Native, zonefile)
+            .await
+            .unwrap();
+        assert_eq!(zone.name.as_deref(), Some("example.com."));
+        assert_eq!(zone.rrsets.unwrap().len(), 3);
+    }
 }